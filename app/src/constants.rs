@@ -4,6 +4,8 @@ pub struct Header {
 }
 
 pub const SERVER_ADDRESS: &str = "http://0.0.0.0:10380";
+pub const LOG_FILE: &str = "logs/app.log";
+pub const RECEIPTS_DIR: &str = "receipts";
 pub static CLIENT_HEADER: Header = Header {
     key: "X-Client-Id",
     value: "app",
@@ -15,8 +17,33 @@ pub const OPEN_PARKING_LOT_EVENT: &str = "open_parking_lot";
 pub const OPEN_FLOOR_EVENT: &str = "open_floor";
 pub const RESET_DATABASE_EVENT: &str = "reset_database";
 pub const PARKING_LOT_STATE_EVENT: &str = "parking_lot_state";
+pub const CLOCK_SKEW_EVENT: &str = "clock_skew";
+pub const BARRIER_FAULT_EVENT: &str = "barrier_fault";
+pub const SENSOR_REPORT_EVENT: &str = "sensor_report";
+pub const REQUEST_ANALYTICS_EVENT: &str = "request_analytics";
+pub const ANALYTICS_EVENT: &str = "analytics";
+pub const REQUEST_OCCUPANCY_HISTORY_EVENT: &str = "request_occupancy_history";
+pub const OCCUPANCY_HISTORY_EVENT: &str = "occupancy_history";
+pub const SET_SPOT_OUT_OF_SERVICE_EVENT: &str = "set_spot_out_of_service";
+pub const RETURN_SPOT_TO_SERVICE_EVENT: &str = "return_spot_to_service";
+pub const VEHICLE_OVERSTAYED_EVENT: &str = "vehicle_overstayed";
+pub const REQUEST_SUBSCRIBERS_EVENT: &str = "request_subscribers";
+pub const SUBSCRIBERS_EVENT: &str = "subscribers";
+pub const ADD_SUBSCRIBER_EVENT: &str = "add_subscriber";
+pub const REMOVE_SUBSCRIBER_EVENT: &str = "remove_subscriber";
+pub const IDENTIFY_VEHICLE_EVENT: &str = "identify_vehicle";
+pub const REQUEST_RECEIPT_EVENT: &str = "request_receipt";
+pub const RECEIPT_EVENT: &str = "receipt";
+pub const SET_RECEIPT_PAYMENT_EVENT: &str = "set_receipt_payment";
+pub const CLOSE_DAY_EVENT: &str = "close_day";
+pub const DAY_CLOSED_EVENT: &str = "day_closed";
+pub const REQUEST_CASH_CLOSINGS_EVENT: &str = "request_cash_closings";
+pub const CASH_CLOSINGS_EVENT: &str = "cash_closings";
+
+// Payment methods as the server names them, in the order offered to the operator
+pub const PAYMENT_METHODS: [&str; 4] = ["cash", "card", "pix", "subscription"];
 
 pub const DASHBOARD_POS: (u16, u16) = (1, 1);
 pub const DASHBOARD_INFO_COLUMN: u16 = 86;
 pub const MENU_POS: (u16, u16) = (1, 9);
-pub const FEEDBACK_POS: (u16, u16) = (1, 18);
+pub const FEEDBACK_POS: (u16, u16) = (1, 23);
@@ -6,20 +6,40 @@ mod models;
 mod operations;
 mod socket_client;
 
-use crate::models::ParkingLotDataPayload;
+use crate::{constants::LOG_FILE, models::ParkingLotDataPayload};
+use fse_trab_1_logging::{self as logging, LoggingConfig};
 use std::{
     io::{stdin, stdout},
     sync::{Arc, Mutex},
 };
 use termion::{event::Key, input::TermRead, raw::IntoRawMode};
+use tracing::info;
 
 fn main() {
+    // The terminal belongs to the dashboard, so the logs always go to a file
+    logging::init_off_terminal(&LoggingConfig::new(Some(LOG_FILE)).with_env_overrides());
+    info!("app started");
+
     let stdin = stdin();
     let stdout = Arc::new(Mutex::new(stdout().into_raw_mode().unwrap()));
 
     let parking_lot = Arc::new(Mutex::new(ParkingLotDataPayload::new()));
 
-    let client = socket_client::create(stdout.clone(), parking_lot.clone());
+    let analytics = Arc::new(Mutex::new(None));
+    let history = Arc::new(Mutex::new(None));
+    let subscribers = Arc::new(Mutex::new(None));
+    let receipt = Arc::new(Mutex::new(None));
+    let closings = Arc::new(Mutex::new(None));
+
+    let client = socket_client::create(
+        stdout.clone(),
+        parking_lot.clone(),
+        analytics.clone(),
+        history.clone(),
+        subscribers.clone(),
+        receipt.clone(),
+        closings.clone(),
+    );
 
     dashboard_pooling::set(stdout.clone(), parking_lot.clone());
     ctrlc_handler::set(client.clone(), stdout.clone());
@@ -50,9 +70,31 @@ fn main() {
             Key::Char('5') => {
                 operations::reset_database(&client, &stdout);
             }
+            Key::Char('6') => {
+                operations::statistics(&client, &stdout, &analytics);
+            }
+            Key::Char('7') => {
+                operations::occupancy_history(&client, &stdout, &history);
+            }
+            Key::Char('8') => {
+                operations::spot_maintenance(&client, &stdout, &parking_lot);
+            }
+            Key::Char('9') => {
+                operations::overstays(&stdout, &parking_lot);
+            }
+            Key::Char('a') => {
+                operations::subscribers(&client, &stdout, &parking_lot, &subscribers);
+            }
+            Key::Char('b') => {
+                operations::receipt(&client, &stdout, &parking_lot, &receipt);
+            }
+            Key::Char('c') => {
+                operations::cash_closings(&client, &stdout, &closings);
+            }
             _ => {}
         }
     }
 
     menus::finalize_console(&stdout);
+    info!("app finished");
 }
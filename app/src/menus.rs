@@ -1,7 +1,12 @@
 use crate::{
     constants::{DASHBOARD_INFO_COLUMN, DASHBOARD_POS, FEEDBACK_POS, MENU_POS},
-    models::{ParkingLotDataPayload, SpotDataPayload},
+    models::{
+        format_duration, AnalyticsPayload, CashClosingPayload, ForecastPayload,
+        OccupancyHistoryPayload, OccupancyPointPayload, ParkingLotDataPayload, SpotDataPayload,
+        SubscriberPayload,
+    },
 };
+use chrono::{DateTime, Local};
 use std::{
     io::{Stdout, Write},
     sync::{Arc, Mutex, MutexGuard},
@@ -50,6 +55,27 @@ pub fn main_menu(stdout: &Arc<Mutex<RawTerminal<Stdout>>>) {
     write!(stdout, "5. Resetar dados").unwrap();
     new_line(&mut stdout, &mut line);
 
+    write!(stdout, "6. Estatísticas").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "7. Histórico de ocupação").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "8. Manutenção de vagas").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "9. Permanência excessiva").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "a. Assinantes").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "b. Recibos").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "c. Fechamento de caixa").unwrap();
+    new_line(&mut stdout, &mut line);
+
     write!(stdout, "0. Sair").unwrap();
     new_line(&mut stdout, &mut line);
 
@@ -66,6 +92,29 @@ pub fn feedback(stdout: &Arc<Mutex<RawTerminal<Stdout>>>, message: &str) {
     stdout.flush().unwrap();
 }
 
+// Numbered options, starting at 1, plus the option to go back
+pub fn choice_menu(stdout: &Arc<Mutex<RawTerminal<Stdout>>>, title: &str, options: &[String]) {
+    let mut stdout = stdout.lock().unwrap();
+
+    write!(stdout, "{}", cursor::Goto(FEEDBACK_POS.0, FEEDBACK_POS.1)).unwrap();
+    write!(stdout, "{}", clear::AfterCursor).unwrap();
+
+    let mut line = FEEDBACK_POS.1;
+
+    write!(stdout, "{}", title).unwrap();
+    new_line(&mut stdout, &mut line);
+
+    for (index, option) in options.iter().enumerate() {
+        write!(stdout, "{}. {}", index + 1, option).unwrap();
+        new_line(&mut stdout, &mut line);
+    }
+
+    write!(stdout, "0. Voltar").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    stdout.flush().unwrap();
+}
+
 pub fn floor_menu(stdout: &Arc<Mutex<RawTerminal<Stdout>>>) {
     let mut stdout = stdout.lock().unwrap();
 
@@ -103,15 +152,15 @@ pub fn dashboard(
                   -----------------------------------------------------------------
     */
 
-    // Get the total of occupied spaces
-    let occupied_spots: u16 = parking_lot
+    // Get the total of available spaces, the ones out of service don't count
+    let available_spots: u16 = parking_lot
         .floors
         .iter()
         .map(|floor| {
             floor
                 .spots
                 .iter()
-                .filter(|spot| spot.parked_vehicle.is_some())
+                .filter(|spot| spot.is_available())
                 .count() as u16
         })
         .sum();
@@ -124,7 +173,7 @@ pub fn dashboard(
             floor
                 .spots
                 .iter()
-                .filter(|spot| spot.spot_type == 1 && spot.parked_vehicle.is_none())
+                .filter(|spot| spot.spot_type == 1 && spot.is_available())
                 .count() as u16
         })
         .sum();
@@ -136,7 +185,7 @@ pub fn dashboard(
             floor
                 .spots
                 .iter()
-                .filter(|spot| spot.spot_type == 2 && spot.parked_vehicle.is_none())
+                .filter(|spot| spot.spot_type == 2 && spot.is_available())
                 .count() as u16
         })
         .sum();
@@ -150,7 +199,7 @@ pub fn dashboard(
         "{}-----------------------------------------------------------------{}Vagas disponíveis: {}",
         cursor::Goto(DASHBOARD_POS.0 + 10, DASHBOARD_POS.1),
         cursor::Goto(DASHBOARD_INFO_COLUMN, DASHBOARD_POS.1),
-        24-occupied_spots,
+        available_spots,
     )
     .unwrap();
 
@@ -244,6 +293,10 @@ pub fn dashboard(
             format!("{:.2}", last_vehicle.fee()),
         )
         .unwrap();
+
+        if let Some(receipt_number) = last_vehicle.receipt_number {
+            write!(stdout, " (recibo nº {})", receipt_number).unwrap();
+        }
     }
 
     // Fifth line
@@ -262,9 +315,18 @@ pub fn dashboard(
     )
     .unwrap();
 
+    // The server only sends the exits of the last 24 hours. Subscribers are
+    // billed by contract, so their revenue is shown apart
+    let subscribers_revenue: f64 = parking_lot
+        .exited_vehicles
+        .iter()
+        .filter(|vehicle| vehicle.subscription.is_some())
+        .map(|vehicle| vehicle.fee())
+        .sum();
+
     write!(
         stdout,
-        "{}Total arrecadado: R${}",
+        "{}Total arrecadado em 24h: R${} (assinantes: R${})",
         cursor::Goto(DASHBOARD_INFO_COLUMN, DASHBOARD_POS.1 + 4),
         format!(
             "{:.2}",
@@ -273,7 +335,8 @@ pub fn dashboard(
                 .iter()
                 .map(|vehicle| vehicle.fee())
                 .sum::<f64>()
-        )
+        ),
+        format!("{:.2}", subscribers_revenue)
     )
     .unwrap();
 
@@ -305,6 +368,20 @@ pub fn dashboard(
     )
     .unwrap();
 
+    if let Some(forecast) = parking_lot.forecast.as_ref() {
+        write!(
+            stdout,
+            "{}Lotação prevista: {}",
+            cursor::Goto(DASHBOARD_INFO_COLUMN, DASHBOARD_POS.1 + 5),
+            match forecast.minutes_to_full {
+                Some(0) => "lotado".to_string(),
+                Some(minutes) => format!("em ~{} minutos", minutes),
+                None => "não nas próximas horas".to_string(),
+            }
+        )
+        .unwrap();
+    }
+
     // Seventh line
     write!(
         stdout,
@@ -321,19 +398,611 @@ pub fn dashboard(
     )
     .unwrap();
 
+    // Free spots expected in one hour, per floor
+    if let Some(forecast) = parking_lot.forecast.as_ref() {
+        write!(
+            stdout,
+            "{}Vagas livres em 1h: Terreo {} | 1° {} | 2° {}",
+            cursor::Goto(DASHBOARD_INFO_COLUMN, DASHBOARD_POS.1 + 6),
+            expected_available(forecast, 0),
+            expected_available(forecast, 1),
+            expected_available(forecast, 2),
+        )
+        .unwrap();
+    }
+
+    // Eighth line, only while a barrier is stopped
+    write!(
+        stdout,
+        "{}",
+        cursor::Goto(DASHBOARD_POS.0, DASHBOARD_POS.1 + 7)
+    )
+    .unwrap();
+    write!(stdout, "{}", clear::CurrentLine).unwrap();
+
+    if !parking_lot.barrier_faults.is_empty() {
+        let faults: Vec<String> = parking_lot
+            .barrier_faults
+            .iter()
+            .map(|fault| format!("{} ({})", fault.barrier_name(), fault.fault_description()))
+            .collect();
+
+        write!(
+            stdout,
+            "{}{}Cancelas com falha: {}{}",
+            cursor::Goto(DASHBOARD_POS.0 + 10, DASHBOARD_POS.1 + 7),
+            color::Fg(color::Rgb(255, 0, 0)),
+            faults.join(" | "),
+            color::Fg(color::Reset)
+        )
+        .unwrap();
+    }
+
+    stdout.flush().unwrap();
+}
+
+fn expected_available(forecast: &ForecastPayload, floor_number: i32) -> String {
+    forecast
+        .floors
+        .iter()
+        .find(|floor| floor.floor_number == floor_number)
+        .and_then(|floor| floor.expected_available.first())
+        .map(|available| available.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+pub fn statistics(stdout: &Arc<Mutex<RawTerminal<Stdout>>>, analytics: &AnalyticsPayload) {
+    let mut stdout = stdout.lock().unwrap();
+
+    write!(stdout, "{}", cursor::Goto(FEEDBACK_POS.0, FEEDBACK_POS.1)).unwrap();
+    write!(stdout, "{}", clear::AfterCursor).unwrap();
+
+    let mut line = FEEDBACK_POS.1;
+
+    write!(
+        stdout,
+        "Estatísticas de {} até {}",
+        format_time(analytics.from, "%d/%m/%Y %H:%M"),
+        format_time(analytics.to, "%d/%m/%Y %H:%M")
+    )
+    .unwrap();
+    new_line(&mut stdout, &mut line);
+    new_line(&mut stdout, &mut line);
+
+    write!(
+        stdout,
+        "Entradas: {}    Saídas: {}",
+        analytics.arrivals, analytics.departures
+    )
+    .unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(
+        stdout,
+        "Permanência média: {}    Mediana: {}",
+        format_minutes(analytics.average_dwell_minutes),
+        format_minutes(analytics.median_dwell_minutes)
+    )
+    .unwrap();
+    new_line(&mut stdout, &mut line);
+
+    // Turnover is the amount of vehicles each spot receives per day
+    write!(stdout, "Giro por dia:").unwrap();
+
+    for turnover in &analytics.spot_type_turnover {
+        write!(
+            stdout,
+            "  {} {:.2}",
+            spot_type_name(turnover.spot_type),
+            turnover.turnover_per_day
+        )
+        .unwrap();
+    }
+    new_line(&mut stdout, &mut line);
+
+    for floor_number in 0..3 {
+        write!(stdout, "  Andar {}:", floor_number).unwrap();
+
+        for turnover in analytics
+            .spot_turnover
+            .iter()
+            .filter(|turnover| turnover.floor_number == floor_number)
+        {
+            write!(stdout, " {:>5.2}", turnover.turnover_per_day).unwrap();
+        }
+        new_line(&mut stdout, &mut line);
+    }
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "Horário     ").unwrap();
+    for hour in 0..24 {
+        write!(stdout, "{:>3}", hour).unwrap();
+    }
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "Entradas    ").unwrap();
+    write_histogram(&mut stdout, &analytics.hourly_arrivals);
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "Saídas      ").unwrap();
+    write_histogram(&mut stdout, &analytics.hourly_departures);
+    new_line(&mut stdout, &mut line);
+    new_line(&mut stdout, &mut line);
+
+    // Only the last week fits on the screen
+    write!(stdout, "Pico de ocupação:").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    let skip = analytics.daily_peak_occupancy.len().saturating_sub(7);
+
+    for peak in analytics.daily_peak_occupancy.iter().skip(skip) {
+        write!(
+            stdout,
+            "  {}: {} vagas às {}",
+            format_time(peak.peak_at, "%d/%m"),
+            peak.peak_occupancy,
+            format_time(peak.peak_at, "%H:%M")
+        )
+        .unwrap();
+        new_line(&mut stdout, &mut line);
+    }
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "0. Voltar").unwrap();
+
     stdout.flush().unwrap();
 }
 
+pub fn occupancy_history(
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    history: &OccupancyHistoryPayload,
+) {
+    let mut stdout = stdout.lock().unwrap();
+
+    write!(stdout, "{}", cursor::Goto(FEEDBACK_POS.0, FEEDBACK_POS.1)).unwrap();
+    write!(stdout, "{}", clear::AfterCursor).unwrap();
+
+    let mut line = FEEDBACK_POS.1;
+
+    write!(stdout, "Ocupação nas últimas 24 horas").unwrap();
+    new_line(&mut stdout, &mut line);
+    new_line(&mut stdout, &mut line);
+
+    // One column per hour, the last one is the current hour
+    let end = (history.to / 3600 + 1) * 3600;
+    let hours: Vec<i64> = (0..24).map(|hour| end - (24 - hour) * 3600).collect();
+
+    write!(stdout, "Horário     ").unwrap();
+    for hour in &hours {
+        write!(stdout, "{:>3}", format_time(*hour, "%H")).unwrap();
+    }
+    new_line(&mut stdout, &mut line);
+
+    let capacity = history
+        .points
+        .last()
+        .map(|point| point.capacity as f64)
+        .unwrap_or(24.0);
+
+    write!(stdout, "Total       ").unwrap();
+    let values = hourly_average(history, &hours, |point| Some(point.occupied));
+    write_bars(&mut stdout, &values, capacity);
+    new_line(&mut stdout, &mut line);
+
+    for floor_number in 0..3 {
+        write!(stdout, "Andar {}     ", floor_number).unwrap();
+
+        let values = hourly_average(history, &hours, |point| {
+            point
+                .floors
+                .iter()
+                .find(|floor| floor.floor_number == floor_number)
+                .map(|floor| floor.occupied)
+        });
+        write_bars(&mut stdout, &values, 8.0);
+        new_line(&mut stdout, &mut line);
+    }
+    new_line(&mut stdout, &mut line);
+
+    let peak = history
+        .points
+        .iter()
+        .max_by(|a, b| a.occupied.total_cmp(&b.occupied));
+
+    match peak {
+        Some(peak) => write!(
+            stdout,
+            "Ocupação máxima: {:.0} de {} vagas às {}",
+            peak.occupied,
+            peak.capacity,
+            format_time(peak.timestamp, "%H:%M")
+        )
+        .unwrap(),
+        None => write!(stdout, "Nenhum registro de ocupação no período").unwrap(),
+    }
+    new_line(&mut stdout, &mut line);
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "0. Voltar").unwrap();
+
+    stdout.flush().unwrap();
+}
+
+// Average of the points inside each hour, None for the hours without points
+fn hourly_average<F>(history: &OccupancyHistoryPayload, hours: &[i64], value: F) -> Vec<Option<f64>>
+where
+    F: Fn(&OccupancyPointPayload) -> Option<f64>,
+{
+    hours
+        .iter()
+        .map(|hour| {
+            let values: Vec<f64> = history
+                .points
+                .iter()
+                .filter(|point| point.timestamp >= *hour && point.timestamp < hour + 3600)
+                .filter_map(&value)
+                .collect();
+
+            match values.len() {
+                0 => None,
+                count => Some(values.iter().sum::<f64>() / count as f64),
+            }
+        })
+        .collect()
+}
+
+fn write_histogram(stdout: &mut MutexGuard<RawTerminal<Stdout>>, counts: &[usize]) {
+    let max = counts.iter().copied().max().unwrap_or(0) as f64;
+    let values: Vec<Option<f64>> = counts
+        .iter()
+        .map(|count| Some(*count as f64).filter(|count| *count > 0.0))
+        .collect();
+
+    write_bars(stdout, &values, max);
+}
+
+// One bar per value, scaled to max, missing values are left blank
+fn write_bars(stdout: &mut MutexGuard<RawTerminal<Stdout>>, values: &[Option<f64>], max: f64) {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    for value in values {
+        let bar = match value {
+            Some(value) if max > 0.0 => {
+                let index = (value / max * (BARS.len() - 1) as f64).round() as usize;
+                BARS[index.min(BARS.len() - 1)]
+            }
+            _ => ' ',
+        };
+
+        write!(stdout, "{:>3}", bar).unwrap();
+    }
+}
+
+fn format_minutes(minutes: Option<f64>) -> String {
+    match minutes {
+        Some(minutes) => format!("{:.0} min", minutes),
+        None => "-".to_string(),
+    }
+}
+
+pub fn overstays(stdout: &Arc<Mutex<RawTerminal<Stdout>>>, parking_lot: &ParkingLotDataPayload) {
+    let mut stdout = stdout.lock().unwrap();
+
+    write!(stdout, "{}", cursor::Goto(FEEDBACK_POS.0, FEEDBACK_POS.1)).unwrap();
+    write!(stdout, "{}", clear::AfterCursor).unwrap();
+
+    let mut line = FEEDBACK_POS.1;
+
+    write!(stdout, "Veículos com permanência excessiva").unwrap();
+    new_line(&mut stdout, &mut line);
+    new_line(&mut stdout, &mut line);
+
+    let mut found = false;
+
+    for (floor_number, floor) in parking_lot.floors.iter().enumerate() {
+        for (spot_number, spot) in floor.spots.iter().enumerate() {
+            let Some(vehicle) = &spot.parked_vehicle else {
+                continue;
+            };
+            let Some(overstay) = &vehicle.overstay else {
+                continue;
+            };
+
+            write!(
+                stdout,
+                "Vaga {}.{} ({})  entrada {}  há {} (limite {})  valor atual R$ {:.2}",
+                floor_number,
+                spot_number,
+                spot_type_name(spot.spot_type),
+                format_time(vehicle.entry_time, "%d/%m %H:%M"),
+                format_duration(vehicle.parked_minutes()),
+                format_duration(overstay.max_minutes),
+                vehicle.fee()
+            )
+            .unwrap();
+            new_line(&mut stdout, &mut line);
+
+            found = true;
+        }
+    }
+
+    if !found {
+        write!(stdout, "Nenhum veículo acima do limite.").unwrap();
+        new_line(&mut stdout, &mut line);
+    }
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "0. Voltar").unwrap();
+
+    stdout.flush().unwrap();
+}
+
+pub fn subscribers(stdout: &Arc<Mutex<RawTerminal<Stdout>>>, subscribers: &[SubscriberPayload]) {
+    let mut stdout = stdout.lock().unwrap();
+
+    write!(stdout, "{}", cursor::Goto(FEEDBACK_POS.0, FEEDBACK_POS.1)).unwrap();
+    write!(stdout, "{}", clear::AfterCursor).unwrap();
+
+    let mut line = FEEDBACK_POS.1;
+
+    write!(stdout, "Assinantes").unwrap();
+    new_line(&mut stdout, &mut line);
+    new_line(&mut stdout, &mut line);
+
+    for subscriber in subscribers {
+        let floors = if subscriber.allowed_floors.is_empty() {
+            "todos".to_string()
+        } else {
+            subscriber
+                .allowed_floors
+                .iter()
+                .map(|floor_number| floor_number.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        let reserved_spot = match &subscriber.reserved_spot {
+            Some(spot) => format!("{}.{}", spot.floor_number, spot.spot_number),
+            None => "-".to_string(),
+        };
+
+        let rate = if subscriber.rate_per_minute == 0.0 {
+            "isento".to_string()
+        } else {
+            format!("R$ {:.2}/min", subscriber.rate_per_minute)
+        };
+
+        write!(
+            stdout,
+            "{}  {}  até {}  andares: {}  vaga: {}  {}",
+            subscriber.credential,
+            subscriber.name,
+            format_time(subscriber.valid_until, "%d/%m/%Y"),
+            floors,
+            reserved_spot,
+            rate
+        )
+        .unwrap();
+        new_line(&mut stdout, &mut line);
+    }
+
+    if subscribers.is_empty() {
+        write!(stdout, "Nenhum assinante cadastrado.").unwrap();
+        new_line(&mut stdout, &mut line);
+    }
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "1. Cadastrar assinante").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "2. Remover assinante").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "3. Identificar veículo estacionado").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "0. Voltar").unwrap();
+
+    stdout.flush().unwrap();
+}
+
+pub fn receipt(stdout: &Arc<Mutex<RawTerminal<Stdout>>>, text: &str) {
+    let mut stdout = stdout.lock().unwrap();
+
+    write!(stdout, "{}", cursor::Goto(FEEDBACK_POS.0, FEEDBACK_POS.1)).unwrap();
+    write!(stdout, "{}", clear::AfterCursor).unwrap();
+
+    let mut line = FEEDBACK_POS.1;
+
+    for text_line in text.lines() {
+        write!(stdout, "{}", text_line).unwrap();
+        new_line(&mut stdout, &mut line);
+    }
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "1. Salvar para impressão (texto)").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "2. Exportar em JSON").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "3. Alterar forma de pagamento").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "0. Voltar").unwrap();
+
+    stdout.flush().unwrap();
+}
+
+pub fn cash_closings(stdout: &Arc<Mutex<RawTerminal<Stdout>>>, closings: &[CashClosingPayload]) {
+    let mut stdout = stdout.lock().unwrap();
+
+    write!(stdout, "{}", cursor::Goto(FEEDBACK_POS.0, FEEDBACK_POS.1)).unwrap();
+    write!(stdout, "{}", clear::AfterCursor).unwrap();
+
+    let mut line = FEEDBACK_POS.1;
+
+    write!(stdout, "Fechamentos de caixa").unwrap();
+    new_line(&mut stdout, &mut line);
+    new_line(&mut stdout, &mut line);
+
+    // The last 9 days, the ones that can be chosen with a single key
+    for closing in closings.iter().take(9) {
+        write!(
+            stdout,
+            "{}  {} veículos  R$ {:.2}  ainda no estacionamento: {}",
+            closing.date(),
+            closing.vehicles,
+            closing.total,
+            closing.vehicles_inside
+        )
+        .unwrap();
+        new_line(&mut stdout, &mut line);
+    }
+
+    if closings.is_empty() {
+        write!(stdout, "Nenhum dia fechado.").unwrap();
+        new_line(&mut stdout, &mut line);
+    }
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "1. Fechar o último dia").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "2. Ver fechamento").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "0. Voltar").unwrap();
+
+    stdout.flush().unwrap();
+}
+
+pub fn cash_closing(stdout: &Arc<Mutex<RawTerminal<Stdout>>>, closing: &CashClosingPayload) {
+    let mut stdout = stdout.lock().unwrap();
+
+    write!(stdout, "{}", cursor::Goto(FEEDBACK_POS.0, FEEDBACK_POS.1)).unwrap();
+    write!(stdout, "{}", clear::AfterCursor).unwrap();
+
+    let mut line = FEEDBACK_POS.1;
+
+    write!(stdout, "Fechamento de caixa de {}", closing.date()).unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(
+        stdout,
+        "Saídas de {} até {}, fechado em {}",
+        format_time(closing.starts_at, "%d/%m/%Y %H:%M"),
+        format_time(closing.ends_at, "%d/%m/%Y %H:%M"),
+        format_time(closing.closed_at, "%d/%m/%Y %H:%M")
+    )
+    .unwrap();
+    new_line(&mut stdout, &mut line);
+    new_line(&mut stdout, &mut line);
+
+    write!(
+        stdout,
+        "Total: R$ {:.2} ({} veículos)  Ainda no estacionamento: {}",
+        closing.total, closing.vehicles, closing.vehicles_inside
+    )
+    .unwrap();
+    new_line(&mut stdout, &mut line);
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "Por tipo de vaga:").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    for total in &closing.spot_types {
+        write!(
+            stdout,
+            "  {}: R$ {:.2} ({} veículos)",
+            spot_type_name(total.spot_type),
+            total.total,
+            total.vehicles
+        )
+        .unwrap();
+        new_line(&mut stdout, &mut line);
+    }
+
+    write!(stdout, "Por forma de pagamento:").unwrap();
+    new_line(&mut stdout, &mut line);
+
+    for total in &closing.payment_methods {
+        write!(
+            stdout,
+            "  {}: R$ {:.2} ({} veículos)",
+            payment_method_name(&total.payment_method),
+            total.total,
+            total.vehicles
+        )
+        .unwrap();
+        new_line(&mut stdout, &mut line);
+    }
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "0. Voltar").unwrap();
+
+    stdout.flush().unwrap();
+}
+
+pub fn payment_method_name(payment_method: &str) -> &str {
+    match payment_method {
+        "cash" => "Dinheiro",
+        "card" => "Cartão",
+        "pix" => "Pix",
+        "subscription" => "Assinatura",
+        other => other,
+    }
+}
+
+// Text typed by the operator, e.g. a plate
+pub fn text_prompt(stdout: &Arc<Mutex<RawTerminal<Stdout>>>, title: &str, text: &str) {
+    let mut stdout = stdout.lock().unwrap();
+
+    write!(stdout, "{}", cursor::Goto(FEEDBACK_POS.0, FEEDBACK_POS.1)).unwrap();
+    write!(stdout, "{}", clear::AfterCursor).unwrap();
+
+    let mut line = FEEDBACK_POS.1;
+
+    write!(stdout, "{}", title).unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "> {}", text).unwrap();
+    new_line(&mut stdout, &mut line);
+
+    write!(stdout, "Enter confirma, Esc cancela").unwrap();
+
+    stdout.flush().unwrap();
+}
+
+fn format_time(timestamp: i64, format: &str) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .unwrap()
+        .with_timezone(&Local)
+        .format(format)
+        .to_string()
+}
+
+fn spot_type_name(spot_type: i32) -> &'static str {
+    match spot_type {
+        1 => "Deficiente",
+        2 => "Idoso",
+        _ => "Normal",
+    }
+}
+
 fn write_floor(stdout: &mut MutexGuard<RawTerminal<Stdout>>, spots: &Vec<SpotDataPayload>) {
     write!(stdout, "|").unwrap();
 
     for spot in spots {
-        let parked_vehicle = match spot.parked_vehicle.as_ref() {
-            Some(vehicle) => format!("R${:.1}", vehicle.fee()),
-            None => "-----".to_string(),
+        let parked_vehicle = match (spot.parked_vehicle.as_ref(), &spot.out_of_service) {
+            (Some(vehicle), _) => format!("R${:.1}", vehicle.fee()),
+            (None, Some(_)) => "manut".to_string(),
+            (None, None) => "-----".to_string(),
         };
 
         let color = match spot.spot_type {
+            // Red, out of service
+            _ if spot.out_of_service.is_some() => color::Fg(color::Rgb(255, 0, 0)),
             // Green
             0 => color::Fg(color::Rgb(0, 255, 0)),
             // Blue
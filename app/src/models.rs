@@ -1,4 +1,4 @@
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -6,6 +6,10 @@ pub struct ParkingLotDataPayload {
     pub floors: Vec<FloorDataPayload>,
     pub exited_vehicles: Vec<VehicleDataPayload>,
     pub is_closed: bool,
+    #[serde(default)]
+    pub forecast: Option<ForecastPayload>,
+    #[serde(default)]
+    pub barrier_faults: Vec<BarrierFaultPayload>,
 }
 
 impl ParkingLotDataPayload {
@@ -18,6 +22,8 @@ impl ParkingLotDataPayload {
             ],
             exited_vehicles: vec![],
             is_closed: false,
+            forecast: None,
+            barrier_faults: vec![],
         }
     }
 }
@@ -47,6 +53,8 @@ impl FloorDataPayload {
 pub struct SpotDataPayload {
     pub spot_type: i32,
     pub parked_vehicle: Option<VehicleDataPayload>,
+    #[serde(default)]
+    pub out_of_service: Option<SpotMaintenancePayload>,
 }
 
 impl SpotDataPayload {
@@ -54,8 +62,24 @@ impl SpotDataPayload {
         Self {
             spot_type,
             parked_vehicle: None,
+            out_of_service: None,
         }
     }
+
+    // Free and in service
+    pub fn is_available(&self) -> bool {
+        self.parked_vehicle.is_none() && self.out_of_service.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SpotMaintenancePayload {
+    pub id: i64,
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub reason: String,
+    pub starts_at: i64,
+    pub ends_at: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -63,13 +87,359 @@ pub struct VehicleDataPayload {
     pub id: i32,
     pub entry_time: i64,
     pub exit_time: Option<i64>,
+    #[serde(default)]
+    pub overstay: Option<OverstayPayload>,
+    #[serde(default)]
+    pub subscription: Option<SubscriptionPayload>,
+    #[serde(default)]
+    pub receipt_number: Option<i64>,
+    #[serde(default)]
+    pub total: Option<f64>,
 }
 
 impl VehicleDataPayload {
     pub fn fee(&self) -> f64 {
-        let entry_time = self.entry_time;
+        // Once the vehicle left, the receipt issued by the server is what it paid
+        if let Some(total) = self.total {
+            return total;
+        }
+
+        let minutes = self.parked_minutes();
+
+        // Subscribers pay the price of their contract instead
+        let rate_per_minute = match &self.subscription {
+            Some(subscription) => subscription.rate_per_minute,
+            None => 0.1,
+        };
+        let fee = minutes as f64 * rate_per_minute;
+
+        // The minutes past the overstay limit also pay the surcharge
+        match &self.overstay {
+            Some(overstay) => {
+                fee + (minutes - overstay.max_minutes).max(0) as f64 * overstay.surcharge_per_minute
+            }
+            None => fee,
+        }
+    }
+
+    pub fn parked_minutes(&self) -> i64 {
         let exit_time = self.exit_time.unwrap_or_else(|| Utc::now().timestamp());
 
-        ((exit_time - entry_time) / 60) as f64 * 0.1
+        (exit_time - self.entry_time) / 60
     }
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct OverstayPayload {
+    pub max_minutes: i64,
+    pub surcharge_per_minute: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SubscriptionPayload {
+    pub subscriber_id: i64,
+    pub rate_per_minute: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SubscriberPayload {
+    pub id: i64,
+    pub credential: String,
+    pub name: String,
+    pub valid_from: i64,
+    pub valid_until: i64,
+    pub allowed_floors: Vec<i32>,
+    pub reserved_spot: Option<SpotPayload>,
+    pub rate_per_minute: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SpotPayload {
+    pub floor_number: i32,
+    pub spot_number: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReceiptDocumentPayload {
+    pub number: i64,
+    // Kept as sent by the server, it is only exported
+    pub receipt: Option<serde_json::Value>,
+    pub text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VehicleOverstayedPayload {
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub spot_type: i32,
+    pub vehicle: VehicleDataPayload,
+    pub parked_minutes: i64,
+    pub max_minutes: i64,
+}
+
+impl VehicleOverstayedPayload {
+    pub fn message(&self) -> String {
+        format!(
+            "Alerta: veículo na vaga {}.{} está estacionado há {} (limite: {})",
+            self.floor_number,
+            self.spot_number,
+            format_duration(self.parked_minutes),
+            format_duration(self.max_minutes)
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CashClosingPayload {
+    pub id: i64,
+    // yyyy-mm-dd
+    pub business_date: String,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub closed_at: i64,
+    pub vehicles: i64,
+    pub total: f64,
+    pub spot_types: Vec<SpotTypeTotalPayload>,
+    pub payment_methods: Vec<PaymentMethodTotalPayload>,
+    pub vehicles_inside: i64,
+}
+
+impl CashClosingPayload {
+    // dd/mm/yyyy, as the operator reads it
+    pub fn date(&self) -> String {
+        match NaiveDate::parse_from_str(&self.business_date, "%Y-%m-%d") {
+            Ok(date) => date.format("%d/%m/%Y").to_string(),
+            Err(_) => self.business_date.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SpotTypeTotalPayload {
+    pub spot_type: i32,
+    pub vehicles: i64,
+    pub total: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PaymentMethodTotalPayload {
+    pub payment_method: String,
+    pub vehicles: i64,
+    pub total: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DayClosedPayload {
+    pub closing: Option<CashClosingPayload>,
+    pub error: Option<String>,
+}
+
+impl DayClosedPayload {
+    pub fn message(&self) -> String {
+        match &self.closing {
+            Some(closing) => format!(
+                "Caixa de {} fechado: {} veículos, R$ {:.2}.",
+                closing.date(),
+                closing.vehicles,
+                closing.total
+            ),
+            None => "Não foi possível fechar o caixa: o dia já foi fechado ou ainda não terminou."
+                .to_string(),
+        }
+    }
+}
+
+pub fn format_duration(minutes: i64) -> String {
+    format!("{}h{:02}", minutes / 60, minutes % 60)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ClockSkewPayload {
+    pub client_id: String,
+    pub offset_millis: i64,
+    pub round_trip_millis: i64,
+    pub max_offset_secs: i64,
+}
+
+impl ClockSkewPayload {
+    pub fn message(&self) -> String {
+        let floor = match self.client_id.as_str() {
+            "ground_floor" => "térreo",
+            "first_floor" => "1° andar",
+            "second_floor" => "2° andar",
+            other => other,
+        };
+
+        let direction = if self.offset_millis > 0 {
+            "adiantado"
+        } else {
+            "atrasado"
+        };
+
+        format!(
+            "Alerta: relógio do controlador do {} está {} {:.1}s (limite: {}s)",
+            floor,
+            direction,
+            self.offset_millis.abs() as f64 / 1000.0,
+            self.max_offset_secs
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BarrierFaultPayload {
+    pub barrier: String,
+    pub fault: String,
+    pub timestamp: i64,
+}
+
+impl BarrierFaultPayload {
+    pub fn barrier_name(&self) -> &str {
+        match self.barrier.as_str() {
+            "entry" => "entrada",
+            "exit" => "saída",
+            other => other,
+        }
+    }
+
+    pub fn fault_description(&self) -> &str {
+        match self.fault.as_str() {
+            "close_signal_timeout" => "o carro não passou pelo sensor de fechamento",
+            "motor_timeout" => "motor desligado por tempo excedido",
+            other => other,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        format!(
+            "Alerta: cancela de {} com falha, {}",
+            self.barrier_name(),
+            self.fault_description()
+        )
+    }
+}
+
+// A spot mismatch or an ambiguous passage seen by a floor
+#[derive(Serialize, Deserialize)]
+pub struct SensorReportPayload {
+    pub id: i64,
+    pub floor_number: i32,
+    pub kind: String,
+    pub parking_space: Option<i32>,
+    pub first_sensor: Option<String>,
+    pub timestamp: i64,
+    pub received_at: i64,
+}
+
+impl SensorReportPayload {
+    pub fn description(&self) -> String {
+        let spot = self
+            .parking_space
+            .map(|spot| format!("{}.{}", self.floor_number, spot))
+            .unwrap_or_default();
+
+        match self.kind.as_str() {
+            "arrival_without_entry" => format!("vaga {} ocupada sem carro passar", spot),
+            "departure_without_exit" => format!("vaga {} liberada sem carro sair", spot),
+            "entry_without_arrival" => "carro entrou e não estacionou".to_string(),
+            "exit_without_departure" => "carro saiu sem liberar vaga".to_string(),
+            "ambiguous_passage" => "carro visto por um só sensor da rampa".to_string(),
+            other => other.to_string(),
+        }
+    }
+
+    pub fn message(&self) -> String {
+        format!(
+            "Aviso: andar {}, {}, verifique os sensores",
+            self.floor_number,
+            self.description()
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AnalyticsPayload {
+    pub from: i64,
+    pub to: i64,
+    pub arrivals: usize,
+    pub departures: usize,
+    pub average_dwell_minutes: Option<f64>,
+    pub median_dwell_minutes: Option<f64>,
+    pub spot_turnover: Vec<SpotTurnoverPayload>,
+    pub spot_type_turnover: Vec<SpotTypeTurnoverPayload>,
+    pub hourly_arrivals: Vec<usize>,
+    pub hourly_departures: Vec<usize>,
+    pub daily_peak_occupancy: Vec<DailyPeakPayload>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SpotTurnoverPayload {
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub spot_type: i32,
+    pub vehicles: usize,
+    pub turnover_per_day: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SpotTypeTurnoverPayload {
+    pub spot_type: i32,
+    pub spots: usize,
+    pub vehicles: usize,
+    pub turnover_per_day: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DailyPeakPayload {
+    pub date: String,
+    pub peak_occupancy: i32,
+    pub peak_at: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OccupancyHistoryPayload {
+    pub from: i64,
+    pub to: i64,
+    pub points: Vec<OccupancyPointPayload>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OccupancyPointPayload {
+    pub timestamp: i64,
+    pub resolution: String,
+    pub is_closed: bool,
+    pub occupied: f64,
+    pub capacity: i32,
+    pub floors: Vec<FloorOccupancyPayload>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FloorOccupancyPayload {
+    pub floor_number: i32,
+    pub is_closed: bool,
+    pub occupied: f64,
+    pub capacity: i32,
+    pub spot_types: Vec<SpotTypeOccupancyPayload>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SpotTypeOccupancyPayload {
+    pub spot_type: i32,
+    pub occupied: f64,
+    pub capacity: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ForecastPayload {
+    pub generated_at: i64,
+    pub minutes_to_full: Option<i64>,
+    pub expected_available: Vec<i32>,
+    pub floors: Vec<FloorForecastPayload>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FloorForecastPayload {
+    pub floor_number: i32,
+    pub minutes_to_full: Option<i64>,
+    pub expected_available: Vec<i32>,
+}
@@ -1,15 +1,27 @@
 use crate::{
     constants::{
-        CLOSE_FLOOR_EVENT, CLOSE_PARKING_LOT_EVENT, OPEN_FLOOR_EVENT, OPEN_PARKING_LOT_EVENT,
-        RESET_DATABASE_EVENT,
+        ADD_SUBSCRIBER_EVENT, CLOSE_DAY_EVENT, CLOSE_FLOOR_EVENT, CLOSE_PARKING_LOT_EVENT,
+        IDENTIFY_VEHICLE_EVENT, OPEN_FLOOR_EVENT, OPEN_PARKING_LOT_EVENT, PAYMENT_METHODS,
+        RECEIPTS_DIR, REMOVE_SUBSCRIBER_EVENT, REQUEST_ANALYTICS_EVENT,
+        REQUEST_CASH_CLOSINGS_EVENT, REQUEST_OCCUPANCY_HISTORY_EVENT, REQUEST_RECEIPT_EVENT,
+        REQUEST_SUBSCRIBERS_EVENT, RESET_DATABASE_EVENT, RETURN_SPOT_TO_SERVICE_EVENT,
+        SET_RECEIPT_PAYMENT_EVENT, SET_SPOT_OUT_OF_SERVICE_EVENT,
     },
     menus,
+    models::{
+        AnalyticsPayload, CashClosingPayload, OccupancyHistoryPayload, ParkingLotDataPayload,
+        ReceiptDocumentPayload, SubscriberPayload,
+    },
 };
+use chrono::Utc;
 use rust_socketio::client::Client;
-use serde_json::json;
+use serde_json::{json, Value};
 use std::{
+    fs,
     io::{stdin, Stdout},
     sync::{Arc, Mutex},
+    thread,
+    time::Duration,
 };
 use termion::{event::Key, input::TermRead, raw::RawTerminal};
 
@@ -118,3 +130,621 @@ pub fn reset_database(client: &Arc<Mutex<Client>>, stdout: &Arc<Mutex<RawTermina
 
     menus::feedback(stdout, "Ordem de reset de dados enviada.");
 }
+
+pub fn statistics(
+    client: &Arc<Mutex<Client>>,
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    analytics: &Arc<Mutex<Option<AnalyticsPayload>>>,
+) {
+    menus::feedback(stdout, "Calculando estatísticas...");
+
+    let received = request(
+        client,
+        REQUEST_ANALYTICS_EVENT,
+        json!({ "from": null, "to": null }),
+        analytics,
+    );
+
+    match analytics.lock().unwrap().as_ref() {
+        Some(analytics) if received => menus::statistics(stdout, analytics),
+        _ => {
+            menus::feedback(stdout, "Não foi possível obter as estatísticas.");
+            return;
+        }
+    }
+
+    wait_for_return(stdout);
+}
+
+pub fn occupancy_history(
+    client: &Arc<Mutex<Client>>,
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    history: &Arc<Mutex<Option<OccupancyHistoryPayload>>>,
+) {
+    menus::feedback(stdout, "Carregando histórico...");
+
+    // The server defaults to the last 24 hours
+    let received = request(
+        client,
+        REQUEST_OCCUPANCY_HISTORY_EVENT,
+        json!({ "from": null, "to": null, "resolution": null }),
+        history,
+    );
+
+    match history.lock().unwrap().as_ref() {
+        Some(history) if received => menus::occupancy_history(stdout, history),
+        _ => {
+            menus::feedback(stdout, "Não foi possível obter o histórico.");
+            return;
+        }
+    }
+
+    wait_for_return(stdout);
+}
+
+pub fn spot_maintenance(
+    client: &Arc<Mutex<Client>>,
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    parking_lot: &Arc<Mutex<ParkingLotDataPayload>>,
+) {
+    let floors = ["Térreo", "Primeiro andar", "Segundo andar"].map(String::from);
+    menus::choice_menu(stdout, "Escolha o andar:", &floors);
+
+    let Some(floor_number) = read_choice(floors.len()) else {
+        menus::main_menu(stdout);
+        return;
+    };
+
+    let spots: Vec<String> = parking_lot.lock().unwrap().floors[floor_number]
+        .spots
+        .iter()
+        .enumerate()
+        .map(|(spot_number, spot)| match &spot.out_of_service {
+            Some(maintenance) => format!(
+                "Vaga {}.{} - fora de serviço: {}",
+                floor_number, spot_number, maintenance.reason
+            ),
+            None => format!("Vaga {}.{} - em serviço", floor_number, spot_number),
+        })
+        .collect();
+    menus::choice_menu(stdout, "Escolha a vaga:", &spots);
+
+    let Some(spot_number) = read_choice(spots.len()) else {
+        menus::main_menu(stdout);
+        return;
+    };
+
+    let is_out_of_service = parking_lot.lock().unwrap().floors[floor_number].spots[spot_number]
+        .out_of_service
+        .is_some();
+
+    let message = if is_out_of_service {
+        return_spot_to_service(client, stdout, floor_number, spot_number)
+    } else {
+        set_spot_out_of_service(client, stdout, floor_number, spot_number)
+    };
+
+    menus::main_menu(stdout);
+
+    if let Some(message) = message {
+        menus::feedback(stdout, message);
+    }
+}
+
+fn set_spot_out_of_service(
+    client: &Arc<Mutex<Client>>,
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    floor_number: usize,
+    spot_number: usize,
+) -> Option<&'static str> {
+    let reasons = ["Sensor com defeito", "Obra", "Vaga bloqueada"].map(String::from);
+    menus::choice_menu(stdout, "Motivo:", &reasons);
+    let reason = read_choice(reasons.len())?;
+
+    let durations = ["1 hora", "1 dia", "Até ser devolvida"].map(String::from);
+    menus::choice_menu(stdout, "Por quanto tempo?", &durations);
+
+    let ends_at = match read_choice(durations.len())? {
+        0 => Some(Utc::now().timestamp() + 3600),
+        1 => Some(Utc::now().timestamp() + 86400),
+        _ => None,
+    };
+
+    client
+        .lock()
+        .unwrap()
+        .emit(
+            SET_SPOT_OUT_OF_SERVICE_EVENT,
+            json!({
+                "floor_number": floor_number,
+                "spot_number": spot_number,
+                "reason": reasons[reason],
+                "starts_at": null,
+                "ends_at": ends_at,
+            }),
+        )
+        .unwrap();
+
+    Some("Vaga retirada de serviço.")
+}
+
+fn return_spot_to_service(
+    client: &Arc<Mutex<Client>>,
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    floor_number: usize,
+    spot_number: usize,
+) -> Option<&'static str> {
+    menus::choice_menu(
+        stdout,
+        "Vaga fora de serviço:",
+        &["Devolver ao serviço".to_string()],
+    );
+    read_choice(1)?;
+
+    client
+        .lock()
+        .unwrap()
+        .emit(
+            RETURN_SPOT_TO_SERVICE_EVENT,
+            json!({ "floor_number": floor_number, "spot_number": spot_number }),
+        )
+        .unwrap();
+
+    Some("Vaga devolvida ao serviço.")
+}
+
+// Built from the state the dashboard already has, the server marks the vehicles
+pub fn overstays(
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    parking_lot: &Arc<Mutex<ParkingLotDataPayload>>,
+) {
+    menus::overstays(stdout, &parking_lot.lock().unwrap());
+
+    wait_for_return(stdout);
+}
+
+pub fn subscribers(
+    client: &Arc<Mutex<Client>>,
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    parking_lot: &Arc<Mutex<ParkingLotDataPayload>>,
+    subscribers: &Arc<Mutex<Option<Vec<SubscriberPayload>>>>,
+) {
+    menus::feedback(stdout, "Carregando assinantes...");
+
+    let received = request(client, REQUEST_SUBSCRIBERS_EVENT, json!(()), subscribers);
+
+    match subscribers.lock().unwrap().as_ref() {
+        Some(subscribers) if received => menus::subscribers(stdout, subscribers),
+        _ => {
+            menus::feedback(stdout, "Não foi possível obter os assinantes.");
+            return;
+        }
+    }
+
+    let message = match read_choice(3) {
+        Some(0) => add_subscriber(client, stdout),
+        Some(1) => remove_subscriber(client, stdout, subscribers),
+        Some(2) => identify_vehicle(client, stdout, parking_lot),
+        _ => None,
+    };
+
+    menus::main_menu(stdout);
+
+    if let Some(message) = message {
+        menus::feedback(stdout, message);
+    }
+}
+
+pub fn receipt(
+    client: &Arc<Mutex<Client>>,
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    parking_lot: &Arc<Mutex<ParkingLotDataPayload>>,
+    receipt: &Arc<Mutex<Option<ReceiptDocumentPayload>>>,
+) {
+    let last_number = parking_lot
+        .lock()
+        .unwrap()
+        .exited_vehicles
+        .first()
+        .and_then(|vehicle| vehicle.receipt_number);
+
+    let options = ["Último recibo", "Buscar pelo número"].map(String::from);
+    menus::choice_menu(stdout, "Recibos:", &options);
+
+    let number = match read_choice(options.len()) {
+        Some(0) => last_number,
+        Some(_) => read_text(stdout, "Número do recibo:").and_then(|number| number.parse().ok()),
+        None => {
+            menus::main_menu(stdout);
+            return;
+        }
+    };
+
+    let Some(number) = number else {
+        menus::main_menu(stdout);
+        menus::feedback(stdout, "Recibo não encontrado.");
+        return;
+    };
+
+    menus::feedback(stdout, "Carregando recibo...");
+
+    let received = request(client, REQUEST_RECEIPT_EVENT, json!(number), receipt);
+
+    let document = receipt.lock().unwrap().take();
+
+    let message = match document {
+        Some(ReceiptDocumentPayload {
+            receipt: Some(data),
+            text: Some(text),
+            ..
+        }) if received => {
+            menus::receipt(stdout, &text);
+
+            match read_choice(3) {
+                Some(0) => save_receipt(number, "txt", &text),
+                Some(1) => save_receipt(
+                    number,
+                    "json",
+                    &serde_json::to_string_pretty(&data).unwrap(),
+                ),
+                Some(_) => set_payment_method(client, stdout, number, receipt),
+                None => None,
+            }
+        }
+        _ => Some("Recibo não encontrado.".to_string()),
+    };
+
+    menus::main_menu(stdout);
+
+    if let Some(message) = message {
+        menus::feedback(stdout, &message);
+    }
+}
+
+pub fn cash_closings(
+    client: &Arc<Mutex<Client>>,
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    closings: &Arc<Mutex<Option<Vec<CashClosingPayload>>>>,
+) {
+    menus::feedback(stdout, "Carregando fechamentos...");
+
+    let received = request(client, REQUEST_CASH_CLOSINGS_EVENT, json!(()), closings);
+
+    let Some(closings) = closings.lock().unwrap().take().filter(|_| received) else {
+        menus::feedback(stdout, "Não foi possível obter os fechamentos.");
+        return;
+    };
+
+    menus::cash_closings(stdout, &closings);
+
+    match read_choice(2) {
+        Some(0) => close_day(client, stdout),
+        Some(_) => {
+            let dates = closings
+                .iter()
+                .take(9)
+                .map(|closing| closing.date())
+                .collect::<Vec<_>>();
+
+            menus::choice_menu(stdout, "Escolha o dia:", &dates);
+
+            match read_choice(dates.len()) {
+                Some(choice) => {
+                    menus::cash_closing(stdout, &closings[choice]);
+                    wait_for_return(stdout);
+                }
+                None => menus::main_menu(stdout),
+            }
+        }
+        None => menus::main_menu(stdout),
+    }
+}
+
+// The closing can't be undone, so the operator confirms it first
+fn close_day(client: &Arc<Mutex<Client>>, stdout: &Arc<Mutex<RawTerminal<Stdout>>>) {
+    let options = ["Confirmar".to_string()];
+    menus::choice_menu(
+        stdout,
+        "Fechar o caixa do último dia? O fechamento não pode ser alterado.",
+        &options,
+    );
+
+    let confirmed = read_choice(options.len()).is_some();
+
+    menus::main_menu(stdout);
+
+    if confirmed {
+        // The server closes the last day that ended, the result arrives as day_closed
+        client
+            .lock()
+            .unwrap()
+            .emit(CLOSE_DAY_EVENT, json!({}))
+            .unwrap();
+
+        menus::feedback(stdout, "Fechando o caixa...");
+    }
+}
+
+fn set_payment_method(
+    client: &Arc<Mutex<Client>>,
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    number: i64,
+    receipt: &Arc<Mutex<Option<ReceiptDocumentPayload>>>,
+) -> Option<String> {
+    let names = PAYMENT_METHODS.map(|method| menus::payment_method_name(method).to_string());
+    menus::choice_menu(stdout, "Forma de pagamento:", &names);
+
+    let payment_method = PAYMENT_METHODS[read_choice(names.len())?];
+
+    let received = request(
+        client,
+        SET_RECEIPT_PAYMENT_EVENT,
+        json!({ "number": number, "payment_method": payment_method }),
+        receipt,
+    );
+
+    // The server answers with the receipt as it is, changed or not
+    let changed = receipt
+        .lock()
+        .unwrap()
+        .take()
+        .and_then(|document| document.receipt)
+        .is_some_and(|data| data["payment_method"] == payment_method);
+
+    if received && changed {
+        Some("Forma de pagamento alterada.".to_string())
+    } else {
+        Some(
+            "Não foi possível alterar a forma de pagamento, o dia pode já ter sido fechado."
+                .to_string(),
+        )
+    }
+}
+
+// Saved under RECEIPTS_DIR, e.g. receipts/recibo-000123.txt, ready to be sent to the printer
+fn save_receipt(number: i64, extension: &str, contents: &str) -> Option<String> {
+    let path = format!("{}/recibo-{:06}.{}", RECEIPTS_DIR, number, extension);
+
+    let saved = fs::create_dir_all(RECEIPTS_DIR).and_then(|_| fs::write(&path, contents));
+
+    match saved {
+        Ok(()) => Some(format!("Recibo salvo em {}.", path)),
+        Err(_) => Some("Não foi possível salvar o recibo.".to_string()),
+    }
+}
+
+fn add_subscriber(
+    client: &Arc<Mutex<Client>>,
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+) -> Option<&'static str> {
+    let credential = read_text(stdout, "Placa ou número do cartão:")?;
+    let name = read_text(stdout, "Nome:")?;
+
+    let validities = ["1 mês", "3 meses", "1 ano"].map(String::from);
+    menus::choice_menu(stdout, "Validade:", &validities);
+
+    let days = match read_choice(validities.len())? {
+        0 => 30,
+        1 => 90,
+        _ => 365,
+    };
+
+    let floors = [
+        "Todos os andares",
+        "Somente térreo",
+        "Somente primeiro andar",
+        "Somente segundo andar",
+    ]
+    .map(String::from);
+    menus::choice_menu(stdout, "Andares permitidos:", &floors);
+
+    let allowed_floors = match read_choice(floors.len())? {
+        0 => vec![],
+        floor => vec![floor - 1],
+    };
+
+    let reserved = ["Sem vaga reservada", "Reservar uma vaga"].map(String::from);
+    menus::choice_menu(stdout, "Vaga reservada:", &reserved);
+
+    let reserved_spot = match read_choice(reserved.len())? {
+        0 => None,
+        _ => {
+            let (floor_number, spot_number) = choose_spot(stdout)?;
+            Some(json!({ "floor_number": floor_number, "spot_number": spot_number }))
+        }
+    };
+
+    let contracts = ["Isento", "Tarifa por minuto"].map(String::from);
+    menus::choice_menu(stdout, "Cobrança:", &contracts);
+
+    let rate_per_minute = match read_choice(contracts.len())? {
+        0 => 0.0,
+        _ => {
+            let rate = read_text(stdout, "Tarifa por minuto (R$):")?;
+
+            match rate.replace(',', ".").parse::<f64>() {
+                Ok(rate) if rate >= 0.0 => rate,
+                _ => return Some("Tarifa inválida."),
+            }
+        }
+    };
+
+    client
+        .lock()
+        .unwrap()
+        .emit(
+            ADD_SUBSCRIBER_EVENT,
+            json!({
+                "credential": credential,
+                "name": name,
+                "valid_from": null,
+                "valid_until": Utc::now().timestamp() + days * 86400,
+                "allowed_floors": allowed_floors,
+                "reserved_spot": reserved_spot,
+                "rate_per_minute": rate_per_minute,
+            }),
+        )
+        .unwrap();
+
+    Some("Assinante cadastrado.")
+}
+
+fn remove_subscriber(
+    client: &Arc<Mutex<Client>>,
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    subscribers: &Arc<Mutex<Option<Vec<SubscriberPayload>>>>,
+) -> Option<&'static str> {
+    // Only the first 9 can be chosen with a single key
+    let (ids, names): (Vec<i64>, Vec<String>) = subscribers
+        .lock()
+        .unwrap()
+        .iter()
+        .flatten()
+        .take(9)
+        .map(|subscriber| {
+            (
+                subscriber.id,
+                format!("{} - {}", subscriber.credential, subscriber.name),
+            )
+        })
+        .unzip();
+
+    menus::choice_menu(stdout, "Escolha o assinante:", &names);
+
+    let choice = read_choice(names.len())?;
+
+    client
+        .lock()
+        .unwrap()
+        .emit(REMOVE_SUBSCRIBER_EVENT, json!(ids[choice]))
+        .unwrap();
+
+    Some("Assinante removido.")
+}
+
+fn identify_vehicle(
+    client: &Arc<Mutex<Client>>,
+    stdout: &Arc<Mutex<RawTerminal<Stdout>>>,
+    parking_lot: &Arc<Mutex<ParkingLotDataPayload>>,
+) -> Option<&'static str> {
+    let (floor_number, spot_number) = choose_spot(stdout)?;
+
+    if parking_lot.lock().unwrap().floors[floor_number].spots[spot_number]
+        .parked_vehicle
+        .is_none()
+    {
+        return Some("Não há veículo nessa vaga.");
+    }
+
+    let credential = read_text(stdout, "Placa ou número do cartão:")?;
+
+    client
+        .lock()
+        .unwrap()
+        .emit(
+            IDENTIFY_VEHICLE_EVENT,
+            json!({
+                "floor_number": floor_number,
+                "spot_number": spot_number,
+                "credential": credential,
+            }),
+        )
+        .unwrap();
+
+    Some("Identificação enviada.")
+}
+
+fn choose_spot(stdout: &Arc<Mutex<RawTerminal<Stdout>>>) -> Option<(usize, usize)> {
+    let floors = ["Térreo", "Primeiro andar", "Segundo andar"].map(String::from);
+    menus::choice_menu(stdout, "Escolha o andar:", &floors);
+    let floor_number = read_choice(floors.len())?;
+
+    let spots: Vec<String> = (0..8)
+        .map(|spot_number| format!("Vaga {}.{}", floor_number, spot_number))
+        .collect();
+    menus::choice_menu(stdout, "Escolha a vaga:", &spots);
+    let spot_number = read_choice(spots.len())?;
+
+    Some((floor_number, spot_number))
+}
+
+// Reads a line typed by the operator, None when it is cancelled or left empty
+fn read_text(stdout: &Arc<Mutex<RawTerminal<Stdout>>>, title: &str) -> Option<String> {
+    let stdin = stdin().lock();
+    let mut text = String::new();
+
+    menus::text_prompt(stdout, title, &text);
+
+    for key in stdin.keys() {
+        match key.unwrap() {
+            Key::Char('\n') => break,
+            Key::Esc => return None,
+            Key::Backspace => {
+                text.pop();
+            }
+            Key::Char(key) => text.push(key),
+            _ => {}
+        }
+
+        menus::text_prompt(stdout, title, &text);
+    }
+
+    let text = text.trim().to_string();
+
+    (!text.is_empty()).then_some(text)
+}
+
+// Waits for one of the options of menus::choice_menu, None when going back
+fn read_choice(options: usize) -> Option<usize> {
+    let stdin = stdin().lock();
+
+    for key in stdin.keys() {
+        match key.unwrap() {
+            Key::Char('0') => return None,
+            Key::Char(key) => match key.to_digit(10) {
+                Some(choice) if (1..=options as u32).contains(&choice) => {
+                    return Some(choice as usize - 1)
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    None
+}
+
+// Emits the request and waits up to 5 seconds for the socket client to store the answer
+fn request<T>(
+    client: &Arc<Mutex<Client>>,
+    event: &str,
+    payload: Value,
+    response: &Arc<Mutex<Option<T>>>,
+) -> bool {
+    // Drop the previous answer
+    *response.lock().unwrap() = None;
+
+    client.lock().unwrap().emit(event, payload).unwrap();
+
+    for _ in 0..50 {
+        if response.lock().unwrap().is_some() {
+            return true;
+        }
+
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    false
+}
+
+fn wait_for_return(stdout: &Arc<Mutex<RawTerminal<Stdout>>>) {
+    let stdin = stdin().lock();
+
+    for key in stdin.keys() {
+        if let Key::Char('0') = key.unwrap() {
+            break;
+        }
+    }
+
+    menus::main_menu(stdout);
+}
@@ -1,7 +1,15 @@
 use crate::{
-    constants::{CLIENT_HEADER, PARKING_LOT_STATE_EVENT, SERVER_ADDRESS},
+    constants::{
+        ANALYTICS_EVENT, BARRIER_FAULT_EVENT, CASH_CLOSINGS_EVENT, CLIENT_HEADER, CLOCK_SKEW_EVENT,
+        DAY_CLOSED_EVENT, OCCUPANCY_HISTORY_EVENT, PARKING_LOT_STATE_EVENT, RECEIPT_EVENT,
+        SENSOR_REPORT_EVENT, SERVER_ADDRESS, SUBSCRIBERS_EVENT, VEHICLE_OVERSTAYED_EVENT,
+    },
     menus,
-    models::ParkingLotDataPayload,
+    models::{
+        AnalyticsPayload, BarrierFaultPayload, CashClosingPayload, ClockSkewPayload,
+        DayClosedPayload, OccupancyHistoryPayload, ParkingLotDataPayload, ReceiptDocumentPayload,
+        SensorReportPayload, SubscriberPayload, VehicleOverstayedPayload,
+    },
 };
 use rust_socketio::{client::Client, ClientBuilder, Payload};
 use std::{
@@ -12,11 +20,19 @@ use std::{
     time::Duration,
 };
 use termion::raw::RawTerminal;
+use tracing::{error, info, warn};
 
 pub fn create(
     stdout: Arc<Mutex<RawTerminal<Stdout>>>,
     parking_lot: Arc<Mutex<ParkingLotDataPayload>>,
+    analytics: Arc<Mutex<Option<AnalyticsPayload>>>,
+    history: Arc<Mutex<Option<OccupancyHistoryPayload>>>,
+    subscribers: Arc<Mutex<Option<Vec<SubscriberPayload>>>>,
+    receipt: Arc<Mutex<Option<ReceiptDocumentPayload>>>,
+    closings: Arc<Mutex<Option<Vec<CashClosingPayload>>>>,
 ) -> Arc<Mutex<Client>> {
+    let stdout_clone = stdout.clone();
+
     let mut client_builder = ClientBuilder::new(SERVER_ADDRESS)
         .opening_header(CLIENT_HEADER.key, CLIENT_HEADER.value)
         .reconnect_on_disconnect(true)
@@ -35,6 +51,114 @@ pub fn create(
         menus::dashboard(&mut stdout, &parking_lot);
     });
 
+    let stdout_alert = stdout_clone.clone();
+
+    client_builder = client_builder.on(CLOCK_SKEW_EVENT, move |payload, _| {
+        if let Payload::Text(data) = payload {
+            let alert: ClockSkewPayload = serde_json::from_str(&data[0].to_string()).unwrap();
+
+            warn!(
+                client_id = %alert.client_id,
+                offset_millis = alert.offset_millis,
+                "controller clock is skewed"
+            );
+            menus::feedback(&stdout_alert, &alert.message());
+        }
+    });
+
+    let stdout_barrier = stdout_clone.clone();
+
+    client_builder = client_builder.on(BARRIER_FAULT_EVENT, move |payload, _| {
+        if let Payload::Text(data) = payload {
+            let alert: BarrierFaultPayload = serde_json::from_str(&data[0].to_string()).unwrap();
+
+            warn!(
+                barrier = %alert.barrier,
+                fault = %alert.fault,
+                "barrier fault"
+            );
+            menus::feedback(&stdout_barrier, &alert.message());
+        }
+    });
+
+    let stdout_sensor = stdout_clone.clone();
+
+    client_builder = client_builder.on(SENSOR_REPORT_EVENT, move |payload, _| {
+        if let Payload::Text(data) = payload {
+            let report: SensorReportPayload = serde_json::from_str(&data[0].to_string()).unwrap();
+
+            warn!(
+                floor = report.floor_number,
+                kind = %report.kind,
+                "sensor report"
+            );
+            menus::feedback(&stdout_sensor, &report.message());
+        }
+    });
+
+    client_builder = client_builder.on(ANALYTICS_EVENT, move |payload, _| {
+        if let Payload::Text(data) = payload {
+            *analytics.lock().unwrap() = Some(serde_json::from_str(&data[0].to_string()).unwrap());
+        }
+    });
+
+    client_builder = client_builder.on(OCCUPANCY_HISTORY_EVENT, move |payload, _| {
+        if let Payload::Text(data) = payload {
+            *history.lock().unwrap() = Some(serde_json::from_str(&data[0].to_string()).unwrap());
+        }
+    });
+
+    client_builder = client_builder.on(SUBSCRIBERS_EVENT, move |payload, _| {
+        if let Payload::Text(data) = payload {
+            *subscribers.lock().unwrap() =
+                Some(serde_json::from_str(&data[0].to_string()).unwrap());
+        }
+    });
+
+    client_builder = client_builder.on(RECEIPT_EVENT, move |payload, _| {
+        if let Payload::Text(data) = payload {
+            *receipt.lock().unwrap() = Some(serde_json::from_str(&data[0].to_string()).unwrap());
+        }
+    });
+
+    client_builder = client_builder.on(CASH_CLOSINGS_EVENT, move |payload, _| {
+        if let Payload::Text(data) = payload {
+            *closings.lock().unwrap() = Some(serde_json::from_str(&data[0].to_string()).unwrap());
+        }
+    });
+
+    let stdout_closing = stdout_clone.clone();
+
+    // The closing takes a moment, so the result is shown whenever it arrives
+    client_builder = client_builder.on(DAY_CLOSED_EVENT, move |payload, _| {
+        if let Payload::Text(data) = payload {
+            let result: DayClosedPayload = serde_json::from_str(&data[0].to_string()).unwrap();
+
+            match &result.error {
+                Some(error) => warn!(%error, "could not close the business day"),
+                None => info!("business day closed"),
+            }
+            menus::feedback(&stdout_closing, &result.message());
+        }
+    });
+
+    let stdout_overstay = stdout_clone.clone();
+
+    client_builder = client_builder.on(VEHICLE_OVERSTAYED_EVENT, move |payload, _| {
+        if let Payload::Text(data) = payload {
+            let alert: VehicleOverstayedPayload =
+                serde_json::from_str(&data[0].to_string()).unwrap();
+
+            warn!(
+                floor = alert.floor_number,
+                spot = alert.spot_number,
+                parked_minutes = alert.parked_minutes,
+                "vehicle overstayed"
+            );
+            menus::feedback(&stdout_overstay, &alert.message());
+        }
+    });
+
     let mut connection: Option<Client> = None;
 
     for _ in 0..10 {
@@ -43,8 +167,8 @@ pub fn create(
                 connection = Some(conn);
                 break;
             }
-            Err(_) => {
-                println!("Error connecting to the server. Retrying...");
+            Err(error) => {
+                warn!(%error, "error connecting to the server, retrying");
             }
         }
 
@@ -52,9 +176,13 @@ pub fn create(
     }
 
     if connection.is_none() {
-        println!("Failed to connect to the server. Exiting...");
+        error!("failed to connect to the server, exiting");
+        menus::finalize_console(&stdout_clone);
+        eprintln!("Failed to connect to the server. Exiting...");
         process::exit(1);
     }
 
+    info!(server = SERVER_ADDRESS, "connected to the server");
+
     Arc::new(Mutex::new(connection.unwrap()))
 }
@@ -8,7 +8,7 @@ fi
 # Clean the .bin folder
 rm -rf .bin/*
 
-# Cross-Compile all four projects using cross and the armv7-unknown-linux-musleabihf target
+# Cross-Compile all three projects using cross and the armv7-unknown-linux-musleabihf target
 cd server
 cross build --release --target armv7-unknown-linux-musleabihf
 cp target/armv7-unknown-linux-musleabihf/release/fse_trab_1_server ../.bin/fse_trab_1_server
@@ -17,16 +17,10 @@ cd ../app
 cross build --release --target armv7-unknown-linux-musleabihf
 cp target/armv7-unknown-linux-musleabihf/release/fse_trab_1_app ../.bin/fse_trab_1_app
 
-cd ../ground_floor
+# Every floor runs the same controller, the configuration files tell them apart
+cd ../floor_controller
 cross build --release --target armv7-unknown-linux-musleabihf
-cp target/armv7-unknown-linux-musleabihf/release/fse_trab_1_ground_floor ../.bin/fse_trab_1_ground_floor
-
-cd ../first_floor
-cross build --release --target armv7-unknown-linux-musleabihf
-cp target/armv7-unknown-linux-musleabihf/release/fse_trab_1_first_floor ../.bin/fse_trab_1_first_floor
-
-cd ../second_floor
-cross build --release --target armv7-unknown-linux-musleabihf
-cp target/armv7-unknown-linux-musleabihf/release/fse_trab_1_second_floor ../.bin/fse_trab_1_second_floor
+cp target/armv7-unknown-linux-musleabihf/release/fse_trab_1_floor_controller ../.bin/fse_trab_1_floor_controller
+cp config/*.toml ../.bin/
 
 echo "All projects compiled successfully, binaries are in the .bin folder"
\ No newline at end of file
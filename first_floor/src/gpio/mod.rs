@@ -1,2 +0,0 @@
-pub mod gpio_async_interrupts;
-pub mod gpio_pins;
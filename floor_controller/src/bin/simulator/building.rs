@@ -0,0 +1,264 @@
+use fse_trab_1_floor_controller::config::{FloorConfig, FloorRole};
+use fse_trab_1_floor_controller::controller::{self, Controller, ControllerEvent};
+use fse_trab_1_floor_controller::gpio::gpio_pins::GpioPins;
+use fse_trab_1_floor_controller::hal::mock_gpio::MockGpio;
+use fse_trab_1_floor_controller::hal::Level;
+use fse_trab_1_floor_controller::model::ParkingLot;
+use fse_trab_1_floor_controller::socket::event_queue::EventQueue;
+use fse_trab_1_floor_controller::socket::socket_client;
+use rand::{rngs::StdRng, Rng};
+use std::{
+    env, fs,
+    sync::{mpsc::Sender, Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+// Time a barrier takes to lift, or a car to go past a sensor
+const PASSING_TIME: Duration = Duration::from_secs(1);
+
+// How long a sensor sees the car, well over the debouncing of the controllers
+const SENSOR_PULSE: Duration = Duration::from_millis(200);
+
+pub const SPOTS_PER_FLOOR: usize = 8;
+
+// One floor controller running against in memory pins, with the cars on its spots
+pub struct SimulatedFloor {
+    pub config: FloorConfig,
+    pub gpio: MockGpio,
+    // To stop the controller, which runs on its own thread
+    events: Sender<ControllerEvent>,
+    controller: Mutex<Option<JoinHandle<()>>>,
+    // What the spot sensors see
+    occupied: Arc<Mutex<Vec<bool>>>,
+    // Spots a car is heading to or parked on, so two cars never pick the same one
+    claimed: Mutex<Vec<bool>>,
+    // One car at a time through each barrier, the ramps only use the entry lane
+    entry_lane: Mutex<()>,
+    exit_lane: Mutex<()>,
+}
+
+pub struct Building {
+    pub floors: Vec<SimulatedFloor>,
+}
+
+impl SimulatedFloor {
+    fn new(mut config: FloorConfig) -> SimulatedFloor {
+        // The events of a simulated run must never be replayed into the server by
+        // a real controller with the same client id, nor by the next run
+        if config.queue.path.is_none() {
+            let path =
+                env::temp_dir().join(format!("simulator_pending_{}.jsonl", config.client_id));
+            let _ = fs::remove_file(&path);
+            config.queue.path = Some(path.to_string_lossy().into_owned());
+        }
+
+        let gpio = MockGpio::new();
+        let gpio_pins = GpioPins::new(&gpio, &config);
+        let parking_lot = ParkingLot::new();
+
+        // The spot sensor reads the spot selected by the address pins
+        let occupied = Arc::new(Mutex::new(vec![false; SPOTS_PER_FLOOR]));
+        let occupied_clone = occupied.clone();
+        let [address_1, address_2, address_3] = config.pins.space_address;
+
+        gpio.set_input_source(
+            config.pins.space_sensor,
+            Box::new(move |outputs| {
+                let address = [address_1, address_2, address_3]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, pin)| outputs.level(**pin) == Level::High)
+                    .fold(0, |address, (bit, _)| address | 1 << bit);
+
+                if occupied_clone.lock().unwrap()[address] {
+                    Level::High
+                } else {
+                    Level::Low
+                }
+            }),
+        );
+
+        let (events, receiver) = controller::channel();
+        let event_queue = EventQueue::open(&config);
+        let client = socket_client::new_client(&config, &parking_lot, &event_queue, &events);
+        let controller = Controller::new(
+            &config,
+            gpio_pins,
+            &client,
+            &parking_lot,
+            &event_queue,
+            &events,
+            receiver,
+        );
+
+        SimulatedFloor {
+            config,
+            gpio,
+            events,
+            controller: Mutex::new(Some(thread::spawn(move || controller.run()))),
+            occupied,
+            claimed: Mutex::new(vec![false; SPOTS_PER_FLOOR]),
+            entry_lane: Mutex::new(()),
+            exit_lane: Mutex::new(()),
+        }
+    }
+
+    // The closed sign, lit by the server through the controller
+    pub fn is_closed(&self) -> bool {
+        self.gpio.output(self.config.pins.closed_signal) == Level::High
+    }
+
+    pub fn has_free_spot(&self) -> bool {
+        self.claimed.lock().unwrap().contains(&false)
+    }
+
+    pub fn claim_spot(&self, rng: &mut StdRng) -> Option<usize> {
+        let mut claimed = self.claimed.lock().unwrap();
+        let free: Vec<usize> = (0..SPOTS_PER_FLOOR)
+            .filter(|&spot| !claimed[spot])
+            .collect();
+
+        if free.is_empty() {
+            return None;
+        }
+
+        let spot = free[rng.gen_range(0..free.len())];
+        claimed[spot] = true;
+
+        Some(spot)
+    }
+
+    pub fn release_spot(&self, spot: usize) {
+        self.claimed.lock().unwrap()[spot] = false;
+    }
+
+    pub fn park(&self, spot: usize) {
+        self.occupied.lock().unwrap()[spot] = true;
+    }
+
+    pub fn unpark(&self, spot: usize) {
+        self.occupied.lock().unwrap()[spot] = false;
+    }
+
+    // Held long enough to get through the debouncing of the controller
+    fn pulse(&self, pin: u8) {
+        self.gpio.pulse_for(pin, SENSOR_PULSE);
+    }
+}
+
+impl Building {
+    pub fn new(configs: Vec<FloorConfig>) -> Building {
+        for (floor, config) in configs.iter().enumerate() {
+            let runs_barriers = matches!(config.role, FloorRole::Gates { .. });
+
+            if runs_barriers != (floor == 0) {
+                panic!("The scenario floors must be the one with the barriers, then the ramps");
+            }
+        }
+
+        Building {
+            floors: configs.into_iter().map(SimulatedFloor::new).collect(),
+        }
+    }
+
+    pub fn ground_floor(&self) -> &SimulatedFloor {
+        &self.floors[0]
+    }
+
+    // Drives through the entry barrier, which only lifts if the controller turns
+    // its engine on. Returns whether the car got in
+    pub fn enter(&self) -> bool {
+        let ground_floor = self.ground_floor();
+        let FloorRole::Gates {
+            entry_open_signal,
+            entry_close_signal,
+            entry_engine,
+            ..
+        } = ground_floor.config.role
+        else {
+            unreachable!()
+        };
+
+        let _lane = ground_floor.entry_lane.lock().unwrap();
+
+        ground_floor.pulse(entry_open_signal);
+        thread::sleep(PASSING_TIME);
+
+        if ground_floor.gpio.output(entry_engine) == Level::Low {
+            return false;
+        }
+
+        ground_floor.pulse(entry_close_signal);
+        thread::sleep(PASSING_TIME);
+
+        true
+    }
+
+    pub fn exit(&self) {
+        let ground_floor = self.ground_floor();
+        let FloorRole::Gates {
+            exit_open_signal,
+            exit_close_signal,
+            ..
+        } = ground_floor.config.role
+        else {
+            unreachable!()
+        };
+
+        let _lane = ground_floor.exit_lane.lock().unwrap();
+
+        ground_floor.pulse(exit_open_signal);
+        thread::sleep(PASSING_TIME);
+
+        ground_floor.pulse(exit_close_signal);
+    }
+
+    // Takes the ramp from the floor below up to `floor`, sensor 1 then sensor 2
+    pub fn go_up(&self, floor: usize) {
+        let (sensor_1, sensor_2) = self.ramp_sensors(floor);
+        let ramp = &self.floors[floor];
+        let _lane = ramp.entry_lane.lock().unwrap();
+
+        ramp.pulse(sensor_1);
+        thread::sleep(PASSING_TIME);
+        ramp.pulse(sensor_2);
+    }
+
+    // Takes the ramp from `floor` down to the floor below, sensor 2 then sensor 1
+    pub fn go_down(&self, floor: usize) {
+        let (sensor_1, sensor_2) = self.ramp_sensors(floor);
+        let ramp = &self.floors[floor];
+        let _lane = ramp.entry_lane.lock().unwrap();
+
+        ramp.pulse(sensor_2);
+        thread::sleep(PASSING_TIME);
+        ramp.pulse(sensor_1);
+    }
+
+    fn ramp_sensors(&self, floor: usize) -> (u8, u8) {
+        match self.floors[floor].config.role {
+            FloorRole::Ramp {
+                pass_through_sensor_1,
+                pass_through_sensor_2,
+            } => (pass_through_sensor_1, pass_through_sensor_2),
+            FloorRole::Gates { .. } => unreachable!(),
+        }
+    }
+
+    // Stops the controllers the way Ctrl-C does on the Raspberry Pi
+    pub fn disconnect(&self) {
+        for floor in &self.floors {
+            floor
+                .events
+                .send(ControllerEvent::Shutdown("simulation finished"))
+                .unwrap();
+        }
+
+        for floor in &self.floors {
+            if let Some(controller) = floor.controller.lock().unwrap().take() {
+                controller.join().unwrap();
+            }
+        }
+    }
+}
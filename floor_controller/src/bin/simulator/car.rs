@@ -0,0 +1,82 @@
+use crate::building::Building;
+use rand::{rngs::StdRng, Rng};
+use std::{sync::Arc, thread, time::Duration};
+use tracing::info;
+
+// Time to drive from a barrier or a ramp to the next one, or to a spot
+const DRIVING_TIME: Duration = Duration::from_secs(2);
+
+pub struct Car {
+    pub id: u64,
+    pub dwell: Duration,
+    // Picks the floor and the spot, seeded by the scenario
+    pub rng: StdRng,
+}
+
+impl Car {
+    // The whole visit: in through the barrier, up the ramps to a free spot, parked
+    // for the dwell time, then down the ramps and out
+    pub fn run(mut self, building: Arc<Building>) {
+        if building.ground_floor().is_closed() {
+            info!(car = self.id, "parking lot closed, car turned away");
+            return;
+        }
+
+        let floors: Vec<usize> = (0..building.floors.len())
+            .filter(|&floor| {
+                let floor = &building.floors[floor];
+                !floor.is_closed() && floor.has_free_spot()
+            })
+            .collect();
+
+        if floors.is_empty() {
+            info!(car = self.id, "no free spot, car turned away");
+            return;
+        }
+
+        let floor = floors[self.rng.gen_range(0..floors.len())];
+
+        // Someone else may have taken the last one in the meantime
+        let Some(spot) = building.floors[floor].claim_spot(&mut self.rng) else {
+            info!(car = self.id, "no free spot, car turned away");
+            return;
+        };
+
+        if !building.enter() {
+            info!(car = self.id, "entry barrier didn't open, car turned away");
+            building.floors[floor].release_spot(spot);
+            return;
+        }
+
+        info!(
+            car = self.id,
+            floor,
+            spot,
+            dwell_secs = self.dwell.as_secs(),
+            "car entered"
+        );
+
+        for ramp in 1..=floor {
+            thread::sleep(DRIVING_TIME);
+            building.go_up(ramp);
+        }
+
+        thread::sleep(DRIVING_TIME);
+        building.floors[floor].park(spot);
+
+        thread::sleep(self.dwell);
+
+        building.floors[floor].unpark(spot);
+
+        for ramp in (1..=floor).rev() {
+            thread::sleep(DRIVING_TIME);
+            building.go_down(ramp);
+        }
+
+        thread::sleep(DRIVING_TIME);
+        building.exit();
+        building.floors[floor].release_spot(spot);
+
+        info!(car = self.id, floor, spot, "car left");
+    }
+}
@@ -0,0 +1,130 @@
+mod building;
+mod car;
+mod scenario;
+
+use crate::building::Building;
+use crate::car::Car;
+use crate::scenario::Scenario;
+use fse_trab_1_floor_controller::config::FloorConfig;
+use fse_trab_1_floor_controller::controller::{self, ControllerEvent};
+use fse_trab_1_floor_controller::utils::configure_graceful_shutdown;
+use fse_trab_1_logging::{self as logging, LoggingConfig};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    sync::{
+        mpsc::{Receiver, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::info;
+
+// Emulates the whole building with in memory pins: the floor controllers run as
+// they would on the Raspberry Pi, against the server on localhost, while virtual
+// cars go in, park and leave
+fn main() {
+    logging::init(&LoggingConfig::new(None).with_env_overrides());
+
+    let scenario = Scenario::load();
+    info!(
+        path = %Scenario::path(),
+        seed = scenario.seed,
+        "scenario loaded"
+    );
+
+    // Ctrl-C and the panics arrive like they do to a controller
+    let (events, receiver) = controller::channel();
+    configure_graceful_shutdown::send_shutdown_on_signals(&events);
+    let mut running = true;
+
+    let configs = scenario
+        .floors
+        .iter()
+        .map(|path| FloorConfig::load_from(path))
+        .collect();
+    let building = Arc::new(Building::new(configs));
+
+    let mut rng = StdRng::seed_from_u64(scenario.seed);
+    let mut cars = Vec::new();
+    let mut next_id = 1;
+
+    for phase in &scenario.phases {
+        info!(
+            duration_secs = phase.duration_secs,
+            arrivals_per_minute = phase.arrivals_per_minute,
+            "phase started"
+        );
+
+        let ends_at = Instant::now() + Duration::from_secs(phase.duration_secs);
+
+        loop {
+            // Exponential gaps make the arrivals a Poisson process with the given rate
+            let gap = if phase.arrivals_per_minute > 0.0 {
+                let uniform: f64 = rng.gen_range(f64::EPSILON..1.0);
+                Duration::from_secs_f64(-uniform.ln() * 60.0 / phase.arrivals_per_minute)
+            } else {
+                Duration::MAX
+            };
+
+            let arrives_at = Instant::now().checked_add(gap).unwrap_or(ends_at);
+
+            if arrives_at >= ends_at {
+                running = sleep_until(&receiver, ends_at);
+                break;
+            }
+
+            running = sleep_until(&receiver, arrives_at);
+
+            if !running {
+                break;
+            }
+
+            let car = Car {
+                id: next_id,
+                dwell: Duration::from_secs(
+                    rng.gen_range(scenario.min_dwell_secs..=scenario.max_dwell_secs),
+                ),
+                rng: StdRng::seed_from_u64(rng.gen()),
+            };
+            next_id += 1;
+
+            let building_clone = building.clone();
+            cars.push(thread::spawn(move || car.run(building_clone)));
+        }
+
+        if !running {
+            break;
+        }
+    }
+
+    // Let the cars still inside leave, unless asked to stop
+    info!(
+        cars = cars.len(),
+        "arrivals finished, waiting for the cars to leave"
+    );
+    while running && cars.iter().any(|car| !car.is_finished()) {
+        running = sleep_until(&receiver, Instant::now() + Duration::from_millis(100));
+    }
+
+    building.disconnect();
+    info!("simulation finished");
+}
+
+// Waits until `until` for a shutdown event. Returns whether the simulation goes on
+fn sleep_until(receiver: &Receiver<ControllerEvent>, until: Instant) -> bool {
+    loop {
+        let timeout = until.saturating_duration_since(Instant::now());
+
+        match receiver.recv_timeout(timeout) {
+            Ok(ControllerEvent::Shutdown(reason)) => {
+                info!(reason, "stopping the simulation");
+                return false;
+            }
+            // Nothing else is sent to the simulator
+            Ok(_) => {}
+            Err(RecvTimeoutError::Timeout) => return true,
+            Err(RecvTimeoutError::Disconnected) => return false,
+        }
+    }
+}
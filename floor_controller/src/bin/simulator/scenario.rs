@@ -0,0 +1,71 @@
+use serde::Deserialize;
+use std::{env, fs};
+
+pub const SCENARIO_PATH_ENV: &str = "FSE_SIMULATOR_SCENARIO";
+pub const DEFAULT_SCENARIO_PATH: &str = "./scenarios/default.toml";
+
+#[derive(Deserialize)]
+pub struct Scenario {
+    // The same seed drives the same cars, with the same dwell times and choices
+    pub seed: u64,
+    // Floor controller configurations from the ground floor up, the first one runs
+    // the barriers and the others a ramp
+    pub floors: Vec<String>,
+    // How long each car stays parked, picked uniformly between the two
+    pub min_dwell_secs: u64,
+    pub max_dwell_secs: u64,
+    // Run one after the other, cars stop arriving after the last one
+    #[serde(rename = "phase")]
+    pub phases: Vec<Phase>,
+}
+
+#[derive(Deserialize)]
+pub struct Phase {
+    pub duration_secs: u64,
+    // On average, the time between two cars is random (exponential)
+    pub arrivals_per_minute: f64,
+}
+
+impl Scenario {
+    pub fn path() -> String {
+        env::var(SCENARIO_PATH_ENV).unwrap_or_else(|_| DEFAULT_SCENARIO_PATH.to_string())
+    }
+
+    pub fn load() -> Self {
+        let path = Self::path();
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) => panic!("Could not read the scenario file {}: {}", path, error),
+        };
+
+        let scenario: Self = match toml::from_str(&contents) {
+            Ok(scenario) => scenario,
+            Err(error) => panic!("Invalid scenario file {}: {}", path, error),
+        };
+
+        if scenario.floors.is_empty() {
+            panic!("Invalid scenario file {}: floors can't be empty", path);
+        }
+
+        if scenario.min_dwell_secs > scenario.max_dwell_secs {
+            panic!(
+                "Invalid scenario file {}: min_dwell_secs is greater than max_dwell_secs",
+                path
+            );
+        }
+
+        if scenario
+            .phases
+            .iter()
+            .any(|phase| phase.arrivals_per_minute < 0.0)
+        {
+            panic!(
+                "Invalid scenario file {}: arrivals_per_minute can't be negative",
+                path
+            );
+        }
+
+        scenario
+    }
+}
@@ -0,0 +1,774 @@
+use crate::gpio::barrier::BarrierTimings;
+use crate::hal::{debounce::DebounceSettings, Pull};
+use serde::Deserialize;
+use std::{collections::HashMap, env, fs, process, time::Duration};
+use toml::{Table, Value};
+
+pub const CONFIG_PATH_ENV: &str = "FSE_FLOOR_CONFIG";
+pub const DEFAULT_CONFIG_PATH: &str = "./floor.toml";
+
+// Override the file, and are overridden by the command line flags
+pub const SERVER_URL_ENV: &str = "FSE_FLOOR_SERVER_URL";
+pub const CLIENT_ID_ENV: &str = "FSE_FLOOR_CLIENT_ID";
+pub const TOKEN_ENV: &str = "FSE_FLOOR_TOKEN";
+
+// Any key of the file can be set as FSE_FLOOR_<SECTION>_<KEY>, e.g.
+// FSE_FLOOR_BARRIER_TRAVEL_MILLIS=2500, and on the command line with --set
+pub const OVERRIDE_ENV_PREFIX: &str = "FSE_FLOOR_";
+
+// The sections of the file the overrides can set keys in
+const SECTIONS: [&str; 9] = [
+    "credentials",
+    "pins",
+    "role",
+    "timings",
+    "debounce",
+    "barrier",
+    "ramp",
+    "scanner",
+    "queue",
+];
+
+// The floors the server knows about
+const CLIENT_IDS: [&str; 3] = ["ground_floor", "first_floor", "second_floor"];
+
+// BCM numbers of the GPIO pins on the Raspberry Pi header
+const MAX_PIN: u8 = 27;
+
+const USAGE: &str = "\
+Usage: fse_trab_1_floor_controller [OPTIONS]
+
+Options:
+  --config <PATH>      Configuration file [env: FSE_FLOOR_CONFIG] [default: ./floor.toml]
+  --server-url <URL>   Server to connect to [env: FSE_FLOOR_SERVER_URL]
+  --client-id <ID>     ground_floor, first_floor or second_floor [env: FSE_FLOOR_CLIENT_ID]
+  --token <TOKEN>      Sent to the server as a bearer token [env: FSE_FLOOR_TOKEN]
+  --set <KEY=VALUE>    Sets a key of the file, as section.key=value, e.g.
+                       --set barrier.travel_millis=2500 or --set pins.input_pull=up.
+                       Can be repeated [env: FSE_FLOOR_<SECTION>_<KEY>]
+  -h, --help           Print this help";
+
+// Everything that sets one floor apart from the others, so a new floor is a new
+// file instead of a new build
+#[derive(Deserialize)]
+pub struct FloorConfig {
+    // Where it was loaded from, for the logs
+    #[serde(skip)]
+    pub path: String,
+    // Sent as the client id, the server tells the floors apart by it:
+    // "ground_floor", "first_floor" or "second_floor"
+    pub client_id: String,
+    #[serde(default = "default_server_url")]
+    pub server_url: String,
+    #[serde(default)]
+    pub credentials: CredentialsConfig,
+    pub pins: PinsConfig,
+    pub role: FloorRole,
+    #[serde(default)]
+    pub timings: TimingsConfig,
+    #[serde(default)]
+    pub debounce: DebounceConfig,
+    #[serde(default)]
+    pub barrier: BarrierConfig,
+    #[serde(default)]
+    pub ramp: RampConfig,
+    #[serde(default)]
+    pub scanner: ScannerConfig,
+    #[serde(default)]
+    pub queue: QueueConfig,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct CredentialsConfig {
+    // Sent in the Authorization header, for a server or proxy that asks for one
+    pub token: Option<String>,
+}
+
+// BCM numbers of the pins every floor has
+#[derive(Deserialize)]
+pub struct PinsConfig {
+    // Select the spot read by space_sensor, least significant bit first
+    pub space_address: [u8; 3],
+    pub space_sensor: u8,
+    // Lights the "full/closed" sign of the floor
+    pub closed_signal: u8,
+    // Resistor of every input pin: "down", "up" or "off". The sensors are still
+    // expected to go high when they see a car
+    #[serde(default = "default_input_pull")]
+    pub input_pull: Pull,
+}
+
+// What the floor does besides watching its spots
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FloorRole {
+    // The ground floor runs the entry and exit barriers and closes with the whole lot
+    Gates {
+        entry_open_signal: u8,
+        entry_close_signal: u8,
+        entry_engine: u8,
+        exit_open_signal: u8,
+        exit_close_signal: u8,
+        exit_engine: u8,
+    },
+    // The upper floors have two sensors on the ramp, the order they are
+    // triggered in tells if the car went up or down
+    Ramp {
+        pass_through_sensor_1: u8,
+        pass_through_sensor_2: u8,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct TimingsConfig {
+    // Between selecting a spot and reading its sensor
+    pub sensor_settle_millis: u64,
+    // Failed connections before the floor reports that it runs without the
+    // server, the client keeps connecting in the background
+    pub connect_attempts: u32,
+    pub connect_retry_millis: u64,
+    // Once connected, how many times the client tries to get the connection back,
+    // 0 keeps trying. The spot events are queued in the meantime
+    pub reconnect_attempts: u8,
+}
+
+impl Default for TimingsConfig {
+    fn default() -> Self {
+        Self {
+            sensor_settle_millis: 50,
+            connect_attempts: 10,
+            connect_retry_millis: 1000,
+            reconnect_attempts: 0,
+        }
+    }
+}
+
+// Filtering of the barrier and ramp sensors, the spot sensor is read on demand
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct DebounceConfig {
+    // A new level only counts once it held for this long
+    pub stable_millis: u64,
+    // Edges within this time of the last one that fired are merged into it
+    pub coalesce_millis: u64,
+    // Overrides by the name of the pin in [role], e.g.
+    // entry_close_signal = { stable_millis = 30, coalesce_millis = 1000 }
+    pub pins: HashMap<String, PinDebounceConfig>,
+    // How often the filtered glitches are reported to the server, 0 never does
+    pub report_interval_secs: u64,
+}
+
+impl Default for DebounceConfig {
+    fn default() -> Self {
+        Self {
+            stable_millis: 20,
+            coalesce_millis: 500,
+            pins: HashMap::new(),
+            report_interval_secs: 60,
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct PinDebounceConfig {
+    pub stable_millis: Option<u64>,
+    pub coalesce_millis: Option<u64>,
+}
+
+// Limits of the entry and exit barriers, only used by the gates
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct BarrierConfig {
+    // How long the arm takes to go up or down
+    pub travel_millis: u64,
+    // How long the barrier stays open waiting for the car to pass the close
+    // sensor, unless the car is still in front of it
+    pub pass_timeout_millis: u64,
+    // The engine is turned off after this long no matter what
+    pub max_motor_on_millis: u64,
+}
+
+impl Default for BarrierConfig {
+    fn default() -> Self {
+        Self {
+            travel_millis: 2000,
+            pass_timeout_millis: 10000,
+            max_motor_on_millis: 30000,
+        }
+    }
+}
+
+// Direction of the cars on the ramp, only used by the upper floors
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct RampConfig {
+    // Longest a car takes from one sensor of the ramp to the other, a passage seen
+    // by only one of them is dropped after it
+    pub passage_timeout_millis: u64,
+}
+
+impl Default for RampConfig {
+    fn default() -> Self {
+        Self {
+            passage_timeout_millis: 10000,
+        }
+    }
+}
+
+// Scan of the spots, which runs all the time
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct ScannerConfig {
+    // Time to go through the 8 spots
+    pub cycle_millis: u64,
+    // Cycles in a row a spot must read the same for the change to be reported
+    pub confirm_samples: u32,
+    // Longest time between a car passing the gate or the ramp and its spot
+    // changing, for the spot to be credited to it
+    pub match_window_millis: u64,
+    // Cars go by on their way to or from the floors above, so one going by without
+    // a spot changing isn't reported. Only the top floor turns it off
+    pub through_traffic: bool,
+}
+
+impl Default for ScannerConfig {
+    fn default() -> Self {
+        Self {
+            cycle_millis: 1000,
+            confirm_samples: 3,
+            match_window_millis: 30000,
+            through_traffic: true,
+        }
+    }
+}
+
+// Spot events the server couldn't be given, see socket/event_queue.rs
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct QueueConfig {
+    // Defaults to ./pending_<client_id>.jsonl, so the floors don't share a file
+    pub path: Option<String>,
+    // How often the queued events are tried again
+    pub replay_interval_millis: u64,
+    // How long the server has to acknowledge an event before it is sent again
+    pub ack_timeout_millis: u64,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            replay_interval_millis: 2000,
+            ack_timeout_millis: 5000,
+        }
+    }
+}
+
+// Values given on the command line, they win over the file and the environment
+#[derive(Default)]
+struct Flags {
+    config: Option<String>,
+    server_url: Option<String>,
+    client_id: Option<String>,
+    token: Option<String>,
+    // section.key and its value, in the order given
+    set: Vec<(String, String)>,
+}
+
+fn default_server_url() -> String {
+    "http://0.0.0.0:10380".to_string()
+}
+
+fn default_input_pull() -> Pull {
+    Pull::Down
+}
+
+impl FloorConfig {
+    pub fn path() -> String {
+        env::var(CONFIG_PATH_ENV).unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string())
+    }
+
+    pub fn queue_path(&self) -> String {
+        self.queue
+            .path
+            .clone()
+            .unwrap_or_else(|| format!("./pending_{}.jsonl", self.client_id))
+    }
+
+    // Reads the file given by --config or FSE_FLOOR_CONFIG, then applies the
+    // environment and the command line on top of it
+    pub fn load() -> Self {
+        let flags = Flags::parse(env::args().skip(1));
+        let path = flags.config.clone().unwrap_or_else(Self::path);
+
+        let mut overrides = env_overrides();
+        overrides.extend(flags.set);
+
+        let mut config = Self::read(&path, &overrides);
+
+        if let Ok(server_url) = env::var(SERVER_URL_ENV) {
+            config.server_url = server_url;
+        }
+
+        if let Ok(client_id) = env::var(CLIENT_ID_ENV) {
+            config.client_id = client_id;
+        }
+
+        if let Ok(token) = env::var(TOKEN_ENV) {
+            config.credentials.token = Some(token);
+        }
+
+        if let Some(server_url) = flags.server_url {
+            config.server_url = server_url;
+        }
+
+        if let Some(client_id) = flags.client_id {
+            config.client_id = client_id;
+        }
+
+        if let Some(token) = flags.token {
+            config.credentials.token = Some(token);
+        }
+
+        config.validate();
+        config
+    }
+
+    // Only the file, for running several floors in the same process
+    pub fn load_from(path: &str) -> Self {
+        let config = Self::read(path, &[]);
+
+        config.validate();
+        config
+    }
+
+    // The overrides are set on the file before it is read into the config, so
+    // they go through the same checks as the file
+    fn read(path: &str, overrides: &[(String, String)]) -> Self {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) => panic!("Could not read the configuration file {}: {}", path, error),
+        };
+
+        let mut table: Table = match contents.parse() {
+            Ok(table) => table,
+            Err(error) => panic!("Invalid configuration file {}: {}", path, error),
+        };
+
+        for (key, value) in overrides {
+            set_key(&mut table, key, value);
+        }
+
+        let mut config: Self = match table.try_into() {
+            Ok(config) => config,
+            Err(error) if overrides.is_empty() => {
+                panic!("Invalid configuration file {}: {}", path, error)
+            }
+            Err(error) => panic!(
+                "Invalid configuration file {} with the overrides: {}",
+                path, error
+            ),
+        };
+
+        config.path = path.to_string();
+        config
+    }
+
+    // Every problem is reported at once, so a new file can be fixed in one go
+    fn validate(&self) {
+        let mut errors = Vec::new();
+
+        if !CLIENT_IDS.contains(&self.client_id.as_str()) {
+            errors.push(format!(
+                "client_id \"{}\" must be one of {}",
+                self.client_id,
+                CLIENT_IDS.join(", ")
+            ));
+        }
+
+        // Only the ground floor has the barriers, the server counts the others by
+        // their ramp
+        match (self.client_id.as_str(), &self.role) {
+            ("ground_floor", FloorRole::Gates { .. }) => {}
+            ("first_floor" | "second_floor", FloorRole::Ramp { .. }) => {}
+            ("ground_floor", _) => {
+                errors.push("client_id \"ground_floor\" needs role.kind = \"gates\"".to_string())
+            }
+            (client_id, _) if CLIENT_IDS.contains(&client_id) => errors.push(format!(
+                "client_id \"{}\" needs role.kind = \"ramp\"",
+                client_id
+            )),
+            _ => {}
+        }
+
+        if !self.server_url.starts_with("http://") && !self.server_url.starts_with("https://") {
+            errors.push(format!(
+                "server_url \"{}\" must start with http:// or https://",
+                self.server_url
+            ));
+        }
+
+        if self.credentials.token.as_deref() == Some("") {
+            errors.push("credentials.token can't be empty, leave it out instead".to_string());
+        }
+
+        let mut used_by: HashMap<u8, &str> = HashMap::new();
+
+        for (name, pin) in self.named_pins() {
+            if pin > MAX_PIN {
+                errors.push(format!(
+                    "pins: {} = {} is not a GPIO pin (0 to {})",
+                    name, pin, MAX_PIN
+                ));
+            }
+
+            if let Some(other) = used_by.insert(pin, name) {
+                errors.push(format!(
+                    "pins: {} and {} are both set to {}",
+                    other, name, pin
+                ));
+            }
+        }
+
+        if self.timings.sensor_settle_millis > 1000 {
+            errors.push(format!(
+                "timings.sensor_settle_millis = {} is too long, a scan would take over 8s",
+                self.timings.sensor_settle_millis
+            ));
+        }
+
+        if self.timings.connect_attempts == 0 {
+            errors.push("timings.connect_attempts must be at least 1".to_string());
+        }
+
+        if self.barrier.pass_timeout_millis <= self.barrier.travel_millis {
+            errors.push(format!(
+                "barrier.pass_timeout_millis = {} must be longer than travel_millis = {}",
+                self.barrier.pass_timeout_millis, self.barrier.travel_millis
+            ));
+        }
+
+        if self.barrier.max_motor_on_millis < self.barrier.pass_timeout_millis {
+            errors.push(format!(
+                "barrier.max_motor_on_millis = {} can't be shorter than pass_timeout_millis = {}",
+                self.barrier.max_motor_on_millis, self.barrier.pass_timeout_millis
+            ));
+        }
+
+        // Both sensors must be able to see the car before its passage is dropped
+        if matches!(self.role, FloorRole::Ramp { .. })
+            && self.ramp.passage_timeout_millis <= self.debounce.coalesce_millis
+        {
+            errors.push(format!(
+                "ramp.passage_timeout_millis = {} must be longer than debounce.coalesce_millis = {}",
+                self.ramp.passage_timeout_millis, self.debounce.coalesce_millis
+            ));
+        }
+
+        if self.scanner.cycle_millis == 0
+            || self.scanner.cycle_millis < 8 * self.timings.sensor_settle_millis
+        {
+            errors.push(format!(
+                "scanner.cycle_millis = {} is shorter than reading the 8 spots ({}ms)",
+                self.scanner.cycle_millis,
+                8 * self.timings.sensor_settle_millis
+            ));
+        }
+
+        if self.scanner.confirm_samples == 0 {
+            errors.push("scanner.confirm_samples must be at least 1".to_string());
+        }
+
+        // A car must be able to park before its entry stops counting
+        let confirm_millis = self.scanner.cycle_millis * u64::from(self.scanner.confirm_samples);
+
+        if self.scanner.match_window_millis <= confirm_millis {
+            errors.push(format!(
+                "scanner.match_window_millis = {} must be longer than confirming a change ({}ms)",
+                self.scanner.match_window_millis, confirm_millis
+            ));
+        }
+
+        let input_names = self.role.input_names();
+
+        for name in self.debounce.pins.keys() {
+            if !input_names.contains(&name.as_str()) {
+                errors.push(format!(
+                    "debounce.pins: {} is not one of {}",
+                    name,
+                    input_names.join(", ")
+                ));
+            }
+        }
+
+        if self.queue.path.as_deref() == Some("") {
+            errors.push("queue.path can't be empty, leave it out instead".to_string());
+        }
+
+        if self.queue.replay_interval_millis == 0 {
+            errors.push("queue.replay_interval_millis must be at least 1".to_string());
+        }
+
+        if self.queue.ack_timeout_millis == 0 {
+            errors.push("queue.ack_timeout_millis must be at least 1".to_string());
+        }
+
+        if !errors.is_empty() {
+            panic!(
+                "Invalid configuration file {}:\n  - {}",
+                self.path,
+                errors.join("\n  - ")
+            );
+        }
+    }
+
+    fn named_pins(&self) -> Vec<(&'static str, u8)> {
+        let [address_1, address_2, address_3] = self.pins.space_address;
+
+        let mut pins = vec![
+            ("space_address[0]", address_1),
+            ("space_address[1]", address_2),
+            ("space_address[2]", address_3),
+            ("space_sensor", self.pins.space_sensor),
+            ("closed_signal", self.pins.closed_signal),
+        ];
+
+        match self.role {
+            FloorRole::Gates {
+                entry_open_signal,
+                entry_close_signal,
+                entry_engine,
+                exit_open_signal,
+                exit_close_signal,
+                exit_engine,
+            } => pins.extend([
+                ("role.entry_open_signal", entry_open_signal),
+                ("role.entry_close_signal", entry_close_signal),
+                ("role.entry_engine", entry_engine),
+                ("role.exit_open_signal", exit_open_signal),
+                ("role.exit_close_signal", exit_close_signal),
+                ("role.exit_engine", exit_engine),
+            ]),
+            FloorRole::Ramp {
+                pass_through_sensor_1,
+                pass_through_sensor_2,
+            } => pins.extend([
+                ("role.pass_through_sensor_1", pass_through_sensor_1),
+                ("role.pass_through_sensor_2", pass_through_sensor_2),
+            ]),
+        }
+
+        pins
+    }
+}
+
+impl FloorRole {
+    // The sensors of the role, which fire interrupts
+    pub fn input_names(&self) -> Vec<&'static str> {
+        match self {
+            FloorRole::Gates { .. } => vec![
+                "entry_open_signal",
+                "entry_close_signal",
+                "exit_open_signal",
+                "exit_close_signal",
+            ],
+            FloorRole::Ramp { .. } => vec!["pass_through_sensor_1", "pass_through_sensor_2"],
+        }
+    }
+}
+
+impl DebounceConfig {
+    // None when the glitches are never reported
+    pub fn report_interval(&self) -> Option<Duration> {
+        (self.report_interval_secs > 0).then(|| Duration::from_secs(self.report_interval_secs))
+    }
+
+    pub fn settings_for(&self, pin_name: &str) -> DebounceSettings {
+        let pin = self.pins.get(pin_name);
+
+        let stable_millis = pin
+            .and_then(|pin| pin.stable_millis)
+            .unwrap_or(self.stable_millis);
+        let coalesce_millis = pin
+            .and_then(|pin| pin.coalesce_millis)
+            .unwrap_or(self.coalesce_millis);
+
+        DebounceSettings {
+            stable_time: Duration::from_millis(stable_millis),
+            coalesce_time: Duration::from_millis(coalesce_millis),
+        }
+    }
+}
+
+impl BarrierConfig {
+    pub fn timings(&self) -> BarrierTimings {
+        BarrierTimings {
+            travel: Duration::from_millis(self.travel_millis),
+            pass_timeout: Duration::from_millis(self.pass_timeout_millis),
+            max_motor_on: Duration::from_millis(self.max_motor_on_millis),
+        }
+    }
+}
+
+impl RampConfig {
+    pub fn passage_timeout(&self) -> Duration {
+        Duration::from_millis(self.passage_timeout_millis)
+    }
+}
+
+impl ScannerConfig {
+    pub fn cycle(&self) -> Duration {
+        Duration::from_millis(self.cycle_millis)
+    }
+
+    pub fn match_window(&self) -> Duration {
+        Duration::from_millis(self.match_window_millis)
+    }
+}
+
+impl QueueConfig {
+    pub fn replay_interval(&self) -> Duration {
+        Duration::from_millis(self.replay_interval_millis)
+    }
+
+    pub fn ack_timeout(&self) -> Duration {
+        Duration::from_millis(self.ack_timeout_millis)
+    }
+}
+
+impl TimingsConfig {
+    pub fn sensor_settle(&self) -> Duration {
+        Duration::from_millis(self.sensor_settle_millis)
+    }
+
+    pub fn connect_retry(&self) -> Duration {
+        Duration::from_millis(self.connect_retry_millis)
+    }
+}
+
+// FSE_FLOOR_<SECTION>_<KEY> variables as section.key. The section can't have an
+// underscore, the rest is the key
+fn env_overrides() -> Vec<(String, String)> {
+    let mut overrides: Vec<(String, String)> = env::vars()
+        .filter_map(|(name, value)| {
+            let name = name.strip_prefix(OVERRIDE_ENV_PREFIX)?.to_lowercase();
+            let (section, key) = name.split_once('_')?;
+
+            SECTIONS
+                .contains(&section)
+                .then(|| (format!("{}.{}", section, key), value))
+        })
+        .collect();
+
+    // The environment has no order of its own
+    overrides.sort();
+    overrides
+}
+
+// Sets the dotted key, e.g. debounce.pins.entry_open_signal.stable_millis. The
+// value is read as TOML, so numbers, booleans and arrays keep their type and
+// anything else is a string
+fn set_key(table: &mut Table, key: &str, value: &str) {
+    let mut names: Vec<&str> = key.split('.').collect();
+
+    if names.len() < 2 || !SECTIONS.contains(&names[0]) {
+        panic!(
+            "Invalid override {}: it must be section.key, the section one of {}",
+            key,
+            SECTIONS.join(", ")
+        );
+    }
+
+    let last = names.pop().unwrap();
+    let mut table = table;
+
+    for name in names {
+        table = match table
+            .entry(name)
+            .or_insert_with(|| Value::Table(Table::new()))
+        {
+            Value::Table(table) => table,
+            _ => panic!("Invalid override {}: {} is not a section", key, name),
+        };
+    }
+
+    let value = match format!("value = {}", value).parse::<Table>() {
+        Ok(mut parsed) => parsed.remove("value").unwrap(),
+        Err(_) => Value::String(value.to_string()),
+    };
+
+    table.insert(last.to_string(), value);
+}
+
+impl Flags {
+    // Accepts both "--flag value" and "--flag=value"
+    fn parse(args: impl Iterator<Item = String>) -> Flags {
+        let mut flags = Flags::default();
+        let mut args = args;
+
+        while let Some(arg) = args.next() {
+            if arg == "-h" || arg == "--help" {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+
+            if name == "--set" {
+                let value = match inline_value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => panic!("Missing the value of {}\n\n{}", name, USAGE),
+                };
+
+                match value.split_once('=') {
+                    Some((key, value)) => flags.set.push((key.to_string(), value.to_string())),
+                    None => panic!("--set {} must be section.key=value\n\n{}", value, USAGE),
+                }
+
+                continue;
+            }
+
+            let slot = match name.as_str() {
+                "--config" => &mut flags.config,
+                "--server-url" => &mut flags.server_url,
+                "--client-id" => &mut flags.client_id,
+                "--token" => &mut flags.token,
+                _ => panic!("Unknown argument {}\n\n{}", name, USAGE),
+            };
+
+            let value = match inline_value {
+                Some(value) => value,
+                None => match args.next() {
+                    Some(value) => value,
+                    None => panic!("Missing the value of {}\n\n{}", name, USAGE),
+                },
+            };
+
+            *slot = Some(value);
+        }
+
+        flags
+    }
+}
+
+#[cfg(test)]
+impl FloorConfig {
+    // The second floor of config/, with the queue in a file of the test alone
+    pub fn for_tests(name: &str) -> FloorConfig {
+        let mut config: FloorConfig =
+            toml::from_str(include_str!("../config/second_floor.toml")).unwrap();
+
+        let path =
+            env::temp_dir().join(format!("floor_controller_{}_{}.jsonl", name, process::id()));
+        fs::remove_file(&path).ok();
+        config.queue.path = Some(path.to_string_lossy().into_owned());
+
+        config
+    }
+}
@@ -0,0 +1,261 @@
+use crate::config::FloorConfig;
+use crate::gpio::gpio_async_interrupts::{Input, RoleHandler};
+use crate::gpio::gpio_pins::GpioPins;
+use crate::gpio::spot_scanner;
+use crate::model::{LotOccupancyPayload, ParkingLot};
+use crate::socket::event_queue::EventQueue;
+use crate::socket::socket_client::ServerClient;
+use crate::socket::socket_reports::GlitchReporter;
+use std::{
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+use tracing::{error, info, warn};
+
+// Everything the controller reacts to, sent by the interrupts, the socket client
+// and the shutdown handlers. They are handled one at a time by Controller::run,
+// which sleeps while there is none
+pub enum ControllerEvent {
+    // Ctrl-C or a panic, with what it was
+    Shutdown(&'static str),
+    // A sensor of the role fired, with the time it did
+    Input(Input, i64),
+    // The server closed or opened the floor, or the lot for the ground floor
+    SetClosed(bool),
+    // Only sent to the ground floor
+    LotOccupancy(LotOccupancyPayload),
+    ConnectionClosed,
+}
+
+pub fn channel() -> (Sender<ControllerEvent>, Receiver<ControllerEvent>) {
+    mpsc::channel()
+}
+
+// Work done every so often on the controller thread, between the events
+struct Timer {
+    task: Task,
+    every: Duration,
+    // None while disarmed
+    next_at: Option<Instant>,
+}
+
+enum Task {
+    TickRole,
+    ReplayQueue,
+    ReportGlitches,
+}
+
+pub struct Controller {
+    floor: String,
+    gpio_pins: GpioPins,
+    // Taken on shutdown, which stops the scanner
+    role: Option<RoleHandler>,
+    scanner: JoinHandle<()>,
+    client: Arc<ServerClient>,
+    event_queue: Arc<EventQueue>,
+    glitch_reporter: GlitchReporter,
+    events: Receiver<ControllerEvent>,
+    timers: Vec<Timer>,
+}
+
+impl Controller {
+    // Starts the scanner and the interrupts, which only queue events until run
+    // is called
+    pub fn new(
+        config: &FloorConfig,
+        mut gpio_pins: GpioPins,
+        client: &Arc<ServerClient>,
+        parking_lot: &Arc<Mutex<ParkingLot>>,
+        event_queue: &Arc<EventQueue>,
+        events: &Sender<ControllerEvent>,
+        receiver: Receiver<ControllerEvent>,
+    ) -> Controller {
+        // The spots are only read by the scanner, the interrupts just tell it when
+        // a car went by
+        let (car_events, scanner) =
+            spot_scanner::spawn(&gpio_pins, config, client, parking_lot, event_queue);
+
+        let role = gpio_pins.setup_interrupts(config, client, car_events, events);
+
+        let now = Instant::now();
+        let mut timers = vec![
+            Timer {
+                task: Task::ReplayQueue,
+                every: config.queue.replay_interval(),
+                next_at: Some(now),
+            },
+            // The timeouts of the barriers or of the passages on the ramp, armed
+            // by the inputs that start one
+            Timer {
+                task: Task::TickRole,
+                every: role.tick_every(),
+                next_at: None,
+            },
+        ];
+
+        if let Some(interval) = config.debounce.report_interval() {
+            timers.push(Timer {
+                task: Task::ReportGlitches,
+                every: interval,
+                next_at: Some(now + interval),
+            });
+        }
+
+        Controller {
+            floor: config.client_id.clone(),
+            glitch_reporter: GlitchReporter::new(config, client, &gpio_pins.glitch_counters),
+            gpio_pins,
+            role: Some(role),
+            scanner,
+            client: client.clone(),
+            event_queue: event_queue.clone(),
+            events: receiver,
+            timers,
+        }
+    }
+
+    // Blocks until a shutdown event, then stops the interrupts, sends what is
+    // left in the queue and disconnects, in that order
+    pub fn run(mut self) {
+        info!(floor = self.floor.as_str(), "controller running");
+
+        loop {
+            let next_at = self.timers.iter().filter_map(|timer| timer.next_at).min();
+            let timeout = next_at.map_or(Duration::MAX, |next_at| {
+                next_at.saturating_duration_since(Instant::now())
+            });
+
+            match self.events.recv_timeout(timeout) {
+                Ok(ControllerEvent::Shutdown(reason)) => {
+                    info!(floor = self.floor.as_str(), reason, "shutting down");
+                    break;
+                }
+                Ok(event) => self.handle(event),
+                Err(RecvTimeoutError::Timeout) => {}
+                // Every sender is gone, nothing can wake the controller anymore
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            self.run_timers();
+        }
+
+        self.shut_down();
+    }
+
+    fn handle(&mut self, event: ControllerEvent) {
+        let lot_occupancy = self.gpio_pins.lot_occupancy();
+
+        match event {
+            ControllerEvent::Input(input, timestamp) => {
+                if let Some(role) = &mut self.role {
+                    role.handle(input, timestamp);
+                }
+
+                self.arm_role_tick();
+            }
+            ControllerEvent::SetClosed(closed) => match lot_occupancy {
+                // The ground floor has to know, it decides on its own without the server
+                Some(lot_occupancy) => lot_occupancy.server_set_closed(closed),
+                None if closed => self.gpio_pins.closed_signal.lock().unwrap().set_high(),
+                None => self.gpio_pins.closed_signal.lock().unwrap().set_low(),
+            },
+            ControllerEvent::LotOccupancy(occupancy) => {
+                if let Some(lot_occupancy) = lot_occupancy {
+                    lot_occupancy.reconcile(occupancy);
+                }
+            }
+            ControllerEvent::ConnectionClosed => match lot_occupancy {
+                Some(lot_occupancy) => lot_occupancy.disconnected(),
+                None => warn!(
+                    floor = self.floor.as_str(),
+                    "server unreachable, the spot events are queued"
+                ),
+            },
+            ControllerEvent::Shutdown(_) => unreachable!(),
+        }
+    }
+
+    fn run_timers(&mut self) {
+        let now = Instant::now();
+
+        for timer in self.timers.iter_mut() {
+            if timer.next_at.is_none_or(|next_at| next_at > now) {
+                continue;
+            }
+
+            // From now, so a long pause doesn't run it several times in a row
+            timer.next_at = Some(now + timer.every);
+
+            match timer.task {
+                Task::TickRole => {
+                    if let Some(role) = &mut self.role {
+                        role.tick();
+
+                        // Until an input starts a barrier or a passage again
+                        if !role.has_deadline() {
+                            timer.next_at = None;
+                        }
+                    }
+                }
+                Task::ReplayQueue => self.event_queue.replay(&self.client),
+                Task::ReportGlitches => self.glitch_reporter.report(),
+            }
+        }
+    }
+
+    // Only ticked while a barrier engine or a passage on the ramp can time out
+    fn arm_role_tick(&mut self) {
+        let pending = self.role.as_ref().is_some_and(RoleHandler::has_deadline);
+        let now = Instant::now();
+
+        for timer in self.timers.iter_mut() {
+            if matches!(timer.task, Task::TickRole) && pending && timer.next_at.is_none() {
+                timer.next_at = Some(now + timer.every);
+            }
+        }
+    }
+
+    fn shut_down(mut self) {
+        // No sensor reaches the controller anymore and the engines are off
+        self.gpio_pins.clear_interrupts();
+
+        if let Some(role) = self.role.take() {
+            role.turn_off();
+        }
+
+        // The scanner stops once the role is gone, with the car events it held. If
+        // it panicked the queue and the connection are still dealt with
+        if let Err(panic) = self.scanner.join() {
+            let reason = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown");
+
+            error!(
+                floor = self.floor.as_str(),
+                reason, "the spot scanner panicked"
+            );
+        }
+
+        self.event_queue.flush(&self.client);
+
+        if !self.event_queue.is_empty() {
+            warn!(
+                floor = self.floor.as_str(),
+                events = self.event_queue.len(),
+                "events left in the queue, they are sent on the next run"
+            );
+        }
+
+        if let Err(error) = self.client.disconnect() {
+            warn!(floor = self.floor.as_str(), %error, "failed to disconnect");
+        }
+
+        info!(floor = self.floor.as_str(), "controller stopped");
+    }
+}
@@ -0,0 +1,420 @@
+use crate::hal::{InputPin, Level, OutputPin};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+// How often the timeouts are checked
+pub const TICK: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy)]
+pub struct BarrierTimings {
+    // How long the arm takes to go up or down
+    pub travel: Duration,
+    // How long the barrier waits open for the car to reach the close sensor
+    pub pass_timeout: Duration,
+    // Longest the engine may stay on, whatever the sensors say
+    pub max_motor_on: Duration,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BarrierState {
+    Closed,
+    Opening,
+    Open,
+    Closing,
+    // The engine was turned off by a timeout, the next car opens it again
+    Fault(BarrierFault),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BarrierFault {
+    // Nothing reached the close sensor and the car is gone from the open one, it
+    // backed away or the close sensor is broken
+    CloseSignalTimeout,
+    // The engine hit its limit with the car still in front of the barrier
+    MotorTimeout,
+}
+
+// What the barrier tells whoever watches it, the faults and the first full cycle
+// after one
+pub enum BarrierEvent {
+    Fault(BarrierFault),
+    Recovered,
+}
+
+pub type BarrierListener = Box<dyn Fn(BarrierEvent) + Send + Sync>;
+
+// One barrier of the gates, driven by its open sensor (a car in front of it), its
+// close sensor (the car went past it) and the timeouts, so the engine never stays
+// on because a sensor didn't fire
+pub struct Barrier {
+    name: &'static str,
+    timings: BarrierTimings,
+    engine: Arc<Mutex<Box<dyn OutputPin>>>,
+    open_signal: Arc<Mutex<Box<dyn InputPin>>>,
+    machine: Mutex<Machine>,
+    listener: BarrierListener,
+}
+
+struct Machine {
+    state: BarrierState,
+    entered_at: Instant,
+    motor_on_at: Option<Instant>,
+    // Last fault, until the barrier closes normally again
+    fault: Option<BarrierFault>,
+}
+
+impl BarrierState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BarrierState::Closed => "closed",
+            BarrierState::Opening => "opening",
+            BarrierState::Open => "open",
+            BarrierState::Closing => "closing",
+            BarrierState::Fault(_) => "fault",
+        }
+    }
+}
+
+impl BarrierFault {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BarrierFault::CloseSignalTimeout => "close_signal_timeout",
+            BarrierFault::MotorTimeout => "motor_timeout",
+        }
+    }
+}
+
+impl Barrier {
+    // Starts closed with the engine off, the timeouts are checked by calling tick
+    // every TICK while has_deadline
+    pub fn new(
+        name: &'static str,
+        timings: BarrierTimings,
+        engine: Arc<Mutex<Box<dyn OutputPin>>>,
+        open_signal: Arc<Mutex<Box<dyn InputPin>>>,
+        listener: BarrierListener,
+    ) -> Barrier {
+        engine.lock().unwrap().set_low();
+
+        Barrier {
+            name,
+            timings,
+            engine,
+            open_signal,
+            machine: Mutex::new(Machine {
+                state: BarrierState::Closed,
+                entered_at: Instant::now(),
+                motor_on_at: None,
+                fault: None,
+            }),
+            listener,
+        }
+    }
+
+    // A car is in front of the barrier. False when the barrier was already opening
+    // or open, the edge is the same car bouncing on the sensor and isn't counted
+    pub fn on_open_signal(&self) -> bool {
+        let mut machine = self.machine.lock().unwrap();
+
+        match machine.state {
+            BarrierState::Closed | BarrierState::Fault(_) => {
+                self.start_opening(&mut machine);
+                true
+            }
+            BarrierState::Closing => {
+                info!(
+                    barrier = self.name,
+                    "car in front of the closing barrier, opening it again"
+                );
+                self.start_opening(&mut machine);
+                true
+            }
+            BarrierState::Opening | BarrierState::Open => false,
+        }
+    }
+
+    // The car went past the barrier. False when the barrier was not open, the
+    // edge is ignored and no car is counted
+    pub fn on_close_signal(&self) -> bool {
+        let mut machine = self.machine.lock().unwrap();
+
+        match machine.state {
+            BarrierState::Opening | BarrierState::Open => {
+                self.stop_motor(&mut machine);
+                self.enter(&mut machine, BarrierState::Closing);
+                true
+            }
+            state => {
+                warn!(
+                    barrier = self.name,
+                    state = state.as_str(),
+                    "close signal with the barrier not open, ignored"
+                );
+                false
+            }
+        }
+    }
+
+    // Closed or in fault the engine is off and nothing times out
+    pub fn has_deadline(&self) -> bool {
+        let machine = self.machine.lock().unwrap();
+
+        !matches!(machine.state, BarrierState::Closed | BarrierState::Fault(_))
+    }
+
+    pub fn tick(&self) {
+        let event = {
+            let mut machine = self.machine.lock().unwrap();
+            self.check_timeouts(&mut machine, Instant::now())
+        };
+
+        // Without the lock, the listener may take a while to report it
+        if let Some(event) = event {
+            (self.listener)(event);
+        }
+    }
+
+    fn check_timeouts(&self, machine: &mut Machine, now: Instant) -> Option<BarrierEvent> {
+        let motor_on_for = machine.motor_on_at.map(|on_at| now - on_at);
+
+        if motor_on_for.is_some_and(|on_for| on_for >= self.timings.max_motor_on) {
+            return Some(self.fail(machine, BarrierFault::MotorTimeout));
+        }
+
+        let in_state_for = now - machine.entered_at;
+
+        match machine.state {
+            BarrierState::Opening if in_state_for >= self.timings.travel => {
+                self.enter(machine, BarrierState::Open);
+            }
+            // A car still in front of it keeps it open, up to the engine limit
+            BarrierState::Open
+                if motor_on_for.is_some_and(|on_for| on_for >= self.timings.pass_timeout)
+                    && !self.car_present() =>
+            {
+                return Some(self.fail(machine, BarrierFault::CloseSignalTimeout));
+            }
+            BarrierState::Closing if in_state_for >= self.timings.travel => {
+                // Never lower it on a car, e.g. one that came right behind the last
+                if self.car_present() {
+                    info!(
+                        barrier = self.name,
+                        "car in front of the barrier, opening it again"
+                    );
+                    self.start_opening(machine);
+                    return None;
+                }
+
+                self.enter(machine, BarrierState::Closed);
+
+                if let Some(fault) = machine.fault.take() {
+                    info!(
+                        barrier = self.name,
+                        fault = fault.as_str(),
+                        "barrier back to normal"
+                    );
+                    return Some(BarrierEvent::Recovered);
+                }
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    fn start_opening(&self, machine: &mut Machine) {
+        self.engine.lock().unwrap().set_high();
+        machine.motor_on_at = Some(Instant::now());
+        self.enter(machine, BarrierState::Opening);
+    }
+
+    // Leaves the arm where it is, for when the controller stops
+    pub fn turn_off(&self) {
+        let mut machine = self.machine.lock().unwrap();
+        self.stop_motor(&mut machine);
+    }
+
+    fn stop_motor(&self, machine: &mut Machine) {
+        self.engine.lock().unwrap().set_low();
+        machine.motor_on_at = None;
+    }
+
+    fn fail(&self, machine: &mut Machine, fault: BarrierFault) -> BarrierEvent {
+        self.stop_motor(machine);
+        self.enter(machine, BarrierState::Fault(fault));
+        machine.fault = Some(fault);
+
+        warn!(
+            barrier = self.name,
+            fault = fault.as_str(),
+            "barrier fault, engine turned off"
+        );
+
+        BarrierEvent::Fault(fault)
+    }
+
+    fn enter(&self, machine: &mut Machine, state: BarrierState) {
+        machine.state = state;
+        machine.entered_at = Instant::now();
+    }
+
+    fn car_present(&self) -> bool {
+        self.open_signal.lock().unwrap().read() == Level::High
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::{mock_gpio::MockGpio, Gpio, Pull};
+
+    const ENGINE: u8 = 1;
+    const OPEN_SIGNAL: u8 = 2;
+
+    const TIMINGS: BarrierTimings = BarrierTimings {
+        travel: Duration::from_secs(2),
+        pass_timeout: Duration::from_secs(10),
+        max_motor_on: Duration::from_secs(30),
+    };
+
+    fn barrier(gpio: &MockGpio) -> Barrier {
+        Barrier::new(
+            "entry",
+            TIMINGS,
+            Arc::new(Mutex::new(gpio.output_low(ENGINE).unwrap())),
+            Arc::new(Mutex::new(gpio.input(OPEN_SIGNAL, Pull::Down).unwrap())),
+            Box::new(|_| {}),
+        )
+    }
+
+    // The timeouts as checked by a tick `after` from now
+    fn tick_after(barrier: &Barrier, after: Duration) -> Option<BarrierEvent> {
+        let mut machine = barrier.machine.lock().unwrap();
+        barrier.check_timeouts(&mut machine, Instant::now() + after)
+    }
+
+    fn state(barrier: &Barrier) -> BarrierState {
+        barrier.machine.lock().unwrap().state
+    }
+
+    #[test]
+    fn lets_a_car_through() {
+        let gpio = MockGpio::new();
+        let barrier = barrier(&gpio);
+
+        gpio.set_input(OPEN_SIGNAL, Level::High);
+        assert!(barrier.on_open_signal());
+        assert!(state(&barrier) == BarrierState::Opening);
+        assert_eq!(gpio.output(ENGINE), Level::High);
+
+        assert!(tick_after(&barrier, TIMINGS.travel).is_none());
+        assert!(state(&barrier) == BarrierState::Open);
+
+        gpio.set_input(OPEN_SIGNAL, Level::Low);
+        assert!(barrier.on_close_signal());
+        assert!(state(&barrier) == BarrierState::Closing);
+        assert_eq!(gpio.output(ENGINE), Level::Low);
+
+        assert!(tick_after(&barrier, TIMINGS.travel).is_none());
+        assert!(state(&barrier) == BarrierState::Closed);
+        assert!(!barrier.has_deadline());
+    }
+
+    #[test]
+    fn counts_a_bouncing_open_signal_once() {
+        let gpio = MockGpio::new();
+        let barrier = barrier(&gpio);
+
+        assert!(barrier.on_open_signal());
+        assert!(!barrier.on_open_signal());
+
+        tick_after(&barrier, TIMINGS.travel);
+        assert!(!barrier.on_open_signal());
+    }
+
+    #[test]
+    fn ignores_a_close_signal_with_the_barrier_closed() {
+        let gpio = MockGpio::new();
+        let barrier = barrier(&gpio);
+
+        assert!(!barrier.on_close_signal());
+        assert!(state(&barrier) == BarrierState::Closed);
+    }
+
+    #[test]
+    fn turns_the_engine_off_when_the_car_backs_away() {
+        let gpio = MockGpio::new();
+        let barrier = barrier(&gpio);
+
+        barrier.on_open_signal();
+        tick_after(&barrier, TIMINGS.travel);
+
+        let event = tick_after(&barrier, TIMINGS.pass_timeout);
+        assert!(matches!(
+            event,
+            Some(BarrierEvent::Fault(BarrierFault::CloseSignalTimeout))
+        ));
+        assert!(state(&barrier) == BarrierState::Fault(BarrierFault::CloseSignalTimeout));
+        assert_eq!(gpio.output(ENGINE), Level::Low);
+        assert!(!barrier.has_deadline());
+    }
+
+    #[test]
+    fn keeps_it_open_for_a_car_in_front_up_to_the_engine_limit() {
+        let gpio = MockGpio::new();
+        let barrier = barrier(&gpio);
+
+        gpio.set_input(OPEN_SIGNAL, Level::High);
+        barrier.on_open_signal();
+        tick_after(&barrier, TIMINGS.travel);
+
+        assert!(tick_after(&barrier, TIMINGS.pass_timeout).is_none());
+        assert!(state(&barrier) == BarrierState::Open);
+
+        let event = tick_after(&barrier, TIMINGS.max_motor_on);
+        assert!(matches!(
+            event,
+            Some(BarrierEvent::Fault(BarrierFault::MotorTimeout))
+        ));
+        assert_eq!(gpio.output(ENGINE), Level::Low);
+    }
+
+    #[test]
+    fn recovers_after_the_next_full_cycle() {
+        let gpio = MockGpio::new();
+        let barrier = barrier(&gpio);
+
+        barrier.on_open_signal();
+        tick_after(&barrier, TIMINGS.travel);
+        tick_after(&barrier, TIMINGS.pass_timeout);
+
+        assert!(barrier.on_open_signal());
+        assert_eq!(gpio.output(ENGINE), Level::High);
+        tick_after(&barrier, TIMINGS.travel);
+        barrier.on_close_signal();
+
+        let event = tick_after(&barrier, TIMINGS.travel);
+        assert!(matches!(event, Some(BarrierEvent::Recovered)));
+        assert!(state(&barrier) == BarrierState::Closed);
+    }
+
+    #[test]
+    fn opens_again_for_a_car_right_behind() {
+        let gpio = MockGpio::new();
+        let barrier = barrier(&gpio);
+
+        barrier.on_open_signal();
+        tick_after(&barrier, TIMINGS.travel);
+        barrier.on_close_signal();
+
+        gpio.set_input(OPEN_SIGNAL, Level::High);
+        gpio.record_writes();
+
+        assert!(tick_after(&barrier, TIMINGS.travel).is_none());
+        assert!(state(&barrier) == BarrierState::Opening);
+        assert_eq!(gpio.writes(), vec![(ENGINE, Level::High)]);
+    }
+}
@@ -0,0 +1,288 @@
+use crate::config::FloorConfig;
+use crate::controller::ControllerEvent;
+use crate::gpio::barrier::{Barrier, BarrierEvent};
+use crate::gpio::gpio_pins::{GpioPins, RolePins};
+use crate::gpio::lot_occupancy::LotOccupancy;
+use crate::gpio::ramp::{Direction, Ramp, RampEvent, RampSensor};
+use crate::gpio::spot_scanner::CarEvents;
+use crate::gpio::{barrier, ramp};
+use crate::hal::{InputPin, OutputPin, Trigger};
+use crate::model::{AmbiguousPassagePayload, BarrierFaultPayload, BarrierRecoveredPayload};
+use crate::socket::socket_client::ServerClient;
+use crate::socket::socket_operations::{AMBIGUOUS_PASSAGE, BARRIER_FAULT, BARRIER_RECOVERED};
+use chrono::Utc;
+use std::sync::{mpsc::Sender, Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
+
+// The sensors of the roles, the interrupts only tell the controller which one
+// fired and when
+#[derive(Clone, Copy)]
+pub enum Input {
+    EntryOpenSignal,
+    EntryCloseSignal,
+    ExitOpenSignal,
+    ExitCloseSignal,
+    PassThroughSensor1,
+    PassThroughSensor2,
+}
+
+// What the floor does with the inputs of its role, run by the controller
+pub enum RoleHandler {
+    // The barriers are much bigger than the ramp
+    Gates(Box<GateHandler>),
+    Ramp(RampHandler),
+}
+
+pub struct GateHandler {
+    entry_barrier: Barrier,
+    exit_barrier: Barrier,
+    car_events: CarEvents,
+    lot_occupancy: Arc<LotOccupancy>,
+}
+
+pub struct RampHandler {
+    ramp: Ramp,
+}
+
+pub fn configure(
+    gpio_pins: &mut GpioPins,
+    config: &FloorConfig,
+    client: &Arc<ServerClient>,
+    car_events: CarEvents,
+    events: &Sender<ControllerEvent>,
+) -> RoleHandler {
+    match &mut gpio_pins.role {
+        RolePins::Gates(gate_pins) => {
+            forward_interrupt(
+                gate_pins.entry_open_signal.lock().unwrap().as_mut(),
+                Input::EntryOpenSignal,
+                events,
+            );
+            forward_interrupt(
+                gate_pins.entry_close_signal.as_mut(),
+                Input::EntryCloseSignal,
+                events,
+            );
+            forward_interrupt(
+                gate_pins.exit_open_signal.lock().unwrap().as_mut(),
+                Input::ExitOpenSignal,
+                events,
+            );
+            forward_interrupt(
+                gate_pins.exit_close_signal.as_mut(),
+                Input::ExitCloseSignal,
+                events,
+            );
+
+            RoleHandler::Gates(Box::new(GateHandler {
+                entry_barrier: new_barrier(
+                    "entry",
+                    &gate_pins.entry_engine,
+                    &gate_pins.entry_open_signal,
+                    config,
+                    client,
+                ),
+                exit_barrier: new_barrier(
+                    "exit",
+                    &gate_pins.exit_engine,
+                    &gate_pins.exit_open_signal,
+                    config,
+                    client,
+                ),
+                car_events,
+                lot_occupancy: gate_pins.lot_occupancy.clone(),
+            }))
+        }
+        RolePins::Ramp(ramp_pins) => {
+            forward_interrupt(
+                ramp_pins.pass_through_sensor_1.as_mut(),
+                Input::PassThroughSensor1,
+                events,
+            );
+            forward_interrupt(
+                ramp_pins.pass_through_sensor_2.as_mut(),
+                Input::PassThroughSensor2,
+                events,
+            );
+
+            RoleHandler::Ramp(RampHandler {
+                ramp: new_ramp(config, client, car_events),
+            })
+        }
+    }
+}
+
+// The time is taken in the interrupt, not when the controller gets to it
+fn forward_interrupt(pin: &mut dyn InputPin, input: Input, events: &Sender<ControllerEvent>) {
+    let events_clone = events.clone();
+
+    pin.set_async_interrupt(
+        Trigger::RisingEdge,
+        Box::new(move |_| {
+            let timestamp = Utc::now().timestamp();
+
+            events_clone
+                .send(ControllerEvent::Input(input, timestamp))
+                .ok();
+        }),
+    )
+    .unwrap();
+}
+
+// The faults of the barrier and its recovery are reported to the server
+fn new_barrier(
+    name: &'static str,
+    engine: &Arc<Mutex<Box<dyn OutputPin>>>,
+    open_signal: &Arc<Mutex<Box<dyn InputPin>>>,
+    config: &FloorConfig,
+    client: &Arc<ServerClient>,
+) -> Barrier {
+    let client_clone = client.clone();
+
+    Barrier::new(
+        name,
+        config.barrier.timings(),
+        engine.clone(),
+        open_signal.clone(),
+        Box::new(move |event| {
+            let timestamp = Utc::now().timestamp();
+            let result = match event {
+                BarrierEvent::Fault(fault) => client_clone.emit(
+                    BARRIER_FAULT,
+                    BarrierFaultPayload {
+                        barrier: name.to_string(),
+                        fault: fault.as_str().to_string(),
+                        timestamp,
+                    },
+                ),
+                BarrierEvent::Recovered => client_clone.emit(
+                    BARRIER_RECOVERED,
+                    BarrierRecoveredPayload {
+                        barrier: name.to_string(),
+                        timestamp,
+                    },
+                ),
+            };
+
+            if let Err(error) = result {
+                warn!(barrier = name, %error, "failed to report the barrier");
+            }
+        }),
+    )
+}
+
+// The cars that went by are handed to the scanner, the ambiguous passages are
+// reported to the server
+fn new_ramp(config: &FloorConfig, client: &Arc<ServerClient>, car_events: CarEvents) -> Ramp {
+    let client_clone = client.clone();
+
+    Ramp::new(
+        config.ramp.passage_timeout(),
+        Box::new(move |event| match event {
+            RampEvent::Passed(Direction::Up, timestamp) => car_events.entered(timestamp),
+            RampEvent::Passed(Direction::Down, timestamp) => car_events.left(timestamp),
+            RampEvent::Ambiguous(passage) => {
+                let result = client_clone.emit(
+                    AMBIGUOUS_PASSAGE,
+                    AmbiguousPassagePayload {
+                        first_sensor: passage.first_sensor.as_str().to_string(),
+                        timestamp: passage.timestamp,
+                    },
+                );
+
+                if let Err(error) = result {
+                    warn!(%error, "failed to report the ambiguous passage");
+                }
+            }
+        }),
+    )
+}
+
+impl RoleHandler {
+    pub fn handle(&mut self, input: Input, timestamp: i64) {
+        match self {
+            RoleHandler::Gates(gates) => gates.handle(input, timestamp),
+            RoleHandler::Ramp(ramp) => ramp.handle(input, timestamp),
+        }
+    }
+
+    // How often tick has to be called
+    pub fn tick_every(&self) -> Duration {
+        match self {
+            RoleHandler::Gates(_) => barrier::TICK,
+            RoleHandler::Ramp(_) => ramp::TICK,
+        }
+    }
+
+    // Whether something can still time out, tick is only needed meanwhile
+    pub fn has_deadline(&self) -> bool {
+        match self {
+            RoleHandler::Gates(gates) => {
+                gates.entry_barrier.has_deadline() || gates.exit_barrier.has_deadline()
+            }
+            RoleHandler::Ramp(ramp) => ramp.ramp.has_deadline(),
+        }
+    }
+
+    pub fn tick(&mut self) {
+        match self {
+            RoleHandler::Gates(gates) => {
+                gates.entry_barrier.tick();
+                gates.exit_barrier.tick();
+            }
+            RoleHandler::Ramp(ramp) => ramp.ramp.tick(),
+        }
+    }
+
+    // Dropping the handler afterwards stops the scanner
+    pub fn turn_off(&self) {
+        if let RoleHandler::Gates(gates) = self {
+            gates.entry_barrier.turn_off();
+            gates.exit_barrier.turn_off();
+        }
+    }
+}
+
+impl GateHandler {
+    fn handle(&mut self, input: Input, timestamp: i64) {
+        match input {
+            // A car is in front of the entry barrier
+            Input::EntryOpenSignal => {
+                self.entry_barrier.on_open_signal();
+            }
+            // The car passed the entry gate, lower the barrier and record when
+            // the car entered the parking lot
+            Input::EntryCloseSignal => {
+                if self.entry_barrier.on_close_signal() {
+                    self.car_events.entered(timestamp);
+                    self.lot_occupancy.car_entered();
+                }
+            }
+            // A car is leaving, raise the exit barrier and record when the car left
+            // the parking lot
+            Input::ExitOpenSignal => {
+                if self.exit_barrier.on_open_signal() {
+                    self.car_events.left(timestamp);
+                }
+            }
+            // The car is out of the parking lot, lower the exit barrier
+            Input::ExitCloseSignal => {
+                if self.exit_barrier.on_close_signal() {
+                    self.lot_occupancy.car_left();
+                }
+            }
+            Input::PassThroughSensor1 | Input::PassThroughSensor2 => {}
+        }
+    }
+}
+
+impl RampHandler {
+    fn handle(&mut self, input: Input, timestamp: i64) {
+        match input {
+            Input::PassThroughSensor1 => self.ramp.on_sensor(RampSensor::Sensor1, timestamp),
+            Input::PassThroughSensor2 => self.ramp.on_sensor(RampSensor::Sensor2, timestamp),
+            _ => {}
+        }
+    }
+}
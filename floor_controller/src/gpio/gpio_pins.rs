@@ -0,0 +1,169 @@
+use crate::config::{FloorConfig, FloorRole};
+use crate::controller::ControllerEvent;
+use crate::gpio::gpio_async_interrupts::{self, RoleHandler};
+use crate::gpio::lot_occupancy::LotOccupancy;
+use crate::gpio::spot_scanner::CarEvents;
+use crate::hal::debounce::{DebouncedInputPin, GlitchCounters};
+use crate::hal::{Gpio, InputPin, OutputPin};
+use crate::socket::socket_client::ServerClient;
+use std::sync::{mpsc::Sender, Arc, Mutex};
+
+pub struct GpioPins {
+    pub space_address_1: Arc<Mutex<Box<dyn OutputPin>>>,
+    pub space_address_2: Arc<Mutex<Box<dyn OutputPin>>>,
+    pub space_address_3: Arc<Mutex<Box<dyn OutputPin>>>,
+    pub space_sensor: Arc<Mutex<Box<dyn InputPin>>>,
+    pub closed_signal: Arc<Mutex<Box<dyn OutputPin>>>,
+    pub role: RolePins,
+    // Filtered edges of every debounced sensor, by the name of the pin
+    pub glitch_counters: Vec<(&'static str, Arc<GlitchCounters>)>,
+}
+
+pub enum RolePins {
+    Gates(GatePins),
+    Ramp(RampPins),
+}
+
+pub struct GatePins {
+    // Also read by the barriers, to tell if a car is still in front of them
+    pub entry_open_signal: Arc<Mutex<Box<dyn InputPin>>>,
+    pub entry_close_signal: Box<dyn InputPin>,
+    pub entry_engine: Arc<Mutex<Box<dyn OutputPin>>>,
+    pub exit_open_signal: Arc<Mutex<Box<dyn InputPin>>>,
+    pub exit_close_signal: Box<dyn InputPin>,
+    pub exit_engine: Arc<Mutex<Box<dyn OutputPin>>>,
+    // Drives closed_signal when the server can't be reached
+    pub lot_occupancy: Arc<LotOccupancy>,
+}
+
+pub struct RampPins {
+    pub pass_through_sensor_1: Box<dyn InputPin>,
+    pub pass_through_sensor_2: Box<dyn InputPin>,
+}
+
+impl GpioPins {
+    pub fn new(gpio: &dyn Gpio, config: &FloorConfig) -> GpioPins {
+        let pins = &config.pins;
+        let pull = pins.input_pull;
+        let [address_1, address_2, address_3] = pins.space_address;
+
+        // The sensors of the role fire interrupts, so they go through the debouncing
+        let mut glitch_counters = Vec::new();
+        let mut debounced_input = |name: &'static str, pin: u8| -> Box<dyn InputPin> {
+            let counters = Arc::new(GlitchCounters::default());
+            glitch_counters.push((name, counters.clone()));
+
+            Box::new(DebouncedInputPin::new(
+                gpio.input(pin, pull).unwrap(),
+                config.debounce.settings_for(name),
+                counters,
+            ))
+        };
+
+        let closed_signal = Arc::new(Mutex::new(gpio.output_low(pins.closed_signal).unwrap()));
+
+        let role = match config.role {
+            FloorRole::Gates {
+                entry_open_signal,
+                entry_close_signal,
+                entry_engine,
+                exit_open_signal,
+                exit_close_signal,
+                exit_engine,
+            } => RolePins::Gates(GatePins {
+                entry_open_signal: Arc::new(Mutex::new(debounced_input(
+                    "entry_open_signal",
+                    entry_open_signal,
+                ))),
+                entry_close_signal: debounced_input("entry_close_signal", entry_close_signal),
+                entry_engine: Arc::new(Mutex::new(gpio.output_low(entry_engine).unwrap())),
+                exit_open_signal: Arc::new(Mutex::new(debounced_input(
+                    "exit_open_signal",
+                    exit_open_signal,
+                ))),
+                exit_close_signal: debounced_input("exit_close_signal", exit_close_signal),
+                exit_engine: Arc::new(Mutex::new(gpio.output_low(exit_engine).unwrap())),
+                lot_occupancy: LotOccupancy::new(
+                    closed_signal.clone(),
+                    config.scanner.match_window(),
+                ),
+            }),
+            FloorRole::Ramp {
+                pass_through_sensor_1,
+                pass_through_sensor_2,
+            } => RolePins::Ramp(RampPins {
+                pass_through_sensor_1: debounced_input(
+                    "pass_through_sensor_1",
+                    pass_through_sensor_1,
+                ),
+                pass_through_sensor_2: debounced_input(
+                    "pass_through_sensor_2",
+                    pass_through_sensor_2,
+                ),
+            }),
+        };
+
+        GpioPins {
+            space_address_1: Arc::new(Mutex::new(gpio.output_low(address_1).unwrap())),
+            space_address_2: Arc::new(Mutex::new(gpio.output_low(address_2).unwrap())),
+            space_address_3: Arc::new(Mutex::new(gpio.output_low(address_3).unwrap())),
+            space_sensor: Arc::new(Mutex::new(gpio.input(pins.space_sensor, pull).unwrap())),
+            closed_signal,
+            role,
+            glitch_counters,
+        }
+    }
+
+    // Only the ground floor counts the cars of the whole lot
+    pub fn lot_occupancy(&self) -> Option<Arc<LotOccupancy>> {
+        match &self.role {
+            RolePins::Gates(gate_pins) => Some(gate_pins.lot_occupancy.clone()),
+            RolePins::Ramp(_) => None,
+        }
+    }
+
+    // The interrupts send their inputs to the controller, which hands them to the
+    // returned handler
+    pub fn setup_interrupts(
+        &mut self,
+        config: &FloorConfig,
+        client: &Arc<ServerClient>,
+        car_events: CarEvents,
+        events: &Sender<ControllerEvent>,
+    ) -> RoleHandler {
+        gpio_async_interrupts::configure(self, config, client, car_events, events)
+    }
+
+    pub fn clear_interrupts(&mut self) {
+        match &mut self.role {
+            RolePins::Gates(gate_pins) => {
+                let inputs = [
+                    gate_pins
+                        .entry_open_signal
+                        .lock()
+                        .unwrap()
+                        .clear_async_interrupt(),
+                    gate_pins.entry_close_signal.clear_async_interrupt(),
+                    gate_pins
+                        .exit_open_signal
+                        .lock()
+                        .unwrap()
+                        .clear_async_interrupt(),
+                    gate_pins.exit_close_signal.clear_async_interrupt(),
+                ];
+
+                inputs.into_iter().collect::<Result<(), _>>().unwrap();
+            }
+            RolePins::Ramp(ramp_pins) => {
+                ramp_pins
+                    .pass_through_sensor_1
+                    .clear_async_interrupt()
+                    .unwrap();
+                ramp_pins
+                    .pass_through_sensor_2
+                    .clear_async_interrupt()
+                    .unwrap();
+            }
+        }
+    }
+}
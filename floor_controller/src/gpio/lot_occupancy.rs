@@ -0,0 +1,286 @@
+use crate::hal::OutputPin;
+use crate::model::LotOccupancyPayload;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+// Spots of the whole lot, until the server says how many are in service
+const LOT_SPOTS: i32 = 24;
+
+// The cars in the whole lot as the ground floor counts them at the barriers. The
+// server decides when the lot closes while it can be reached, the count only
+// takes over when it can't, and is set back to the server's once it reconnects
+pub struct LotOccupancy {
+    closed_signal: Arc<Mutex<Box<dyn OutputPin>>>,
+    // How long a car that passed the entry is expected to take to park
+    transit_timeout: Duration,
+    state: Mutex<OccupancyState>,
+}
+
+struct OccupancyState {
+    cars: i32,
+    capacity: i32,
+    closed: bool,
+    connected: bool,
+    // The server only counts the parked cars, these passed the entry and weren't
+    // seen parked yet. Oldest first
+    in_transit: VecDeque<Instant>,
+    // The last count of the server, its rises are the cars in transit parking
+    server_occupied: Option<i32>,
+}
+
+impl LotOccupancy {
+    // Counts on its own until the server sends its count, the client connects in
+    // the background and the server may be down when the controller starts
+    pub fn new(
+        closed_signal: Arc<Mutex<Box<dyn OutputPin>>>,
+        transit_timeout: Duration,
+    ) -> Arc<LotOccupancy> {
+        Arc::new(LotOccupancy {
+            closed_signal,
+            transit_timeout,
+            state: Mutex::new(OccupancyState {
+                cars: 0,
+                capacity: LOT_SPOTS,
+                closed: false,
+                connected: false,
+                in_transit: VecDeque::new(),
+                server_occupied: None,
+            }),
+        })
+    }
+
+    // A car passed the entry barrier, it counts before reaching its spot
+    pub fn car_entered(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.cars += 1;
+        state.in_transit.push_back(Instant::now());
+
+        if !state.connected && !state.closed && state.cars >= state.capacity {
+            warn!(
+                cars = state.cars,
+                capacity = state.capacity,
+                "server unreachable and the lot is full, closing it"
+            );
+            self.set_closed(&mut state, true);
+        }
+    }
+
+    // A car passed the exit barrier. While connected the server already took it
+    // off when its spot got free, so only the exits without it are counted
+    pub fn car_left(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        if state.connected {
+            return;
+        }
+
+        // Like the server, only a lot closed for being full is opened again
+        let was_full = state.cars >= state.capacity;
+        state.cars = (state.cars - 1).max(0);
+
+        if was_full && state.closed && state.cars < state.capacity {
+            warn!(
+                cars = state.cars,
+                capacity = state.capacity,
+                "server unreachable and a spot got free, opening the lot"
+            );
+            self.set_closed(&mut state, false);
+        }
+    }
+
+    // From the close_parking_lot and open_parking_lot events of the server
+    pub fn server_set_closed(&self, closed: bool) {
+        let mut state = self.state.lock().unwrap();
+        self.set_closed(&mut state, closed);
+    }
+
+    pub fn disconnected(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        if !state.connected {
+            return;
+        }
+
+        state.connected = false;
+
+        warn!(
+            cars = state.cars,
+            capacity = state.capacity,
+            "server unreachable, the lot now closes on the local count"
+        );
+    }
+
+    // Sent by the server whenever the lot changes and on every connection, its
+    // count and its decision win over the local ones. The cars still on their way
+    // to a spot are added to its count
+    pub fn reconcile(&self, occupancy: LotOccupancyPayload) {
+        let mut state = self.state.lock().unwrap();
+
+        // Every car the server saw parking was one of those in transit, the ones
+        // that never parked in time drove around or left
+        let parked = state
+            .server_occupied
+            .map_or(0, |server_occupied| occupancy.occupied - server_occupied);
+
+        for _ in 0..parked {
+            state.in_transit.pop_front();
+        }
+
+        let now = Instant::now();
+
+        while let Some(entered_at) = state.in_transit.front() {
+            if now - *entered_at < self.transit_timeout {
+                break;
+            }
+
+            state.in_transit.pop_front();
+        }
+
+        if !state.connected {
+            info!(
+                local_cars = state.cars,
+                server_occupied = occupancy.occupied,
+                in_transit = state.in_transit.len(),
+                closed = occupancy.is_closed,
+                "server reachable, taking its count of the lot"
+            );
+        }
+
+        state.connected = true;
+        state.server_occupied = Some(occupancy.occupied);
+        state.cars = occupancy.occupied + state.in_transit.len() as i32;
+        state.capacity = occupancy.capacity;
+
+        if state.closed != occupancy.is_closed {
+            self.set_closed(&mut state, occupancy.is_closed);
+        }
+    }
+
+    fn set_closed(&self, state: &mut OccupancyState, closed: bool) {
+        let mut closed_signal = self.closed_signal.lock().unwrap();
+
+        if closed {
+            closed_signal.set_high();
+        } else {
+            closed_signal.set_low();
+        }
+
+        state.closed = closed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::{mock_gpio::MockGpio, Gpio, Level};
+
+    const CLOSED_SIGNAL: u8 = 3;
+
+    fn lot_occupancy(gpio: &MockGpio, transit_timeout: Duration) -> Arc<LotOccupancy> {
+        LotOccupancy::new(
+            Arc::new(Mutex::new(gpio.output_low(CLOSED_SIGNAL).unwrap())),
+            transit_timeout,
+        )
+    }
+
+    fn occupancy(occupied: i32, capacity: i32, is_closed: bool) -> LotOccupancyPayload {
+        LotOccupancyPayload {
+            occupied,
+            capacity,
+            is_closed,
+        }
+    }
+
+    fn cars(lot_occupancy: &LotOccupancy) -> i32 {
+        lot_occupancy.state.lock().unwrap().cars
+    }
+
+    #[test]
+    fn closes_and_opens_on_its_own_count_without_the_server() {
+        let gpio = MockGpio::new();
+        let lot_occupancy = lot_occupancy(&gpio, Duration::from_secs(60));
+
+        for _ in 0..LOT_SPOTS - 1 {
+            lot_occupancy.car_entered();
+        }
+        assert_eq!(gpio.output(CLOSED_SIGNAL), Level::Low);
+
+        lot_occupancy.car_entered();
+        assert_eq!(gpio.output(CLOSED_SIGNAL), Level::High);
+
+        lot_occupancy.car_left();
+        assert_eq!(gpio.output(CLOSED_SIGNAL), Level::Low);
+        assert_eq!(cars(&lot_occupancy), LOT_SPOTS - 1);
+    }
+
+    #[test]
+    fn leaves_the_decision_to_the_server_while_connected() {
+        let gpio = MockGpio::new();
+        let lot_occupancy = lot_occupancy(&gpio, Duration::from_secs(60));
+
+        lot_occupancy.reconcile(occupancy(1, 2, false));
+        lot_occupancy.car_entered();
+        assert_eq!(gpio.output(CLOSED_SIGNAL), Level::Low);
+
+        lot_occupancy.server_set_closed(true);
+        assert_eq!(gpio.output(CLOSED_SIGNAL), Level::High);
+
+        // Already taken off by the server when the spot got free
+        lot_occupancy.car_left();
+        assert_eq!(cars(&lot_occupancy), 2);
+        assert_eq!(gpio.output(CLOSED_SIGNAL), Level::High);
+    }
+
+    #[test]
+    fn adds_the_cars_on_their_way_to_a_spot_to_the_server_count() {
+        let gpio = MockGpio::new();
+        let lot_occupancy = lot_occupancy(&gpio, Duration::from_secs(60));
+
+        lot_occupancy.reconcile(occupancy(5, 24, false));
+        lot_occupancy.car_entered();
+        lot_occupancy.car_entered();
+
+        lot_occupancy.reconcile(occupancy(5, 24, false));
+        assert_eq!(cars(&lot_occupancy), 7);
+
+        // One of them parked
+        lot_occupancy.reconcile(occupancy(6, 24, false));
+        assert_eq!(cars(&lot_occupancy), 7);
+        assert_eq!(lot_occupancy.state.lock().unwrap().in_transit.len(), 1);
+    }
+
+    #[test]
+    fn forgets_the_cars_that_never_parked() {
+        let gpio = MockGpio::new();
+        let lot_occupancy = lot_occupancy(&gpio, Duration::ZERO);
+
+        lot_occupancy.car_entered();
+        lot_occupancy.car_entered();
+        lot_occupancy.reconcile(occupancy(3, 24, false));
+
+        assert_eq!(cars(&lot_occupancy), 3);
+    }
+
+    #[test]
+    fn takes_over_from_the_last_server_count_once_disconnected() {
+        let gpio = MockGpio::new();
+        let lot_occupancy = lot_occupancy(&gpio, Duration::from_secs(60));
+
+        lot_occupancy.reconcile(occupancy(3, 4, false));
+        lot_occupancy.disconnected();
+        lot_occupancy.car_entered();
+
+        assert_eq!(gpio.output(CLOSED_SIGNAL), Level::High);
+
+        // The server closed it meanwhile and says so on reconnecting
+        lot_occupancy.reconcile(occupancy(4, 4, true));
+        assert_eq!(gpio.output(CLOSED_SIGNAL), Level::High);
+
+        lot_occupancy.reconcile(occupancy(3, 4, false));
+        assert_eq!(gpio.output(CLOSED_SIGNAL), Level::Low);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod barrier;
+pub mod gpio_async_interrupts;
+pub mod gpio_pins;
+pub mod lot_occupancy;
+pub mod ramp;
+pub mod spot_scanner;
@@ -0,0 +1,226 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+// How often the passages that never reached the other sensor are looked for
+pub const TICK: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RampSensor {
+    // At the bottom of the ramp, on the side of the floor below
+    Sensor1,
+    // At the top of the ramp, on the side of the floor
+    Sensor2,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    // Sensor 1 then sensor 2, the car came up to the floor
+    Up,
+    // Sensor 2 then sensor 1, the car went down to the floor below
+    Down,
+}
+
+// A car only one sensor saw, it turned back or the other sensor missed it. It is
+// not counted
+#[derive(Clone, Copy)]
+pub struct AmbiguousPassage {
+    pub first_sensor: RampSensor,
+    // When the first sensor saw it
+    pub timestamp: i64,
+}
+
+// What the ramp tells whoever watches it
+pub enum RampEvent {
+    // With the time the car reached the second sensor
+    Passed(Direction, i64),
+    Ambiguous(AmbiguousPassage),
+}
+
+pub type RampListener = Box<dyn Fn(RampEvent) + Send + Sync>;
+
+// The two sensors of the ramp of an upper floor. Every car between them is a
+// passage of its own, finished by the other sensor or dropped after the timeout,
+// so a car that turns back or an edge that is missed only loses that passage
+// instead of turning the next ones around. Cars following each other close are
+// finished in the order they came in
+pub struct Ramp {
+    passage_timeout: Duration,
+    // Cars between the sensors, oldest first. They all go the same way, a car
+    // coming the other way finishes the oldest instead
+    passages: VecDeque<Passage>,
+    listener: RampListener,
+}
+
+struct Passage {
+    first_sensor: RampSensor,
+    started_at: Instant,
+    timestamp: i64,
+}
+
+impl RampSensor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RampSensor::Sensor1 => "pass_through_sensor_1",
+            RampSensor::Sensor2 => "pass_through_sensor_2",
+        }
+    }
+}
+
+impl Ramp {
+    // Starts empty, the timeouts are checked by calling tick every TICK while
+    // has_deadline
+    pub fn new(passage_timeout: Duration, listener: RampListener) -> Ramp {
+        Ramp {
+            passage_timeout,
+            passages: VecDeque::new(),
+            listener,
+        }
+    }
+
+    // A car reached one of the sensors, at `timestamp`
+    pub fn on_sensor(&mut self, sensor: RampSensor, timestamp: i64) {
+        let now = Instant::now();
+
+        // A passage too old to be finished by this car
+        self.expire(now);
+
+        match self.passages.front() {
+            Some(passage) if passage.first_sensor != sensor => {
+                let direction = match passage.first_sensor {
+                    RampSensor::Sensor1 => Direction::Up,
+                    RampSensor::Sensor2 => Direction::Down,
+                };
+
+                self.passages.pop_front();
+                (self.listener)(RampEvent::Passed(direction, timestamp));
+            }
+            // The first car on the ramp, or one following the others
+            _ => self.passages.push_back(Passage {
+                first_sensor: sensor,
+                started_at: now,
+                timestamp,
+            }),
+        }
+    }
+
+    // A car between the sensors, dropped after the timeout
+    pub fn has_deadline(&self) -> bool {
+        !self.passages.is_empty()
+    }
+
+    pub fn tick(&mut self) {
+        self.expire(Instant::now());
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while let Some(passage) = self.passages.front() {
+            if now - passage.started_at < self.passage_timeout {
+                break;
+            }
+
+            let passage = AmbiguousPassage {
+                first_sensor: passage.first_sensor,
+                timestamp: passage.timestamp,
+            };
+
+            self.passages.pop_front();
+
+            warn!(
+                first_sensor = passage.first_sensor.as_str(),
+                timestamp = passage.timestamp,
+                "car seen by only one sensor of the ramp, not counted"
+            );
+
+            (self.listener)(RampEvent::Ambiguous(passage));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    const PASSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    // What the ramp told its listener, as (what, timestamp)
+    type Told = Arc<Mutex<Vec<(&'static str, i64)>>>;
+
+    fn ramp() -> (Ramp, Told) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+
+        let ramp = Ramp::new(
+            PASSAGE_TIMEOUT,
+            Box::new(move |event| {
+                let event = match event {
+                    RampEvent::Passed(Direction::Up, timestamp) => ("up", timestamp),
+                    RampEvent::Passed(Direction::Down, timestamp) => ("down", timestamp),
+                    RampEvent::Ambiguous(passage) => {
+                        (passage.first_sensor.as_str(), passage.timestamp)
+                    }
+                };
+
+                events_clone.lock().unwrap().push(event);
+            }),
+        );
+
+        (ramp, events)
+    }
+
+    #[test]
+    fn tells_the_direction_by_the_first_sensor() {
+        let (mut ramp, events) = ramp();
+
+        ramp.on_sensor(RampSensor::Sensor1, 1);
+        ramp.on_sensor(RampSensor::Sensor2, 2);
+        ramp.on_sensor(RampSensor::Sensor2, 3);
+        ramp.on_sensor(RampSensor::Sensor1, 4);
+
+        assert_eq!(*events.lock().unwrap(), vec![("up", 2), ("down", 4)]);
+        assert!(!ramp.has_deadline());
+    }
+
+    #[test]
+    fn finishes_cars_close_behind_each_other_in_order() {
+        let (mut ramp, events) = ramp();
+
+        ramp.on_sensor(RampSensor::Sensor1, 1);
+        ramp.on_sensor(RampSensor::Sensor1, 2);
+        ramp.on_sensor(RampSensor::Sensor2, 3);
+        ramp.on_sensor(RampSensor::Sensor2, 4);
+
+        assert_eq!(*events.lock().unwrap(), vec![("up", 3), ("up", 4)]);
+    }
+
+    #[test]
+    fn drops_a_car_that_turned_back() {
+        let (mut ramp, events) = ramp();
+
+        ramp.on_sensor(RampSensor::Sensor1, 1);
+        assert!(ramp.has_deadline());
+
+        ramp.expire(Instant::now() + PASSAGE_TIMEOUT);
+        assert_eq!(*events.lock().unwrap(), vec![("pass_through_sensor_1", 1)]);
+        assert!(!ramp.has_deadline());
+
+        // The next car isn't turned around by the one that backed away
+        ramp.on_sensor(RampSensor::Sensor2, 10);
+        ramp.on_sensor(RampSensor::Sensor1, 11);
+        assert_eq!(events.lock().unwrap().last(), Some(&("down", 11)));
+    }
+
+    #[test]
+    fn only_drops_the_passages_past_the_timeout() {
+        let (mut ramp, events) = ramp();
+
+        ramp.on_sensor(RampSensor::Sensor1, 1);
+        ramp.expire(Instant::now() + PASSAGE_TIMEOUT / 2);
+        ramp.on_sensor(RampSensor::Sensor2, 2);
+
+        assert_eq!(*events.lock().unwrap(), vec![("up", 2)]);
+    }
+}
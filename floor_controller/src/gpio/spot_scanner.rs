@@ -0,0 +1,649 @@
+use crate::config::FloorConfig;
+use crate::gpio::gpio_pins::GpioPins;
+use crate::hal::{InputPin, Level, OutputPin};
+use crate::model::{ParkingLot, ParkingSpaceModifiedPayload, SpotMismatchPayload};
+use crate::socket::event_queue::EventQueue;
+use crate::socket::socket_client::ServerClient;
+use crate::socket::socket_operations::{CAR_ARRIVED, CAR_DEPARTED, SPOT_MISMATCH};
+use chrono::Utc;
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    sync::{
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+// What the barriers and the ramp saw, handed to the scanner to be matched with the
+// spots that change
+enum CarEvent {
+    Entered,
+    Left,
+}
+
+// Given to the interrupts, which only tell the scanner when a car went by, so no
+// one but the scanner touches the address pins
+#[derive(Clone)]
+pub struct CarEvents {
+    sender: Sender<(CarEvent, Instant, i64)>,
+}
+
+impl CarEvents {
+    // A car passed the entry gate or came up the ramp
+    pub fn entered(&self, timestamp: i64) {
+        self.sender
+            .send((CarEvent::Entered, Instant::now(), timestamp))
+            .ok();
+    }
+
+    // A car went to the exit gate or down the ramp
+    pub fn left(&self, timestamp: i64) {
+        self.sender
+            .send((CarEvent::Left, Instant::now(), timestamp))
+            .ok();
+    }
+}
+
+// Owns the address pins and the spot sensor, reading every spot once per cycle.
+// A spot only changes after reading the same for `confirm_samples` cycles in a
+// row, so a car driving past its sensor is not taken for one parking
+struct SpotScanner {
+    floor: String,
+    sensor_settle: Duration,
+    cycle: Duration,
+    confirm_samples: u32,
+    // Longest time between a car going by and its spot changing, or the other way
+    // around, for the two to be matched
+    match_window: Duration,
+    // Cars going by without a spot changing are on their way to another floor
+    through_traffic: bool,
+    space_address_1: Arc<Mutex<Box<dyn OutputPin>>>,
+    space_address_2: Arc<Mutex<Box<dyn OutputPin>>>,
+    space_address_3: Arc<Mutex<Box<dyn OutputPin>>>,
+    space_sensor: Arc<Mutex<Box<dyn InputPin>>>,
+    parking_lot: Arc<Mutex<ParkingLot>>,
+    client: Arc<ServerClient>,
+    event_queue: Arc<EventQueue>,
+    events: Receiver<(CarEvent, Instant, i64)>,
+    candidates: [Option<Candidate>; 8],
+    // Cars that went by and were not matched yet, oldest first
+    entered: VecDeque<Sighting>,
+    left: VecDeque<Sighting>,
+    // Spots that got free before their car reached the exit or the ramp
+    freed: VecDeque<(i32, Sighting)>,
+}
+
+// A spot that reads differently from the parking lot, waiting to be confirmed
+#[derive(Clone, Copy)]
+struct Candidate {
+    samples: u32,
+    first_seen: Sighting,
+}
+
+#[derive(Clone, Copy)]
+struct Sighting {
+    at: Instant,
+    timestamp: i64,
+}
+
+impl Sighting {
+    fn now() -> Self {
+        Sighting {
+            at: Instant::now(),
+            timestamp: Utc::now().timestamp(),
+        }
+    }
+}
+
+// Starts the scanner of the floor, returning where to send it the cars that go by.
+// It stops once every CarEvents is dropped
+pub fn spawn(
+    gpio_pins: &GpioPins,
+    config: &FloorConfig,
+    client: &Arc<ServerClient>,
+    parking_lot: &Arc<Mutex<ParkingLot>>,
+    event_queue: &Arc<EventQueue>,
+) -> (CarEvents, JoinHandle<()>) {
+    let (sender, receiver) = mpsc::channel();
+
+    let mut scanner = SpotScanner {
+        floor: config.client_id.clone(),
+        sensor_settle: config.timings.sensor_settle(),
+        cycle: config.scanner.cycle(),
+        confirm_samples: config.scanner.confirm_samples,
+        match_window: config.scanner.match_window(),
+        through_traffic: config.scanner.through_traffic,
+        space_address_1: gpio_pins.space_address_1.clone(),
+        space_address_2: gpio_pins.space_address_2.clone(),
+        space_address_3: gpio_pins.space_address_3.clone(),
+        space_sensor: gpio_pins.space_sensor.clone(),
+        parking_lot: parking_lot.clone(),
+        client: client.clone(),
+        event_queue: event_queue.clone(),
+        events: receiver,
+        candidates: [None; 8],
+        entered: VecDeque::new(),
+        left: VecDeque::new(),
+        freed: VecDeque::new(),
+    };
+
+    let handle = thread::spawn(move || scanner.run());
+
+    (CarEvents { sender }, handle)
+}
+
+impl SpotScanner {
+    fn run(&mut self) {
+        loop {
+            let cycle_started_at = Instant::now();
+
+            for address in 0..8 {
+                if !self.receive_events() {
+                    info!(floor = self.floor.as_str(), "scanner stopped");
+                    return;
+                }
+
+                self.sample(address);
+            }
+
+            self.expire(Instant::now());
+
+            thread::sleep(self.cycle.saturating_sub(cycle_started_at.elapsed()));
+        }
+    }
+
+    // False once no car can be sent to the scanner anymore
+    fn receive_events(&mut self) -> bool {
+        loop {
+            let (event, at, timestamp) = match self.events.try_recv() {
+                Ok(event) => event,
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return false,
+            };
+
+            let sighting = Sighting { at, timestamp };
+
+            match event {
+                CarEvent::Entered => self.entered.push_back(sighting),
+                // The spot may have been seen free already
+                CarEvent::Left => match self.freed.pop_front() {
+                    Some((parking_space, _)) => self.emit_departure(parking_space, timestamp),
+                    None => self.left.push_back(sighting),
+                },
+            }
+        }
+    }
+
+    fn sample(&mut self, address: usize) {
+        // Spots out of service are skipped, their sensor can't be trusted
+        if self.parking_lot.lock().unwrap().out_of_service[address] {
+            self.candidates[address] = None;
+            return;
+        }
+
+        let occupied = self.read_spot(address) == Level::High;
+
+        if self.parking_lot.lock().unwrap().spaces[address] == occupied {
+            self.candidates[address] = None;
+            return;
+        }
+
+        let candidate = self.candidates[address].get_or_insert(Candidate {
+            samples: 0,
+            first_seen: Sighting::now(),
+        });
+        candidate.samples += 1;
+
+        if candidate.samples < self.confirm_samples {
+            return;
+        }
+
+        let first_seen = candidate.first_seen;
+        self.candidates[address] = None;
+
+        self.parking_lot.lock().unwrap().spaces[address] = occupied;
+
+        if occupied {
+            self.on_spot_taken(address as i32, first_seen);
+        } else {
+            self.on_spot_freed(address as i32, first_seen);
+        }
+    }
+
+    // The car came in before parking, so the oldest one still unmatched is taken
+    // to be it, and the arrival is dated from when it came in
+    fn on_spot_taken(&mut self, parking_space: i32, first_seen: Sighting) {
+        self.entered
+            .retain(|entered| first_seen.at.duration_since(entered.at) <= self.match_window);
+
+        // A car that came in after the spot was taken is not the one parked in it
+        let came_in_before = self
+            .entered
+            .front()
+            .is_some_and(|entered| entered.at <= first_seen.at);
+        let entered = came_in_before.then(|| self.entered.pop_front()).flatten();
+
+        match entered {
+            Some(entered) => self.emit_arrival(parking_space, entered.timestamp),
+            None => {
+                self.emit_mismatch("arrival_without_entry", Some(parking_space), first_seen);
+                self.emit_arrival(parking_space, first_seen.timestamp);
+            }
+        }
+    }
+
+    // The departure is dated from when the car left the floor, which usually
+    // comes after its spot is seen free
+    fn on_spot_freed(&mut self, parking_space: i32, first_seen: Sighting) {
+        self.left
+            .retain(|left| first_seen.at.duration_since(left.at) <= self.match_window);
+
+        match self.left.pop_front() {
+            Some(left) => self.emit_departure(parking_space, left.timestamp),
+            None => self.freed.push_back((parking_space, first_seen)),
+        }
+    }
+
+    // Cars that went by without a spot changing went through the floor, unless
+    // there is no floor to go to, then they are reported. A spot freed without a
+    // car leaving is reported anyway
+    fn expire(&mut self, now: Instant) {
+        while let Some(entered) = pop_expired(&mut self.entered, now, self.match_window) {
+            if !self.through_traffic {
+                self.emit_mismatch("entry_without_arrival", None, entered);
+            }
+        }
+
+        while let Some(left) = pop_expired(&mut self.left, now, self.match_window) {
+            if !self.through_traffic {
+                self.emit_mismatch("exit_without_departure", None, left);
+            }
+        }
+
+        while let Some(&(parking_space, first_seen)) = self.freed.front() {
+            if now.duration_since(first_seen.at) <= self.match_window {
+                break;
+            }
+
+            self.freed.pop_front();
+            self.emit_mismatch("departure_without_exit", Some(parking_space), first_seen);
+            self.emit_departure(parking_space, first_seen.timestamp);
+        }
+    }
+
+    fn emit_arrival(&self, parking_space: i32, timestamp: i64) {
+        info!(
+            floor = self.floor.as_str(),
+            spot = parking_space,
+            "car arrived"
+        );
+
+        self.emit(
+            CAR_ARRIVED,
+            ParkingSpaceModifiedPayload {
+                parking_space,
+                timestamp,
+            },
+        );
+    }
+
+    fn emit_departure(&self, parking_space: i32, timestamp: i64) {
+        info!(
+            floor = self.floor.as_str(),
+            spot = parking_space,
+            "car departed"
+        );
+
+        self.emit(
+            CAR_DEPARTED,
+            ParkingSpaceModifiedPayload {
+                parking_space,
+                timestamp,
+            },
+        );
+    }
+
+    // E.g. a car that changed spots, or a sensor of a spot or the ramp that failed
+    fn emit_mismatch(&self, kind: &'static str, parking_space: Option<i32>, first_seen: Sighting) {
+        warn!(
+            floor = self.floor.as_str(),
+            spot = parking_space,
+            kind,
+            "spots and cars going by don't match"
+        );
+
+        self.emit(
+            SPOT_MISMATCH,
+            SpotMismatchPayload {
+                kind: kind.to_string(),
+                parking_space,
+                timestamp: first_seen.timestamp,
+            },
+        );
+    }
+
+    // Queued while the server can't be reached, never lost
+    fn emit<P: Serialize>(&self, event: &'static str, payload: P) {
+        self.event_queue.send(&self.client, event, payload);
+    }
+
+    // Selects the spot and reads its sensor
+    fn read_spot(&self, address: usize) -> Level {
+        let (address_1, address_2, address_3) = convert_address_to_levels(address as u8);
+
+        self.space_address_1.lock().unwrap().write(address_1);
+        self.space_address_2.lock().unwrap().write(address_2);
+        self.space_address_3.lock().unwrap().write(address_3);
+
+        // Wait the sensor to stabilize
+        thread::sleep(self.sensor_settle);
+
+        self.space_sensor.lock().unwrap().read()
+    }
+}
+
+// The oldest sighting, once it is past the window. They are kept oldest first
+fn pop_expired(
+    sightings: &mut VecDeque<Sighting>,
+    now: Instant,
+    match_window: Duration,
+) -> Option<Sighting> {
+    let expired = sightings
+        .front()
+        .is_some_and(|sighting| now.duration_since(sighting.at) > match_window);
+
+    expired.then(|| sightings.pop_front()).flatten()
+}
+
+fn convert_address_to_levels(address: u8) -> (Level, Level, Level) {
+    let address_1 = if address & 0b001 != 0 {
+        Level::High
+    } else {
+        Level::Low
+    };
+
+    let address_2 = if address & 0b010 != 0 {
+        Level::High
+    } else {
+        Level::Low
+    };
+
+    let address_3 = if address & 0b100 != 0 {
+        Level::High
+    } else {
+        Level::Low
+    };
+
+    (address_1, address_2, address_3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::{mock_gpio::MockGpio, Gpio, Pull};
+    use serde_json::{json, Value};
+    use std::fs;
+
+    const CONFIRM_SAMPLES: u32 = 2;
+    const MATCH_WINDOW: Duration = Duration::from_secs(30);
+
+    // A scanner of mock pins, with what its spot sensors see
+    struct Floor {
+        scanner: SpotScanner,
+        car_events: CarEvents,
+        occupied: Arc<Mutex<Vec<bool>>>,
+        queue_path: String,
+    }
+
+    impl Floor {
+        fn new(name: &str, through_traffic: bool) -> Floor {
+            let config = FloorConfig::for_tests(name);
+            let gpio = MockGpio::new();
+            let output = |pin| Arc::new(Mutex::new(gpio.output_low(pin).unwrap()));
+            let [address_1, address_2, address_3] = config.pins.space_address;
+
+            // The spot sensor reads the spot selected by the address pins
+            let occupied = Arc::new(Mutex::new(vec![false; 8]));
+            let occupied_clone = occupied.clone();
+
+            gpio.set_input_source(
+                config.pins.space_sensor,
+                Box::new(move |outputs| {
+                    let address = [address_1, address_2, address_3]
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, pin)| outputs.level(**pin) == Level::High)
+                        .fold(0, |address, (bit, _)| address | 1 << bit);
+
+                    if occupied_clone.lock().unwrap()[address] {
+                        Level::High
+                    } else {
+                        Level::Low
+                    }
+                }),
+            );
+
+            let (sender, receiver) = mpsc::channel();
+
+            let scanner = SpotScanner {
+                floor: config.client_id.clone(),
+                sensor_settle: Duration::ZERO,
+                cycle: Duration::ZERO,
+                confirm_samples: CONFIRM_SAMPLES,
+                match_window: MATCH_WINDOW,
+                through_traffic,
+                space_address_1: output(address_1),
+                space_address_2: output(address_2),
+                space_address_3: output(address_3),
+                space_sensor: Arc::new(Mutex::new(
+                    gpio.input(config.pins.space_sensor, Pull::Down).unwrap(),
+                )),
+                parking_lot: ParkingLot::new(),
+                client: ServerClient::disconnected(),
+                event_queue: EventQueue::open(&config),
+                events: receiver,
+                candidates: [None; 8],
+                entered: VecDeque::new(),
+                left: VecDeque::new(),
+                freed: VecDeque::new(),
+            };
+
+            Floor {
+                scanner,
+                car_events: CarEvents { sender },
+                occupied,
+                queue_path: config.queue_path(),
+            }
+        }
+
+        // Enough cycles for a change to be confirmed
+        fn scan(&mut self) {
+            for _ in 0..CONFIRM_SAMPLES {
+                assert!(self.scanner.receive_events());
+
+                for address in 0..8 {
+                    self.scanner.sample(address);
+                }
+            }
+        }
+
+        fn expire_window(&mut self) {
+            self.scanner
+                .expire(Instant::now() + MATCH_WINDOW + Duration::from_secs(1));
+        }
+
+        fn sent(&self) -> Vec<(String, Value)> {
+            self.scanner.event_queue.queued()
+        }
+    }
+
+    impl Drop for Floor {
+        fn drop(&mut self) {
+            fs::remove_file(&self.queue_path).ok();
+        }
+    }
+
+    fn mismatch(kind: &str, parking_space: Option<i32>, timestamp: i64) -> (String, Value) {
+        (
+            SPOT_MISMATCH.to_string(),
+            json!({ "kind": kind, "parking_space": parking_space, "timestamp": timestamp }),
+        )
+    }
+
+    fn spot_event(event: &str, parking_space: i32, timestamp: i64) -> (String, Value) {
+        (
+            event.to_string(),
+            json!({ "parking_space": parking_space, "timestamp": timestamp }),
+        )
+    }
+
+    #[test]
+    fn dates_the_arrival_from_the_car_that_came_in() {
+        let mut floor = Floor::new("scanner_arrival", true);
+
+        floor.car_events.entered(100);
+        floor.occupied.lock().unwrap()[3] = true;
+        floor.scan();
+
+        assert_eq!(floor.sent(), vec![spot_event(CAR_ARRIVED, 3, 100)]);
+        assert!(floor.scanner.parking_lot.lock().unwrap().spaces[3]);
+    }
+
+    #[test]
+    fn matches_the_cars_in_the_order_they_came_in() {
+        let mut floor = Floor::new("scanner_order", true);
+
+        floor.car_events.entered(100);
+        floor.car_events.entered(101);
+        floor.occupied.lock().unwrap()[6] = true;
+        floor.scan();
+        floor.occupied.lock().unwrap()[1] = true;
+        floor.scan();
+
+        assert_eq!(
+            floor.sent(),
+            vec![
+                spot_event(CAR_ARRIVED, 6, 100),
+                spot_event(CAR_ARRIVED, 1, 101),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_a_car_driving_past_a_sensor() {
+        let mut floor = Floor::new("scanner_driving_past", true);
+
+        floor.occupied.lock().unwrap()[2] = true;
+        assert!(floor.scanner.receive_events());
+        floor.scanner.sample(2);
+        floor.occupied.lock().unwrap()[2] = false;
+        floor.scan();
+
+        assert!(floor.sent().is_empty());
+    }
+
+    #[test]
+    fn skips_the_spots_out_of_service() {
+        let mut floor = Floor::new("scanner_out_of_service", true);
+
+        floor.scanner.parking_lot.lock().unwrap().out_of_service[4] = true;
+        floor.occupied.lock().unwrap()[4] = true;
+        floor.scan();
+
+        assert!(floor.sent().is_empty());
+    }
+
+    #[test]
+    fn reports_a_spot_taken_without_a_car_coming_in() {
+        let mut floor = Floor::new("scanner_arrival_without_entry", true);
+
+        floor.occupied.lock().unwrap()[4] = true;
+        floor.scan();
+
+        let sent = floor.sent();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].0, SPOT_MISMATCH);
+        assert_eq!(sent[0].1["kind"], "arrival_without_entry");
+        assert_eq!(sent[0].1["parking_space"], 4);
+        assert_eq!(sent[1].0, CAR_ARRIVED);
+        assert_eq!(sent[1].1["parking_space"], 4);
+    }
+
+    #[test]
+    fn dates_the_departure_from_the_car_leaving_after_its_spot_is_free() {
+        let mut floor = Floor::new("scanner_departure", true);
+
+        floor.scanner.parking_lot.lock().unwrap().spaces[2] = true;
+        floor.scan();
+        assert!(floor.sent().is_empty());
+
+        floor.car_events.left(200);
+        assert!(floor.scanner.receive_events());
+
+        assert_eq!(floor.sent(), vec![spot_event(CAR_DEPARTED, 2, 200)]);
+    }
+
+    #[test]
+    fn reports_a_spot_freed_without_a_car_leaving() {
+        let mut floor = Floor::new("scanner_departure_without_exit", true);
+
+        floor.scanner.parking_lot.lock().unwrap().spaces[6] = true;
+        floor.scan();
+        floor.expire_window();
+
+        let sent = floor.sent();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].1["kind"], "departure_without_exit");
+        assert_eq!(sent[0].1["parking_space"], 6);
+        assert_eq!(sent[1].0, CAR_DEPARTED);
+        assert_eq!(sent[1].1["parking_space"], 6);
+    }
+
+    #[test]
+    fn lets_the_cars_go_by_to_the_other_floors() {
+        let mut floor = Floor::new("scanner_through_traffic", true);
+
+        floor.car_events.entered(100);
+        floor.car_events.left(101);
+        assert!(floor.scanner.receive_events());
+        floor.expire_window();
+
+        assert!(floor.sent().is_empty());
+        assert!(floor.scanner.entered.is_empty() && floor.scanner.left.is_empty());
+    }
+
+    #[test]
+    fn reports_the_cars_going_by_the_top_floor() {
+        let mut floor = Floor::new("scanner_top_floor", false);
+
+        floor.car_events.entered(100);
+        floor.car_events.left(101);
+        assert!(floor.scanner.receive_events());
+
+        // Still within the window
+        floor.scanner.expire(Instant::now());
+        assert!(floor.sent().is_empty());
+
+        floor.expire_window();
+        assert_eq!(
+            floor.sent(),
+            vec![
+                mismatch("entry_without_arrival", None, 100),
+                mismatch("exit_without_departure", None, 101),
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_once_no_car_can_be_sent() {
+        let mut floor = Floor::new("scanner_stop", true);
+        let (sender, _) = mpsc::channel();
+
+        floor.car_events = CarEvents { sender };
+        assert!(!floor.scanner.receive_events());
+    }
+}
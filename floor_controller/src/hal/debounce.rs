@@ -0,0 +1,284 @@
+use crate::hal::{InputPin, InterruptCallback, Level, Result, Trigger};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Copy)]
+pub struct DebounceSettings {
+    // A new level only counts once it held for this long, shorter pulses are glitches
+    pub stable_time: Duration,
+    // Edges that would fire the interrupt again within this time of the last one
+    // are merged into it, e.g. a car rocking on the sensor
+    pub coalesce_time: Duration,
+}
+
+// What an input filtered out since the controller started
+#[derive(Default)]
+pub struct GlitchCounters {
+    pub glitches: AtomicU64,
+    pub coalesced: AtomicU64,
+}
+
+impl GlitchCounters {
+    pub fn glitches(&self) -> u64 {
+        self.glitches.load(Relaxed)
+    }
+
+    pub fn coalesced(&self) -> u64 {
+        self.coalesced.load(Relaxed)
+    }
+}
+
+// Wraps an input so its interrupt only sees clean edges. The raw edges are handed
+// to a thread of its own, which waits for them to settle before calling the
+// interrupt, so a bounce never runs it twice
+pub struct DebouncedInputPin {
+    inner: Box<dyn InputPin>,
+    settings: DebounceSettings,
+    counters: Arc<GlitchCounters>,
+}
+
+impl DebouncedInputPin {
+    pub fn new(
+        inner: Box<dyn InputPin>,
+        settings: DebounceSettings,
+        counters: Arc<GlitchCounters>,
+    ) -> Self {
+        DebouncedInputPin {
+            inner,
+            settings,
+            counters,
+        }
+    }
+}
+
+impl InputPin for DebouncedInputPin {
+    fn read(&self) -> Level {
+        self.inner.read()
+    }
+
+    fn set_async_interrupt(&mut self, trigger: Trigger, callback: InterruptCallback) -> Result<()> {
+        let (sender, receiver) = mpsc::channel();
+
+        let mut filter = EdgeFilter {
+            settings: self.settings,
+            counters: self.counters.clone(),
+            trigger,
+            callback,
+            stable_level: self.inner.read(),
+            pending: None,
+            last_fired_at: None,
+        };
+
+        // Stops once the raw interrupt is replaced, which drops the sender
+        thread::spawn(move || filter.run(receiver));
+
+        self.inner.set_async_interrupt(
+            Trigger::Both,
+            Box::new(move |level| {
+                sender.send((level, Instant::now())).ok();
+            }),
+        )
+    }
+
+    // The filter thread stops with the sender
+    fn clear_async_interrupt(&mut self) -> Result<()> {
+        self.inner.clear_async_interrupt()
+    }
+}
+
+struct EdgeFilter {
+    settings: DebounceSettings,
+    counters: Arc<GlitchCounters>,
+    trigger: Trigger,
+    callback: InterruptCallback,
+    // Last level that held for the stable time
+    stable_level: Level,
+    // Level the input changed to, waiting to hold for the stable time
+    pending: Option<(Level, Instant)>,
+    last_fired_at: Option<Instant>,
+}
+
+impl EdgeFilter {
+    // The edges carry the time they happened, so the ones that queued up while the
+    // interrupt ran are still told apart from the bounces
+    fn run(&mut self, receiver: mpsc::Receiver<(Level, Instant)>) {
+        loop {
+            let edge = match self.pending {
+                Some((_, changed_at)) => {
+                    let settles_at = changed_at + self.settings.stable_time;
+                    let timeout = settles_at.saturating_duration_since(Instant::now());
+
+                    match receiver.recv_timeout(timeout) {
+                        Ok(edge) => Some(edge),
+                        Err(RecvTimeoutError::Timeout) => None,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                None => match receiver.recv() {
+                    Ok(edge) => Some(edge),
+                    Err(_) => return,
+                },
+            };
+
+            match edge {
+                Some((level, at)) => self.on_edge(level, at),
+                None => self.settle(),
+            }
+        }
+    }
+
+    fn on_edge(&mut self, level: Level, at: Instant) {
+        if let Some((pending_level, changed_at)) = self.pending {
+            if at.duration_since(changed_at) < self.settings.stable_time {
+                // Changed again before settling, the pending level was a glitch
+                self.counters.glitches.fetch_add(1, Relaxed);
+                self.pending = None;
+            } else {
+                self.accept(pending_level, changed_at);
+            }
+        }
+
+        if level != self.stable_level {
+            self.pending = Some((level, at));
+
+            if self.settings.stable_time.is_zero() {
+                self.settle();
+            }
+        }
+    }
+
+    fn settle(&mut self) {
+        if let Some((level, changed_at)) = self.pending.take() {
+            self.accept(level, changed_at);
+        }
+    }
+
+    fn accept(&mut self, level: Level, changed_at: Instant) {
+        let previous = self.stable_level;
+        self.stable_level = level;
+
+        if !self.trigger.matches(previous, level) {
+            return;
+        }
+
+        let coalesced = self.last_fired_at.is_some_and(|fired_at| {
+            changed_at.duration_since(fired_at) < self.settings.coalesce_time
+        });
+
+        if coalesced {
+            self.counters.coalesced.fetch_add(1, Relaxed);
+            return;
+        }
+
+        self.last_fired_at = Some(changed_at);
+        (self.callback)(level);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hal::{mock_gpio::MockGpio, Gpio, Pull};
+    use std::sync::mpsc::Receiver;
+
+    const PIN: u8 = 4;
+    const STABLE_TIME: Duration = Duration::from_millis(30);
+    // Much longer than the stable time, for the filter thread to get to it
+    const WAIT: Duration = Duration::from_millis(300);
+
+    // The debounced pin, the levels its interrupt fired with and its counters
+    fn debounced(
+        gpio: &MockGpio,
+        coalesce_time: Duration,
+    ) -> (DebouncedInputPin, Receiver<Level>, Arc<GlitchCounters>) {
+        let counters = Arc::new(GlitchCounters::default());
+        let mut pin = DebouncedInputPin::new(
+            gpio.input(PIN, Pull::Down).unwrap(),
+            DebounceSettings {
+                stable_time: STABLE_TIME,
+                coalesce_time,
+            },
+            counters.clone(),
+        );
+
+        let (sender, receiver) = mpsc::channel();
+        pin.set_async_interrupt(
+            Trigger::RisingEdge,
+            Box::new(move |level| {
+                sender.send(level).ok();
+            }),
+        )
+        .unwrap();
+
+        (pin, receiver, counters)
+    }
+
+    #[test]
+    fn fires_once_the_level_held() {
+        let gpio = MockGpio::new();
+        let (_pin, fired, counters) = debounced(&gpio, Duration::ZERO);
+
+        gpio.set_input(PIN, Level::High);
+
+        assert_eq!(fired.recv_timeout(WAIT), Ok(Level::High));
+        assert_eq!(counters.glitches(), 0);
+    }
+
+    #[test]
+    fn drops_a_pulse_shorter_than_the_stable_time() {
+        let gpio = MockGpio::new();
+        let (_pin, fired, counters) = debounced(&gpio, Duration::ZERO);
+
+        gpio.pulse(PIN);
+
+        assert!(fired.recv_timeout(WAIT).is_err());
+        assert_eq!(counters.glitches(), 1);
+    }
+
+    #[test]
+    fn fires_once_for_a_bouncing_edge() {
+        let gpio = MockGpio::new();
+        let (_pin, fired, counters) = debounced(&gpio, Duration::ZERO);
+
+        gpio.pulse(PIN);
+        gpio.pulse(PIN);
+        gpio.set_input(PIN, Level::High);
+
+        assert_eq!(fired.recv_timeout(WAIT), Ok(Level::High));
+        assert!(fired.recv_timeout(WAIT).is_err());
+        assert!(counters.glitches() >= 2);
+    }
+
+    #[test]
+    fn merges_the_edges_close_to_the_last_one() {
+        let gpio = MockGpio::new();
+        let (_pin, fired, counters) = debounced(&gpio, Duration::from_secs(10));
+
+        gpio.pulse_for(PIN, STABLE_TIME * 3);
+        assert_eq!(fired.recv_timeout(WAIT), Ok(Level::High));
+
+        thread::sleep(STABLE_TIME * 3);
+        gpio.pulse_for(PIN, STABLE_TIME * 3);
+
+        assert!(fired.recv_timeout(WAIT).is_err());
+        assert_eq!(counters.coalesced(), 1);
+    }
+
+    #[test]
+    fn stops_firing_once_cleared() {
+        let gpio = MockGpio::new();
+        let (mut pin, fired, _) = debounced(&gpio, Duration::ZERO);
+
+        pin.clear_async_interrupt().unwrap();
+        gpio.set_input(PIN, Level::High);
+
+        assert!(fired.recv_timeout(WAIT).is_err());
+    }
+}
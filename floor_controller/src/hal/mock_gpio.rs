@@ -0,0 +1,225 @@
+use crate::hal::{
+    Gpio, HalError, InputPin, InterruptCallback, Level, OutputPin, Pull, Result, Trigger,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+// Computes the level of an input from the outputs, e.g. the spot sensor from the
+// address the controller wrote
+pub type InputSource = Box<dyn Fn(&OutputLevels) -> Level + Send>;
+
+// In memory pins: the inputs are set by whoever drives the mock, the outputs keep
+// their last level, and every write once record_writes is called. Cloning it
+// shares the same pins
+#[derive(Clone, Default)]
+pub struct MockGpio {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    taken: HashSet<u8>,
+    inputs: HashMap<u8, MockInput>,
+    outputs: HashMap<u8, Level>,
+    // Only while recording, the scanner writes the address pins all the time
+    writes: Option<Vec<(u8, Level)>>,
+}
+
+struct MockInput {
+    level: Level,
+    source: Option<InputSource>,
+    interrupt: Option<(Trigger, Arc<Mutex<InterruptCallback>>)>,
+}
+
+impl Default for MockInput {
+    fn default() -> Self {
+        MockInput {
+            level: Level::Low,
+            source: None,
+            interrupt: None,
+        }
+    }
+}
+
+pub struct OutputLevels<'a>(&'a HashMap<u8, Level>);
+
+impl OutputLevels<'_> {
+    // Outputs start low, like the ones handed out by output_low
+    pub fn level(&self, pin: u8) -> Level {
+        self.0.get(&pin).copied().unwrap_or(Level::Low)
+    }
+}
+
+impl MockState {
+    fn read(&self, pin: u8) -> Level {
+        match self.inputs.get(&pin) {
+            Some(MockInput {
+                source: Some(source),
+                ..
+            }) => source(&OutputLevels(&self.outputs)),
+            Some(input) => input.level,
+            None => Level::Low,
+        }
+    }
+
+    fn take(&mut self, pin: u8) -> Result<()> {
+        if !self.taken.insert(pin) {
+            return Err(HalError(format!("pin {} is already in use", pin)));
+        }
+
+        Ok(())
+    }
+}
+
+impl MockGpio {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Sets the level of an input, firing its interrupt when the change is an edge
+    // it listens to. The interrupt runs on the calling thread, so its effects are
+    // visible once this returns
+    pub fn set_input(&self, pin: u8, level: Level) {
+        let interrupt = {
+            let mut state = self.state.lock().unwrap();
+            let previous = state.read(pin);
+
+            let input = state.inputs.entry(pin).or_default();
+            input.level = level;
+            input.source = None;
+
+            match &input.interrupt {
+                Some((trigger, callback)) if trigger.matches(previous, level) => {
+                    Some(callback.clone())
+                }
+                _ => None,
+            }
+        };
+
+        // Without the lock, the interrupt reads and writes the other pins
+        if let Some(callback) = interrupt {
+            (callback.lock().unwrap())(level);
+        }
+    }
+
+    // A rising edge followed by a falling one, like a car going past a sensor
+    pub fn pulse(&self, pin: u8) {
+        self.set_input(pin, Level::High);
+        self.set_input(pin, Level::Low);
+    }
+
+    // Same as pulse, holding the input high long enough to get through the debouncing
+    pub fn pulse_for(&self, pin: u8, width: Duration) {
+        self.set_input(pin, Level::High);
+        thread::sleep(width);
+        self.set_input(pin, Level::Low);
+    }
+
+    // From now on the input is computed on every read, until set_input is called.
+    // Changes of a computed input fire no interrupts
+    pub fn set_input_source(&self, pin: u8, source: InputSource) {
+        let mut state = self.state.lock().unwrap();
+        state.inputs.entry(pin).or_default().source = Some(source);
+    }
+
+    pub fn output(&self, pin: u8) -> Level {
+        OutputLevels(&self.state.lock().unwrap().outputs).level(pin)
+    }
+
+    // Records the writes from now on, dropping the ones recorded before
+    pub fn record_writes(&self) {
+        self.state.lock().unwrap().writes = Some(Vec::new());
+    }
+
+    // Every write to an output since record_writes, in order, as (pin, level)
+    pub fn writes(&self) -> Vec<(u8, Level)> {
+        self.state
+            .lock()
+            .unwrap()
+            .writes
+            .clone()
+            .unwrap_or_default()
+    }
+}
+
+impl Gpio for MockGpio {
+    // A pulled up input starts high, unless it was already set
+    fn input(&self, pin: u8, pull: Pull) -> Result<Box<dyn InputPin>> {
+        let mut state = self.state.lock().unwrap();
+        state.take(pin)?;
+
+        state.inputs.entry(pin).or_insert_with(|| MockInput {
+            level: if pull == Pull::Up {
+                Level::High
+            } else {
+                Level::Low
+            },
+            ..MockInput::default()
+        });
+
+        Ok(Box::new(MockInputPin {
+            pin,
+            state: self.state.clone(),
+        }))
+    }
+
+    fn output_low(&self, pin: u8) -> Result<Box<dyn OutputPin>> {
+        let mut state = self.state.lock().unwrap();
+        state.take(pin)?;
+        state.outputs.insert(pin, Level::Low);
+
+        Ok(Box::new(MockOutputPin {
+            pin,
+            state: self.state.clone(),
+        }))
+    }
+}
+
+pub struct MockInputPin {
+    pin: u8,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl InputPin for MockInputPin {
+    fn read(&self) -> Level {
+        self.state.lock().unwrap().read(self.pin)
+    }
+
+    fn set_async_interrupt(&mut self, trigger: Trigger, callback: InterruptCallback) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.inputs.entry(self.pin).or_default().interrupt =
+            Some((trigger, Arc::new(Mutex::new(callback))));
+
+        Ok(())
+    }
+
+    fn clear_async_interrupt(&mut self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(input) = state.inputs.get_mut(&self.pin) {
+            input.interrupt = None;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct MockOutputPin {
+    pin: u8,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl OutputPin for MockOutputPin {
+    fn write(&mut self, level: Level) {
+        let mut state = self.state.lock().unwrap();
+        state.outputs.insert(self.pin, level);
+
+        if let Some(writes) = &mut state.writes {
+            writes.push((self.pin, level));
+        }
+    }
+}
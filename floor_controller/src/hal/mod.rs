@@ -0,0 +1,112 @@
+// Pins and interrupts behind traits, so the sensor and barrier logic doesn't depend
+// on the Raspberry Pi. The backend is picked by the cargo features:
+// "rppal" (the default) drives the real pins, "mock" keeps them in memory. The
+// tests always have the mock
+pub mod debounce;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock_gpio;
+#[cfg(feature = "rppal")]
+pub mod rppal_gpio;
+
+#[cfg(not(any(feature = "rppal", feature = "mock")))]
+compile_error!("enable the \"rppal\" or the \"mock\" feature to pick a GPIO backend");
+
+use serde::Deserialize;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Low,
+    High,
+}
+
+// Resistor of an input pin
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pull {
+    Down,
+    Up,
+    Off,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trigger {
+    RisingEdge,
+    FallingEdge,
+    Both,
+}
+
+impl Trigger {
+    // Whether going from `from` to `to` fires an interrupt with this trigger
+    pub fn matches(self, from: Level, to: Level) -> bool {
+        match self {
+            Trigger::RisingEdge => from == Level::Low && to == Level::High,
+            Trigger::FallingEdge => from == Level::High && to == Level::Low,
+            Trigger::Both => from != to,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HalError(pub String);
+
+impl fmt::Display for HalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for HalError {}
+
+pub type Result<T> = std::result::Result<T, HalError>;
+
+// Called with the new level of the pin, from a thread other than the one that set it up
+pub type InterruptCallback = Box<dyn FnMut(Level) + Send>;
+
+pub trait InputPin: Send {
+    fn read(&self) -> Level;
+
+    fn is_high(&self) -> bool {
+        self.read() == Level::High
+    }
+
+    fn is_low(&self) -> bool {
+        self.read() == Level::Low
+    }
+
+    // Replaces the interrupt of the pin, if it had one
+    fn set_async_interrupt(&mut self, trigger: Trigger, callback: InterruptCallback) -> Result<()>;
+
+    // Drops the interrupt, no edge reaches the callback after this returns
+    fn clear_async_interrupt(&mut self) -> Result<()>;
+}
+
+pub trait OutputPin: Send {
+    fn write(&mut self, level: Level);
+
+    fn set_high(&mut self) {
+        self.write(Level::High)
+    }
+
+    fn set_low(&mut self) {
+        self.write(Level::Low)
+    }
+}
+
+// Hands out the pins by their BCM number, each pin only once
+pub trait Gpio {
+    fn input(&self, pin: u8, pull: Pull) -> Result<Box<dyn InputPin>>;
+
+    fn output_low(&self, pin: u8) -> Result<Box<dyn OutputPin>>;
+}
+
+// The backend of the enabled feature, the real pins when both are
+#[cfg(feature = "rppal")]
+pub fn open() -> Result<Box<dyn Gpio>> {
+    Ok(Box::new(rppal_gpio::RppalGpio::new()?))
+}
+
+#[cfg(all(feature = "mock", not(feature = "rppal")))]
+pub fn open() -> Result<Box<dyn Gpio>> {
+    Ok(Box::new(mock_gpio::MockGpio::new()))
+}
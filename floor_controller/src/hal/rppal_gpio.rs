@@ -0,0 +1,94 @@
+use crate::hal::{
+    Gpio, HalError, InputPin, InterruptCallback, Level, OutputPin, Pull, Result, Trigger,
+};
+use rppal::gpio;
+
+pub struct RppalGpio {
+    gpio: gpio::Gpio,
+}
+
+impl RppalGpio {
+    pub fn new() -> Result<Self> {
+        let gpio = gpio::Gpio::new().map_err(to_hal_error)?;
+
+        Ok(RppalGpio { gpio })
+    }
+}
+
+impl Gpio for RppalGpio {
+    fn input(&self, pin: u8, pull: Pull) -> Result<Box<dyn InputPin>> {
+        let pin = self.gpio.get(pin).map_err(to_hal_error)?;
+
+        let pin = match pull {
+            Pull::Down => pin.into_input_pulldown(),
+            Pull::Up => pin.into_input_pullup(),
+            Pull::Off => pin.into_input(),
+        };
+
+        Ok(Box::new(RppalInputPin(pin)))
+    }
+
+    fn output_low(&self, pin: u8) -> Result<Box<dyn OutputPin>> {
+        let pin = self.gpio.get(pin).map_err(to_hal_error)?;
+
+        Ok(Box::new(RppalOutputPin(pin.into_output_low())))
+    }
+}
+
+pub struct RppalInputPin(gpio::InputPin);
+
+impl InputPin for RppalInputPin {
+    fn read(&self) -> Level {
+        from_rppal_level(self.0.read())
+    }
+
+    fn set_async_interrupt(
+        &mut self,
+        trigger: Trigger,
+        mut callback: InterruptCallback,
+    ) -> Result<()> {
+        self.0
+            .set_async_interrupt(to_rppal_trigger(trigger), move |level| {
+                callback(from_rppal_level(level))
+            })
+            .map_err(to_hal_error)
+    }
+
+    fn clear_async_interrupt(&mut self) -> Result<()> {
+        self.0.clear_async_interrupt().map_err(to_hal_error)
+    }
+}
+
+pub struct RppalOutputPin(gpio::OutputPin);
+
+impl OutputPin for RppalOutputPin {
+    fn write(&mut self, level: Level) {
+        self.0.write(to_rppal_level(level))
+    }
+}
+
+fn from_rppal_level(level: gpio::Level) -> Level {
+    match level {
+        gpio::Level::Low => Level::Low,
+        gpio::Level::High => Level::High,
+    }
+}
+
+fn to_rppal_level(level: Level) -> gpio::Level {
+    match level {
+        Level::Low => gpio::Level::Low,
+        Level::High => gpio::Level::High,
+    }
+}
+
+fn to_rppal_trigger(trigger: Trigger) -> gpio::Trigger {
+    match trigger {
+        Trigger::RisingEdge => gpio::Trigger::RisingEdge,
+        Trigger::FallingEdge => gpio::Trigger::FallingEdge,
+        Trigger::Both => gpio::Trigger::Both,
+    }
+}
+
+fn to_hal_error(error: gpio::Error) -> HalError {
+    HalError(error.to_string())
+}
@@ -0,0 +1,7 @@
+pub mod config;
+pub mod controller;
+pub mod gpio;
+pub mod hal;
+pub mod model;
+pub mod socket;
+pub mod utils;
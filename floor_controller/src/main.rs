@@ -0,0 +1,56 @@
+use fse_trab_1_floor_controller::config::FloorConfig;
+use fse_trab_1_floor_controller::controller::{self, Controller};
+use fse_trab_1_floor_controller::gpio::gpio_pins::GpioPins;
+use fse_trab_1_floor_controller::hal;
+use fse_trab_1_floor_controller::model::ParkingLot;
+use fse_trab_1_floor_controller::socket::event_queue::EventQueue;
+use fse_trab_1_floor_controller::socket::socket_client;
+use fse_trab_1_floor_controller::utils::configure_graceful_shutdown;
+use fse_trab_1_logging::{self as logging, LoggingConfig};
+use tracing::info;
+
+fn main() {
+    logging::init(&LoggingConfig::new(None).with_env_overrides());
+
+    // The floor role, client id and pins, see the files in config/
+    let config = FloorConfig::load();
+    info!(
+        path = config.path.as_str(),
+        floor = config.client_id.as_str(),
+        "configuration loaded"
+    );
+
+    // Everything the controller reacts to, including the shutdown
+    let (events, receiver) = controller::channel();
+    configure_graceful_shutdown::send_shutdown_on_signals(&events);
+
+    // Setting up GPIO pins, on the backend picked by the cargo features
+    let gpio = hal::open().unwrap();
+    let gpio_pins = GpioPins::new(gpio.as_ref(), &config);
+
+    // Creating the parking lot
+    let parking_lot = ParkingLot::new();
+
+    // The spot events the server didn't get, including the ones of the last run
+    let event_queue = EventQueue::open(&config);
+
+    // Setting up the socket.io client
+    let client = socket_client::new_client(&config, &parking_lot, &event_queue, &events);
+
+    // Starting the scanner and the interrupts of the pins
+    let controller = Controller::new(
+        &config,
+        gpio_pins,
+        &client,
+        &parking_lot,
+        &event_queue,
+        &events,
+        receiver,
+    );
+
+    // Blocks until Ctrl-C or a panic, then stops the pins and disconnects
+    info!(floor = config.client_id.as_str(), "program started");
+    controller.run();
+
+    info!(floor = config.client_id.as_str(), "program terminated");
+}
@@ -0,0 +1,157 @@
+use rust_socketio::Payload;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+#[derive(Serialize, Deserialize)]
+pub struct ParkingLot {
+    pub spaces: Vec<bool>,
+    // Spots taken out of service by the operator, they are not scanned
+    pub out_of_service: Vec<bool>,
+}
+
+impl ParkingLot {
+    pub fn new() -> Arc<Mutex<ParkingLot>> {
+        let mut spaces = Vec::with_capacity(8);
+
+        for _ in 0..8 {
+            spaces.push(false);
+        }
+
+        let parking_lot = ParkingLot {
+            spaces,
+            out_of_service: vec![false; 8],
+        };
+
+        Arc::new(Mutex::new(parking_lot))
+    }
+
+    pub fn update_spaces(&mut self, spaces: &Vec<bool>) {
+        for i in 0..8 {
+            self.spaces[i] = spaces[i];
+        }
+    }
+
+    pub fn update_out_of_service(&mut self, out_of_service: &Vec<bool>) {
+        for i in 0..8 {
+            self.out_of_service[i] = out_of_service[i];
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ParkingSpaceModifiedPayload {
+    pub parking_space: i32,
+    pub timestamp: i64,
+}
+
+impl Into<Payload> for ParkingSpaceModifiedPayload {
+    fn into(self) -> Payload {
+        Payload::from(serde_json::to_value(self).unwrap())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ClockSyncRequestPayload {
+    pub server_time: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ClockSyncPayload {
+    pub server_time: i64,
+    pub client_time: i64,
+}
+
+impl Into<Payload> for ClockSyncPayload {
+    fn into(self) -> Payload {
+        Payload::from(serde_json::to_value(self).unwrap())
+    }
+}
+
+// Totals since the controller started, per debounced sensor
+#[derive(Serialize, Deserialize)]
+pub struct InputGlitchesPayload {
+    pub pins: Vec<PinGlitchesPayload>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PinGlitchesPayload {
+    pub pin: String,
+    // Pulses shorter than the stable time
+    pub glitches: u64,
+    // Edges merged into the previous one
+    pub coalesced: u64,
+}
+
+impl Into<Payload> for InputGlitchesPayload {
+    fn into(self) -> Payload {
+        Payload::from(serde_json::to_value(self).unwrap())
+    }
+}
+
+// The barrier ("entry" or "exit") was stopped by a timeout
+#[derive(Serialize, Deserialize)]
+pub struct BarrierFaultPayload {
+    pub barrier: String,
+    // "close_signal_timeout" or "motor_timeout"
+    pub fault: String,
+    pub timestamp: i64,
+}
+
+impl Into<Payload> for BarrierFaultPayload {
+    fn into(self) -> Payload {
+        Payload::from(serde_json::to_value(self).unwrap())
+    }
+}
+
+// The barrier went through a full cycle again after a fault
+#[derive(Serialize, Deserialize)]
+pub struct BarrierRecoveredPayload {
+    pub barrier: String,
+    pub timestamp: i64,
+}
+
+impl Into<Payload> for BarrierRecoveredPayload {
+    fn into(self) -> Payload {
+        Payload::from(serde_json::to_value(self).unwrap())
+    }
+}
+
+// A spot changed without a car going past the gate or the ramp to match it, e.g.
+// a car that changed spots or a sensor that failed
+#[derive(Serialize, Deserialize)]
+pub struct SpotMismatchPayload {
+    // "arrival_without_entry" or "departure_without_exit", which have the spot,
+    // "entry_without_arrival" or "exit_without_departure", which don't
+    pub kind: String,
+    pub parking_space: Option<i32>,
+    pub timestamp: i64,
+}
+
+impl Into<Payload> for SpotMismatchPayload {
+    fn into(self) -> Payload {
+        Payload::from(serde_json::to_value(self).unwrap())
+    }
+}
+
+// A car seen by only one sensor of the ramp, which turned back or was missed by
+// the other sensor
+#[derive(Serialize, Deserialize)]
+pub struct AmbiguousPassagePayload {
+    // "pass_through_sensor_1" or "pass_through_sensor_2"
+    pub first_sensor: String,
+    pub timestamp: i64,
+}
+
+impl Into<Payload> for AmbiguousPassagePayload {
+    fn into(self) -> Payload {
+        Payload::from(serde_json::to_value(self).unwrap())
+    }
+}
+
+// Whole lot, sent to the ground floor only
+#[derive(Serialize, Deserialize)]
+pub struct LotOccupancyPayload {
+    pub occupied: i32,
+    pub capacity: i32,
+    pub is_closed: bool,
+}
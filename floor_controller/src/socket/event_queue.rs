@@ -0,0 +1,421 @@
+use crate::config::FloorConfig;
+use crate::model::ParkingSpaceModifiedPayload;
+use crate::socket::socket_client::{EmitError, ServerClient};
+use crate::socket::socket_operations::{CAR_ARRIVED, CAR_DEPARTED};
+use rust_socketio::Payload;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    fs::{self, OpenOptions},
+    io::Write,
+    sync::{Arc, Condvar, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+// The spot events the server didn't take yet, kept on disk so they survive the
+// server being down and the controller restarting. They are sent one at a time in
+// the order they happened, with the timestamps they were taken with, and each one
+// only leaves the queue once the server acknowledges it
+pub struct EventQueue {
+    floor: String,
+    path: String,
+    ack_timeout: Duration,
+    state: Mutex<QueueState>,
+    // Notified on every acknowledgement, for the shutdown
+    acknowledged: Condvar,
+}
+
+struct QueueState {
+    pending: VecDeque<QueuedEvent>,
+    // The front event was sent at this time and its acknowledgement didn't come
+    in_flight: Option<(u64, Instant)>,
+    next_id: u64,
+    // Some event failed or wasn't acknowledged, logged when the queue empties
+    backlog: bool,
+}
+
+// One line of the file
+#[derive(Serialize, Deserialize)]
+struct QueuedEvent {
+    // Tells a late acknowledgement from the one of the event now in front
+    #[serde(skip)]
+    id: u64,
+    event: String,
+    payload: serde_json::Value,
+}
+
+impl EventQueue {
+    // Loads what a previous run left unsent
+    pub fn open(config: &FloorConfig) -> Arc<EventQueue> {
+        let path = config.queue_path();
+        let mut pending: VecDeque<QueuedEvent> = VecDeque::new();
+
+        if let Ok(contents) = fs::read_to_string(&path) {
+            for line in contents.lines().filter(|line| !line.is_empty()) {
+                match serde_json::from_str(line) {
+                    Ok(queued) => pending.push_back(queued),
+                    Err(error) => {
+                        warn!(path = path.as_str(), %error, "unreadable queued event, dropped")
+                    }
+                }
+            }
+        }
+
+        if !pending.is_empty() {
+            info!(
+                floor = config.client_id.as_str(),
+                path = path.as_str(),
+                events = pending.len(),
+                "events left from the last run"
+            );
+        }
+
+        for (id, queued) in pending.iter_mut().enumerate() {
+            queued.id = id as u64;
+        }
+
+        Arc::new(EventQueue {
+            floor: config.client_id.clone(),
+            path,
+            ack_timeout: config.queue.ack_timeout(),
+            state: Mutex::new(QueueState {
+                next_id: pending.len() as u64,
+                backlog: !pending.is_empty(),
+                pending,
+                in_flight: None,
+            }),
+            acknowledged: Condvar::new(),
+        })
+    }
+
+    // Saves the event and sends it right away, unless older ones are still
+    // waiting for the server, in which case it goes after them
+    pub fn send<P: Serialize>(
+        self: &Arc<Self>,
+        client: &Arc<ServerClient>,
+        event: &'static str,
+        payload: P,
+    ) {
+        {
+            let mut state = self.state.lock().unwrap();
+
+            let queued = QueuedEvent {
+                id: state.next_id,
+                event: event.to_string(),
+                payload: serde_json::to_value(payload).unwrap(),
+            };
+
+            state.next_id += 1;
+            self.append(&queued);
+            state.pending.push_back(queued);
+        }
+
+        if let Err(error) = self.send_front(client) {
+            warn!(
+                floor = self.floor.as_str(),
+                event,
+                %error,
+                "server unreachable, queueing the event"
+            );
+        }
+    }
+
+    // The events still queued, oldest first
+    #[cfg(test)]
+    pub fn queued(&self) -> Vec<(String, serde_json::Value)> {
+        let state = self.state.lock().unwrap();
+
+        state
+            .pending
+            .iter()
+            .map(|queued| (queued.event.clone(), queued.payload.clone()))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().pending.is_empty()
+    }
+
+    // Sends the front event again when it failed or wasn't acknowledged in time.
+    // The controller calls it every queue.replay_interval_millis, so the queue
+    // empties once the client reconnects
+    pub fn replay(self: &Arc<Self>, client: &Arc<ServerClient>) {
+        // Already warned about when it was queued
+        self.send_front(client).ok();
+    }
+
+    // For the shutdown, replays and waits while the server keeps acknowledging
+    pub fn flush(self: &Arc<Self>, client: &Arc<ServerClient>) {
+        self.replay(client);
+
+        let mut state = self.state.lock().unwrap();
+
+        while state.in_flight.is_some() {
+            let left = state.pending.len();
+            let (next, waited) = self
+                .acknowledged
+                .wait_timeout_while(state, self.ack_timeout, |state| state.pending.len() == left)
+                .unwrap();
+
+            state = next;
+
+            if waited.timed_out() {
+                break;
+            }
+        }
+    }
+
+    // Sends the front event, unless it is waiting for its acknowledgement. The
+    // next one is sent from the acknowledgement of this one
+    fn send_front(self: &Arc<Self>, client: &Arc<ServerClient>) -> Result<(), EmitError> {
+        let (id, event, payload) = {
+            let mut state = self.state.lock().unwrap();
+            let now = Instant::now();
+
+            if let Some((_, sent_at)) = state.in_flight {
+                if now - sent_at < self.ack_timeout {
+                    return Ok(());
+                }
+
+                // Sent again, the server tells the copies apart by their timestamps
+                warn!(
+                    floor = self.floor.as_str(),
+                    "the server didn't acknowledge the event, sending it again"
+                );
+                state.backlog = true;
+            }
+
+            let Some(queued) = state.pending.front() else {
+                return Ok(());
+            };
+
+            let front = (queued.id, queued.event.clone(), queued.payload.clone());
+            state.in_flight = Some((front.0, now));
+            front
+        };
+
+        let queue = self.clone();
+        let server = client.clone();
+
+        let sent = client.emit_with_ack(
+            event,
+            Payload::from(payload),
+            self.ack_timeout,
+            move |_, _| queue.acknowledge(&server, id),
+        );
+
+        if sent.is_err() {
+            let mut state = self.state.lock().unwrap();
+
+            if state
+                .in_flight
+                .is_some_and(|(in_flight, _)| in_flight == id)
+            {
+                state.in_flight = None;
+            }
+
+            state.backlog = true;
+        }
+
+        sent
+    }
+
+    fn acknowledge(self: &Arc<Self>, client: &Arc<ServerClient>, id: u64) {
+        {
+            let mut state = self.state.lock().unwrap();
+
+            // A late acknowledgement of an event already sent again
+            if state.pending.front().map(|queued| queued.id) != Some(id) {
+                return;
+            }
+
+            state.pending.pop_front();
+            state.in_flight = None;
+            self.rewrite(&state.pending);
+            self.acknowledged.notify_all();
+
+            if state.pending.is_empty() && state.backlog {
+                state.backlog = false;
+                info!(floor = self.floor.as_str(), "queued events sent");
+            }
+        }
+
+        self.replay(client);
+    }
+
+    // The floor state of the server doesn't know about the queued events yet, so
+    // they are applied on top of it
+    pub fn apply_pending(&self, spaces: &mut [bool]) {
+        for queued in self.state.lock().unwrap().pending.iter() {
+            let occupied = match queued.event.as_str() {
+                event if event == CAR_ARRIVED => true,
+                event if event == CAR_DEPARTED => false,
+                _ => continue,
+            };
+
+            let Ok(payload) =
+                serde_json::from_value::<ParkingSpaceModifiedPayload>(queued.payload.clone())
+            else {
+                continue;
+            };
+
+            if let Some(space) = spaces.get_mut(payload.parking_space as usize) {
+                *space = occupied;
+            }
+        }
+    }
+
+    fn append(&self, queued: &QueuedEvent) {
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| {
+                writeln!(file, "{}", serde_json::to_string(queued).unwrap())?;
+                file.sync_data()
+            });
+
+        // Still kept in memory, it is only lost if the controller restarts
+        if let Err(error) = written {
+            warn!(path = self.path.as_str(), %error, "failed to save the queued event");
+        }
+    }
+
+    // Written aside and renamed, so a crash never leaves half a file
+    fn rewrite(&self, pending: &VecDeque<QueuedEvent>) {
+        let written = if pending.is_empty() {
+            fs::remove_file(&self.path)
+        } else {
+            let temporary = format!("{}.tmp", self.path);
+            let contents: String = pending
+                .iter()
+                .map(|queued| serde_json::to_string(queued).unwrap() + "\n")
+                .collect();
+
+            fs::write(&temporary, contents).and_then(|_| fs::rename(&temporary, &self.path))
+        };
+
+        if let Err(error) = written {
+            warn!(path = self.path.as_str(), %error, "failed to save the queue");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn spot_event(parking_space: i32, timestamp: i64) -> ParkingSpaceModifiedPayload {
+        ParkingSpaceModifiedPayload {
+            parking_space,
+            timestamp,
+        }
+    }
+
+    // As if the front event had been sent `ago`
+    fn sent_ago(queue: &EventQueue, ago: Duration) {
+        let mut state = queue.state.lock().unwrap();
+        let id = state.pending.front().unwrap().id;
+        state.in_flight = Some((id, Instant::now().checked_sub(ago).unwrap()));
+    }
+
+    #[test]
+    fn keeps_the_events_across_restarts_in_order() {
+        let config = FloorConfig::for_tests("queue_restart");
+        let client = ServerClient::disconnected();
+
+        let queue = EventQueue::open(&config);
+        queue.send(&client, CAR_ARRIVED, spot_event(1, 10));
+        queue.send(&client, CAR_DEPARTED, spot_event(1, 20));
+        drop(queue);
+
+        let queue = EventQueue::open(&config);
+        assert_eq!(
+            queue.queued(),
+            vec![
+                (
+                    CAR_ARRIVED.to_string(),
+                    json!({ "parking_space": 1, "timestamp": 10 })
+                ),
+                (
+                    CAR_DEPARTED.to_string(),
+                    json!({ "parking_space": 1, "timestamp": 20 })
+                ),
+            ]
+        );
+
+        fs::remove_file(config.queue_path()).ok();
+    }
+
+    #[test]
+    fn leaves_the_queue_only_once_acknowledged() {
+        let config = FloorConfig::for_tests("queue_acknowledged");
+        let client = ServerClient::disconnected();
+
+        let queue = EventQueue::open(&config);
+        queue.send(&client, CAR_ARRIVED, spot_event(1, 10));
+        queue.send(&client, CAR_ARRIVED, spot_event(2, 11));
+        sent_ago(&queue, Duration::ZERO);
+
+        // Of some other event
+        queue.acknowledge(&client, 1);
+        assert_eq!(queue.len(), 2);
+
+        queue.acknowledge(&client, 0);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(EventQueue::open(&config).len(), 1);
+
+        sent_ago(&queue, Duration::ZERO);
+        queue.acknowledge(&client, 1);
+        assert!(queue.is_empty());
+        assert!(fs::metadata(config.queue_path()).is_err());
+    }
+
+    #[test]
+    fn sends_again_what_was_not_acknowledged_in_time() {
+        let config = FloorConfig::for_tests("queue_resend");
+        let client = ServerClient::disconnected();
+
+        let queue = EventQueue::open(&config);
+        queue.send(&client, CAR_ARRIVED, spot_event(1, 10));
+
+        // Still waiting, nothing is sent
+        sent_ago(&queue, Duration::ZERO);
+        queue.replay(&client);
+        assert!(queue.state.lock().unwrap().in_flight.is_some());
+
+        // Sent again, which fails without the server
+        sent_ago(&queue, config.queue.ack_timeout());
+        queue.replay(&client);
+        assert!(queue.state.lock().unwrap().in_flight.is_none());
+        assert_eq!(queue.len(), 1);
+
+        fs::remove_file(config.queue_path()).ok();
+    }
+
+    #[test]
+    fn applies_the_queued_events_on_the_server_state() {
+        let config = FloorConfig::for_tests("queue_apply");
+        let client = ServerClient::disconnected();
+
+        let queue = EventQueue::open(&config);
+        queue.send(&client, CAR_ARRIVED, spot_event(2, 10));
+        queue.send(&client, CAR_DEPARTED, spot_event(5, 11));
+
+        let mut spaces = [false, false, false, false, false, true, false, false];
+        queue.apply_pending(&mut spaces);
+
+        assert_eq!(
+            spaces,
+            [false, false, true, false, false, false, false, false]
+        );
+
+        fs::remove_file(config.queue_path()).ok();
+    }
+}
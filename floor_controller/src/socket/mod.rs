@@ -1,3 +1,5 @@
+pub mod event_queue;
 pub mod socket_async_interrupts;
 pub mod socket_client;
 pub mod socket_operations;
+pub mod socket_reports;
@@ -0,0 +1,137 @@
+use crate::controller::ControllerEvent;
+use crate::model::{ClockSyncPayload, ClockSyncRequestPayload, LotOccupancyPayload, ParkingLot};
+use crate::socket::event_queue::EventQueue;
+use crate::socket::socket_operations::{
+    CLOCK_SYNC, CLOCK_SYNC_REQUEST, CONNECTION_CLOSED, FLOOR_STATE, LOT_OCCUPANCY,
+    SPOTS_OUT_OF_SERVICE,
+};
+use chrono::Utc;
+use rust_socketio::ClientBuilder;
+use rust_socketio::Payload;
+use std::sync::{mpsc::Sender, Arc, Mutex};
+use tracing::warn;
+
+// The ground floor closes with the whole parking lot, the upper floors on their own,
+// so the event depends on the role of the floor
+pub fn set_close_signal(
+    client: ClientBuilder,
+    events: &Sender<ControllerEvent>,
+    event: &'static str,
+) -> ClientBuilder {
+    let events_clone = events.clone();
+
+    client.on(event, move |_, _| {
+        events_clone.send(ControllerEvent::SetClosed(true)).ok();
+    })
+}
+
+pub fn set_open_signal(
+    client: ClientBuilder,
+    events: &Sender<ControllerEvent>,
+    event: &'static str,
+) -> ClientBuilder {
+    let events_clone = events.clone();
+
+    client.on(event, move |_, _| {
+        events_clone.send(ControllerEvent::SetClosed(false)).ok();
+    })
+}
+
+pub fn set_floor_state_signal(
+    client: ClientBuilder,
+    parking_lot: &Arc<Mutex<ParkingLot>>,
+    event_queue: &Arc<EventQueue>,
+) -> ClientBuilder {
+    let parking_lot_clone = parking_lot.clone();
+    let event_queue_clone = event_queue.clone();
+
+    // Sent on every (re)connection, the events still queued happened after it
+    client.on(FLOOR_STATE, move |payload, _| {
+        let mut spaces: Vec<bool>;
+
+        if let Payload::Text(data) = payload {
+            spaces = serde_json::from_str(&data[0].to_string()).unwrap();
+        } else {
+            panic!("Payload is not text");
+        }
+
+        event_queue_clone.apply_pending(&mut spaces);
+
+        parking_lot_clone.lock().unwrap().update_spaces(&spaces);
+    })
+}
+
+pub fn set_out_of_service_signal(
+    client: ClientBuilder,
+    parking_lot: &Arc<Mutex<ParkingLot>>,
+) -> ClientBuilder {
+    let parking_lot_clone = parking_lot.clone();
+
+    client.on(SPOTS_OUT_OF_SERVICE, move |payload, _| {
+        let out_of_service: Vec<bool>;
+
+        if let Payload::Text(data) = payload {
+            out_of_service = serde_json::from_str(&data[0].to_string()).unwrap();
+        } else {
+            panic!("Payload is not text");
+        }
+
+        parking_lot_clone
+            .lock()
+            .unwrap()
+            .update_out_of_service(&out_of_service);
+    })
+}
+
+// The count of the server replaces the local one, until the connection drops
+pub fn set_lot_occupancy_signal(
+    client: ClientBuilder,
+    events: &Sender<ControllerEvent>,
+) -> ClientBuilder {
+    let events_clone = events.clone();
+    let client = client.on(LOT_OCCUPANCY, move |payload, _| {
+        let occupancy: LotOccupancyPayload;
+
+        if let Payload::Text(data) = payload {
+            occupancy = serde_json::from_str(&data[0].to_string()).unwrap();
+        } else {
+            panic!("Payload is not text");
+        }
+
+        events_clone
+            .send(ControllerEvent::LotOccupancy(occupancy))
+            .ok();
+    });
+
+    let events_clone = events.clone();
+    client.on(CONNECTION_CLOSED, move |_, _| {
+        events_clone.send(ControllerEvent::ConnectionClosed).ok();
+    })
+}
+
+pub fn set_clock_sync_signal(client: ClientBuilder) -> ClientBuilder {
+    client.on(CLOCK_SYNC_REQUEST, move |payload, socket| {
+        // Answer right away with our own clock, so the server can estimate the offset
+        let client_time = Utc::now().timestamp_millis();
+        let request: ClockSyncRequestPayload;
+
+        if let Payload::Text(data) = payload {
+            request = serde_json::from_str(&data[0].to_string()).unwrap();
+        } else {
+            panic!("Payload is not text");
+        }
+
+        // The server measures the clock again on its next resync
+        let answer = socket.emit(
+            CLOCK_SYNC,
+            ClockSyncPayload {
+                server_time: request.server_time,
+                client_time,
+            },
+        );
+
+        if let Err(error) = answer {
+            warn!(%error, "failed to answer the clock sync request");
+        }
+    })
+}
@@ -0,0 +1,206 @@
+use crate::config::{FloorConfig, FloorRole};
+use crate::controller::ControllerEvent;
+use crate::model::ParkingLot;
+use crate::socket::event_queue::EventQueue;
+use crate::socket::socket_async_interrupts::{
+    set_clock_sync_signal, set_close_signal, set_floor_state_signal, set_lot_occupancy_signal,
+    set_open_signal, set_out_of_service_signal,
+};
+use crate::socket::socket_operations::{
+    AUTHORIZATION_HEADER_KEY, CLIENT_HEADER_KEY, CLOSING_FLOOR, CLOSING_PARKING_LOT, OPENING_FLOOR,
+    OPENING_PARKING_LOT,
+};
+use rust_socketio::{client::Client, ClientBuilder, Event, Payload, RawClient};
+use std::fmt;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::Sender,
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+// The connection to the server. The controller starts without it and the client
+// keeps connecting in the background, so a floor that boots while the server is
+// down still works: until then everything sent fails like while disconnected
+pub struct ServerClient {
+    client: Mutex<Option<Client>>,
+    // Set on shutdown, stops the connecting
+    stopped: AtomicBool,
+}
+
+pub enum EmitError {
+    // The first connection wasn't made yet
+    NotConnected,
+    Socket(rust_socketio::Error),
+}
+
+impl fmt::Display for EmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmitError::NotConnected => write!(f, "not connected to the server yet"),
+            EmitError::Socket(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl ServerClient {
+    pub fn emit<E: Into<Event>, D: Into<Payload>>(
+        &self,
+        event: E,
+        data: D,
+    ) -> Result<(), EmitError> {
+        match self.client.lock().unwrap().as_ref() {
+            Some(client) => client.emit(event, data).map_err(EmitError::Socket),
+            None => Err(EmitError::NotConnected),
+        }
+    }
+
+    // The callback runs once the server acknowledges the event, and never if it
+    // doesn't within the timeout
+    pub fn emit_with_ack<E, D, F>(
+        &self,
+        event: E,
+        data: D,
+        timeout: Duration,
+        callback: F,
+    ) -> Result<(), EmitError>
+    where
+        E: Into<Event>,
+        D: Into<Payload>,
+        F: FnMut(Payload, RawClient) + 'static + Send + Sync,
+    {
+        match self.client.lock().unwrap().as_ref() {
+            Some(client) => client
+                .emit_with_ack(event, data, timeout, callback)
+                .map_err(EmitError::Socket),
+            None => Err(EmitError::NotConnected),
+        }
+    }
+
+    // Never connects, everything sent fails like while the server is down
+    #[cfg(test)]
+    pub fn disconnected() -> Arc<ServerClient> {
+        Arc::new(ServerClient {
+            client: Mutex::new(None),
+            stopped: AtomicBool::new(true),
+        })
+    }
+
+    // Also stops connecting when the server was never reached
+    pub fn disconnect(&self) -> Result<(), rust_socketio::Error> {
+        self.stopped.store(true, Ordering::SeqCst);
+
+        match self.client.lock().unwrap().take() {
+            Some(client) => client.disconnect(),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn new_client(
+    config: &FloorConfig,
+    parking_lot: &Arc<Mutex<ParkingLot>>,
+    event_queue: &Arc<EventQueue>,
+    events: &Sender<ControllerEvent>,
+) -> Arc<ServerClient> {
+    // Creating the client
+    let mut client = ClientBuilder::new(config.server_url.as_str())
+        .opening_header(CLIENT_HEADER_KEY, config.client_id.as_str())
+        .reconnect_on_disconnect(true);
+
+    if config.timings.reconnect_attempts > 0 {
+        client = client.max_reconnect_attempts(config.timings.reconnect_attempts);
+    }
+
+    if let Some(token) = &config.credentials.token {
+        client = client.opening_header(AUTHORIZATION_HEADER_KEY, format!("Bearer {}", token));
+    }
+
+    let (closing, opening) = match config.role {
+        FloorRole::Gates { .. } => (CLOSING_PARKING_LOT, OPENING_PARKING_LOT),
+        FloorRole::Ramp { .. } => (CLOSING_FLOOR, OPENING_FLOOR),
+    };
+
+    // Setting up the close signal
+    client = set_close_signal(client, events, closing);
+
+    // Setting up the open signal
+    client = set_open_signal(client, events, opening);
+
+    // Setting up the parking lot state signal
+    client = set_floor_state_signal(client, parking_lot, event_queue);
+
+    // Setting up the spots out of service, which are not scanned
+    client = set_out_of_service_signal(client, parking_lot);
+
+    // The ground floor closes the lot on its own while the server is gone, the
+    // others only log it
+    client = set_lot_occupancy_signal(client, events);
+
+    // Answering the server clock measurements
+    client = set_clock_sync_signal(client);
+
+    let server_client = Arc::new(ServerClient {
+        client: Mutex::new(None),
+        stopped: AtomicBool::new(false),
+    });
+
+    // Connecting to the server
+    connect_in_background(config, client, &server_client);
+
+    server_client
+}
+
+// Tries until it connects or the controller stops. Only the first
+// connect_attempts failures are logged one by one
+fn connect_in_background(config: &FloorConfig, client: ClientBuilder, server: &Arc<ServerClient>) {
+    let server_url = config.server_url.clone();
+    let floor = config.client_id.clone();
+    let connect_attempts = config.timings.connect_attempts;
+    let connect_retry = config.timings.connect_retry();
+    let server = server.clone();
+
+    thread::spawn(move || {
+        let mut failed = 0;
+
+        while !server.stopped.load(Ordering::SeqCst) {
+            match client.clone().connect() {
+                Ok(connection) => {
+                    let mut client = server.client.lock().unwrap();
+
+                    // Shut down while connecting
+                    if server.stopped.load(Ordering::SeqCst) {
+                        connection.disconnect().ok();
+                        return;
+                    }
+
+                    info!(
+                        server = server_url.as_str(),
+                        floor = floor.as_str(),
+                        "connected to the server"
+                    );
+                    *client = Some(connection);
+                    return;
+                }
+                Err(error) => {
+                    failed += 1;
+
+                    if failed < connect_attempts {
+                        warn!(%error, "error connecting to the server, retrying");
+                    } else if failed == connect_attempts {
+                        warn!(
+                            server = server_url.as_str(),
+                            floor = floor.as_str(),
+                            %error,
+                            "server unreachable, running without it and still connecting"
+                        );
+                    }
+                }
+            }
+
+            thread::sleep(connect_retry);
+        }
+    });
+}
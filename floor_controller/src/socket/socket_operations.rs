@@ -0,0 +1,21 @@
+// The client id itself comes from the configuration of the floor
+pub static CLIENT_HEADER_KEY: &str = "X-Client-Id";
+pub static AUTHORIZATION_HEADER_KEY: &str = "Authorization";
+pub static CLOSING_PARKING_LOT: &str = "close_parking_lot";
+pub static OPENING_PARKING_LOT: &str = "open_parking_lot";
+pub static CLOSING_FLOOR: &str = "close_floor";
+pub static OPENING_FLOOR: &str = "open_floor";
+pub static CAR_ARRIVED: &str = "car_arrived";
+pub static CAR_DEPARTED: &str = "car_departed";
+pub static FLOOR_STATE: &str = "floor_state";
+pub static CLOCK_SYNC_REQUEST: &str = "clock_sync_request";
+pub static CLOCK_SYNC: &str = "clock_sync";
+pub static SPOTS_OUT_OF_SERVICE: &str = "spots_out_of_service";
+pub static INPUT_GLITCHES: &str = "input_glitches";
+pub static BARRIER_FAULT: &str = "barrier_fault";
+pub static BARRIER_RECOVERED: &str = "barrier_recovered";
+pub static SPOT_MISMATCH: &str = "spot_mismatch";
+pub static AMBIGUOUS_PASSAGE: &str = "ambiguous_passage";
+pub static LOT_OCCUPANCY: &str = "lot_occupancy";
+// Fired by the client itself when the connection drops
+pub static CONNECTION_CLOSED: &str = "close";
@@ -0,0 +1,66 @@
+use crate::config::FloorConfig;
+use crate::hal::debounce::GlitchCounters;
+use crate::model::{InputGlitchesPayload, PinGlitchesPayload};
+use crate::socket::socket_client::ServerClient;
+use crate::socket::socket_operations::INPUT_GLITCHES;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+// Sends the glitches filtered by the debouncing, only when they went up. Called
+// by the controller every debounce.report_interval_secs
+pub struct GlitchReporter {
+    floor: String,
+    client: Arc<ServerClient>,
+    glitch_counters: Vec<(&'static str, Arc<GlitchCounters>)>,
+    last_reported: (u64, u64),
+}
+
+impl GlitchReporter {
+    pub fn new(
+        config: &FloorConfig,
+        client: &Arc<ServerClient>,
+        glitch_counters: &[(&'static str, Arc<GlitchCounters>)],
+    ) -> GlitchReporter {
+        GlitchReporter {
+            floor: config.client_id.clone(),
+            client: client.clone(),
+            glitch_counters: glitch_counters.to_vec(),
+            last_reported: (0, 0),
+        }
+    }
+
+    pub fn report(&mut self) {
+        let pins: Vec<PinGlitchesPayload> = self
+            .glitch_counters
+            .iter()
+            .map(|(name, counters)| PinGlitchesPayload {
+                pin: name.to_string(),
+                glitches: counters.glitches(),
+                coalesced: counters.coalesced(),
+            })
+            .collect();
+
+        let totals = pins.iter().fold((0, 0), |(glitches, coalesced), pin| {
+            (glitches + pin.glitches, coalesced + pin.coalesced)
+        });
+
+        if totals == self.last_reported {
+            return;
+        }
+
+        info!(
+            floor = self.floor.as_str(),
+            glitches = totals.0,
+            coalesced = totals.1,
+            "input glitches filtered"
+        );
+
+        match self
+            .client
+            .emit(INPUT_GLITCHES, InputGlitchesPayload { pins })
+        {
+            Ok(_) => self.last_reported = totals,
+            Err(error) => warn!(%error, "failed to report the input glitches"),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use crate::controller::ControllerEvent;
+use ctrlc;
+use std::{panic, sync::mpsc::Sender};
+use tracing::{error, info};
+
+// Wakes the controller up to shut down, instead of it polling a flag
+pub fn send_shutdown_on_signals(events: &Sender<ControllerEvent>) {
+    // Handling Ctrl-C
+    let e = events.clone();
+    ctrlc::set_handler(move || {
+        info!("received Ctrl-C, shutting down");
+        e.send(ControllerEvent::Shutdown("ctrl-c")).ok();
+    })
+    .unwrap();
+
+    // Handling panic
+    let panic_hook = panic::take_hook();
+    let e = events.clone();
+    panic::set_hook(Box::new(move |panic_info| {
+        error!(%panic_info, "panicked, shutting down");
+        panic_hook(panic_info);
+        e.send(ControllerEvent::Shutdown("panic")).ok();
+    }));
+}
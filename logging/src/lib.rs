@@ -0,0 +1,233 @@
+use serde::Deserialize;
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use tracing_subscriber::{fmt::MakeWriter, EnvFilter};
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct LoggingConfig {
+    // Any tracing filter directive, e.g. "info" or "info,fse_trab_1_server::mqtt=debug"
+    pub level: String,
+    pub format: LogFormat,
+    // Log to this file instead of stdout
+    pub file: Option<String>,
+    pub max_file_size_mb: u64,
+    // How many rotated files are kept besides the current one
+    pub max_files: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::Text,
+            file: None,
+            max_file_size_mb: 10,
+            max_files: 5,
+        }
+    }
+}
+
+impl LoggingConfig {
+    // For the programs without a configuration file of their own
+    pub fn new(file: Option<&str>) -> Self {
+        Self {
+            file: file.map(str::to_string),
+            ..Self::default()
+        }
+    }
+
+    // The environment always has the last word, so the logging can be
+    // changed without touching the configuration file. Everything can be
+    // changed through LOG_LEVEL, LOG_FORMAT, LOG_FILE, LOG_MAX_SIZE_MB and
+    // LOG_MAX_FILES
+    pub fn with_env_overrides(mut self) -> Self {
+        if let Ok(level) = env::var("LOG_LEVEL") {
+            self.level = level;
+        }
+
+        if let Ok(format) = env::var("LOG_FORMAT") {
+            self.format = match format.to_lowercase().as_str() {
+                "json" => LogFormat::Json,
+                _ => LogFormat::Text,
+            };
+        }
+
+        if let Ok(file) = env::var("LOG_FILE") {
+            self.file = Some(file).filter(|file| !file.is_empty());
+        }
+
+        if let Some(max_file_size_mb) = env::var("LOG_MAX_SIZE_MB")
+            .ok()
+            .and_then(|value| value.parse().ok())
+        {
+            self.max_file_size_mb = max_file_size_mb;
+        }
+
+        if let Some(max_files) = env::var("LOG_MAX_FILES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+        {
+            self.max_files = max_files;
+        }
+
+        self
+    }
+}
+
+// Without a file the logs go to stdout
+pub fn init(config: &LoggingConfig) {
+    init_with(config, true);
+}
+
+// For the programs that own the terminal, like the TUI of the app: without a file
+// the logs are discarded
+pub fn init_off_terminal(config: &LoggingConfig) {
+    init_with(config, false);
+}
+
+fn init_with(config: &LoggingConfig, to_stdout: bool) {
+    let filter = EnvFilter::try_new(&config.level).unwrap_or_else(|_| EnvFilter::new("info"));
+
+    match &config.file {
+        Some(path) => {
+            let writer = RotatingFile::open(
+                path,
+                config.max_file_size_mb * 1024 * 1024,
+                config.max_files,
+            )
+            .unwrap();
+
+            install(config.format, filter, writer, false);
+        }
+        None if to_stdout => install(config.format, filter, io::stdout, true),
+        None => install(config.format, filter, io::sink, false),
+    }
+}
+
+fn install<W>(format: LogFormat, filter: EnvFilter, writer: W, ansi: bool)
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(true)
+        .with_writer(writer);
+
+    match format {
+        LogFormat::Json => builder.json().init(),
+        LogFormat::Text => builder.with_ansi(ansi).init(),
+    }
+}
+
+// Log file that is rotated once it grows past max_size:
+// server.log -> server.log.1 -> server.log.2 ... up to max_files
+pub struct RotatingFile {
+    inner: Mutex<RotatingFileState>,
+}
+
+struct RotatingFileState {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    pub fn open(path: &str, max_size: u64, max_files: usize) -> io::Result<Self> {
+        let path = PathBuf::from(path);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            inner: Mutex::new(RotatingFileState {
+                path,
+                file,
+                size,
+                max_size,
+                max_files,
+            }),
+        })
+    }
+}
+
+impl RotatingFileState {
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            for index in (1..self.max_files).rev() {
+                let from = rotated_path(&self.path, index);
+
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, index + 1))?;
+                }
+            }
+
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+
+        self.size = 0;
+
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{}", index));
+
+    PathBuf::from(rotated)
+}
+
+impl Write for &RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.inner.lock().unwrap();
+
+        // Each event is written at once, so rotating here never splits a line
+        if state.max_size > 0 && state.size > 0 && state.size + buf.len() as u64 > state.max_size {
+            state.rotate()?;
+        }
+
+        let written = state.file.write(buf)?;
+        state.size += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.lock().unwrap().file.flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for RotatingFile {
+    type Writer = &'a RotatingFile;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self
+    }
+}
@@ -14,7 +14,7 @@ fi
 
 
 # Check if the fse folder exists on the Raspberry Pi and if all the binaries are there
-if ! sshpass -e ssh eduardofarias@164.41.98.16 -p 13508 "[ -d ~/fse ] && [ -f ~/fse/fse_trab_1_server ] && [ -f ~/fse/fse_trab_1_ground_floor ] && [ -f ~/fse/fse_trab_1_first_floor ] && [ -f ~/fse/fse_trab_1_second_floor ]"; then
+if ! sshpass -e ssh eduardofarias@164.41.98.16 -p 13508 "[ -d ~/fse ] && [ -f ~/fse/fse_trab_1_server ] && [ -f ~/fse/fse_trab_1_floor_controller ] && [ -f ~/fse/ground_floor.toml ] && [ -f ~/fse/first_floor.toml ] && [ -f ~/fse/second_floor.toml ]"; then
     echo "Please run send.sh before running this script"
     exit 1
 fi
@@ -31,31 +31,34 @@ sshpass -e ssh eduardofarias@164.41.98.16 -p 13508 "
     mkdir ~/fse/db
 "
 
-# Run the binaries on the Raspberry Pi
+# Run the binaries on the Raspberry Pi, each one writes its own rotated log file
+# (LOG_LEVEL, LOG_FORMAT, LOG_MAX_SIZE_MB and LOG_MAX_FILES can tune it) and
+# anything written outside the logger, like panics, goes to logs/*.stderr.log.
+# The floors are the same controller, FSE_FLOOR_CONFIG picks the floor it runs
 echo "Running binaries on the Raspberry Pi..."
 
 echo "Starting the server..."
 sshpass -e ssh eduardofarias@164.41.98.16 -p 13508 "
     cd ~/fse 
-    nohup ./fse_trab_1_server > logs/server.log 2>&1 &
+    LOG_FILE=logs/server.log nohup ./fse_trab_1_server > logs/server.stderr.log 2>&1 &
 "
 
 echo "Starting the ground floor..."
 sshpass -e ssh eduardofarias@164.41.98.16 -p 13508 "
     cd ~/fse 
-    nohup ./fse_trab_1_ground_floor > logs/ground_floor.log 2>&1 &
+    FSE_FLOOR_CONFIG=ground_floor.toml LOG_FILE=logs/ground_floor.log nohup ./fse_trab_1_floor_controller > logs/ground_floor.stderr.log 2>&1 &
 "
 
 echo "Starting the first floor..."
 sshpass -e ssh eduardofarias@164.41.98.16 -p 13508 "
     cd ~/fse 
-    nohup ./fse_trab_1_first_floor > logs/first_floor.log 2>&1 &
+    FSE_FLOOR_CONFIG=first_floor.toml LOG_FILE=logs/first_floor.log nohup ./fse_trab_1_floor_controller > logs/first_floor.stderr.log 2>&1 &
 "
 
 echo "Starting the second floor..."
 sshpass -e ssh eduardofarias@164.41.98.16 -p 13508 "
     cd ~/fse 
-    nohup ./fse_trab_1_second_floor > logs/second_floor.log 2>&1 &
+    FSE_FLOOR_CONFIG=second_floor.toml LOG_FILE=logs/second_floor.log nohup ./fse_trab_1_floor_controller > logs/second_floor.stderr.log 2>&1 &
 "
 
 echo "All binaries are running on the Raspberry Pi, use htop to monitor them or send SIGINT to stop them"
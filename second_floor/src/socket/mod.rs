@@ -1,3 +0,0 @@
-pub mod socket_async_interrupts;
-pub mod socket_client;
-pub mod socket_operations;
@@ -1 +0,0 @@
-pub mod configure_graceful_shutdown;
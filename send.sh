@@ -1,7 +1,7 @@
 #!/bin/sh
 
 # Check if the .bin folder exists and if all the binaries are there
-if [ ! -d ".bin" ] || [ ! -f ".bin/fse_trab_1_server" ] || [ ! -f ".bin/fse_trab_1_ground_floor" ] || [ ! -f ".bin/fse_trab_1_first_floor" ] || [ ! -f ".bin/fse_trab_1_second_floor" ]; then
+if [ ! -d ".bin" ] || [ ! -f ".bin/fse_trab_1_server" ] || [ ! -f ".bin/fse_trab_1_floor_controller" ] || [ ! -f ".bin/ground_floor.toml" ] || [ ! -f ".bin/first_floor.toml" ] || [ ! -f ".bin/second_floor.toml" ]; then
   echo "Please run compile.sh before running this script"
   exit 1
 fi
@@ -34,4 +34,4 @@ echo "Sending the .bin folder to the Raspberry Pi..."
 # Send the .bin folder to the Raspberry Pi
 sshpass -e scp -P 13508 .bin/* eduardofarias@164.41.98.16:~/fse
 
-echo "All binaries sent to the Raspberry Pi, use run.sh to run them"
+echo "All binaries and configuration files sent to the Raspberry Pi, use run.sh to run them"
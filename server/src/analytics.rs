@@ -0,0 +1,213 @@
+use crate::{
+    database::Database,
+    models::{analytics::VehicleStay, parking_lot::SpotType},
+    socket::payloads::{
+        AnalyticsPayload, DailyPeakPayload, SpotTurnoverPayload, SpotTypeTurnoverPayload,
+    },
+};
+use chrono::{DateTime, Local, Timelike, Utc};
+use rusqlite::Error;
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+// Computes the statistics of the [from, to] window, by default everything
+// since the first vehicle entered until now
+pub fn compute(
+    database: &Database,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<AnalyticsPayload, Error> {
+    let to = to.unwrap_or_else(|| Utc::now().timestamp());
+    let from = match from {
+        Some(from) => from,
+        None => database.get_first_entry_time()?.unwrap_or(to),
+    };
+
+    let stays = database.get_vehicle_stays(from, to)?;
+    let days = ((to - from) as f64 / SECONDS_PER_DAY).max(1.0);
+
+    let arrivals: Vec<&VehicleStay> = stays
+        .iter()
+        .filter(|stay| stay.entry_time >= from)
+        .collect();
+
+    let departures: Vec<i64> = stays
+        .iter()
+        .filter_map(|stay| stay.exit_time)
+        .filter(|exit_time| *exit_time <= to)
+        .collect();
+
+    // Dwell time of the vehicles that left inside the window
+    let mut dwell_minutes: Vec<f64> = stays
+        .iter()
+        .filter_map(|stay| match stay.exit_time {
+            Some(exit_time) if exit_time <= to => Some((exit_time - stay.entry_time) as f64 / 60.0),
+            _ => None,
+        })
+        .collect();
+
+    dwell_minutes.sort_by(|a, b| a.total_cmp(b));
+
+    let mut hourly_arrivals = vec![0; 24];
+    let mut hourly_departures = vec![0; 24];
+
+    for stay in &arrivals {
+        hourly_arrivals[local_time(stay.entry_time).hour() as usize] += 1;
+    }
+
+    for exit_time in &departures {
+        hourly_departures[local_time(*exit_time).hour() as usize] += 1;
+    }
+
+    Ok(AnalyticsPayload {
+        from,
+        to,
+        arrivals: arrivals.len(),
+        departures: departures.len(),
+        average_dwell_minutes: average(&dwell_minutes),
+        median_dwell_minutes: median(&dwell_minutes),
+        spot_turnover: spot_turnover(database, &arrivals, days)?,
+        spot_type_turnover: spot_type_turnover(database, &arrivals, days)?,
+        hourly_arrivals,
+        hourly_departures,
+        daily_peak_occupancy: daily_peak_occupancy(&stays, from, to),
+    })
+}
+
+fn spot_turnover(
+    database: &Database,
+    arrivals: &[&VehicleStay],
+    days: f64,
+) -> Result<Vec<SpotTurnoverPayload>, Error> {
+    let mut turnover = Vec::with_capacity(24);
+
+    for floor_number in 0..3 {
+        for spot in database.get_floor(floor_number)?.spots {
+            let vehicles = arrivals
+                .iter()
+                .filter(|stay| {
+                    stay.floor_number == Some(floor_number)
+                        && stay.spot_number == Some(spot.spot_number)
+                })
+                .count();
+
+            turnover.push(SpotTurnoverPayload {
+                floor_number,
+                spot_number: spot.spot_number,
+                spot_type: spot.spot_type as i32,
+                vehicles,
+                turnover_per_day: vehicles as f64 / days,
+            });
+        }
+    }
+
+    Ok(turnover)
+}
+
+fn spot_type_turnover(
+    database: &Database,
+    arrivals: &[&VehicleStay],
+    days: f64,
+) -> Result<Vec<SpotTypeTurnoverPayload>, Error> {
+    let mut spot_types = Vec::new();
+
+    for floor_number in 0..3 {
+        for spot in database.get_floor(floor_number)?.spots {
+            spot_types.push(spot.spot_type);
+        }
+    }
+
+    let turnover = [SpotType::Normal, SpotType::Handicapped, SpotType::Elderly]
+        .into_iter()
+        .map(|spot_type| {
+            let spots = spot_types.iter().filter(|t| **t == spot_type).count();
+            let vehicles = arrivals
+                .iter()
+                .filter(|stay| stay.spot_type == Some(spot_type))
+                .count();
+
+            SpotTypeTurnoverPayload {
+                spot_type: spot_type as i32,
+                spots,
+                vehicles,
+                turnover_per_day: match spots {
+                    0 => 0.0,
+                    spots => vehicles as f64 / spots as f64 / days,
+                },
+            }
+        })
+        .collect();
+
+    Ok(turnover)
+}
+
+// Replays every entry and exit in order, keeping the highest occupancy of each day
+fn daily_peak_occupancy(stays: &[VehicleStay], from: i64, to: i64) -> Vec<DailyPeakPayload> {
+    let mut events: Vec<(i64, i32)> = Vec::with_capacity(stays.len() * 2);
+
+    for stay in stays {
+        events.push((stay.entry_time, 1));
+
+        if let Some(exit_time) = stay.exit_time {
+            events.push((exit_time, -1));
+        }
+    }
+
+    // Exits first when they happen at the same second, so a freed spot isn't counted twice
+    events.sort();
+
+    let mut peaks: Vec<DailyPeakPayload> = Vec::new();
+    let mut occupancy = 0;
+
+    for (time, change) in events {
+        occupancy += change;
+
+        if time < from || time > to {
+            continue;
+        }
+
+        let date = local_time(time).format("%Y-%m-%d").to_string();
+
+        match peaks.last_mut() {
+            Some(peak) if peak.date == date => {
+                if occupancy > peak.peak_occupancy {
+                    peak.peak_occupancy = occupancy;
+                    peak.peak_at = time;
+                }
+            }
+            _ => peaks.push(DailyPeakPayload {
+                date,
+                // The day starts with whatever was parked before its first event
+                peak_occupancy: occupancy.max(occupancy - change),
+                peak_at: time,
+            }),
+        }
+    }
+
+    peaks
+}
+
+fn local_time(timestamp: i64) -> DateTime<Local> {
+    DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .with_timezone(&Local)
+}
+
+fn average(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+// Expects the values to be sorted
+fn median(values: &[f64]) -> Option<f64> {
+    let middle = values.len() / 2;
+
+    match values.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((values[middle - 1] + values[middle]) / 2.0),
+        _ => Some(values[middle]),
+    }
+}
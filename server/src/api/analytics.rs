@@ -0,0 +1,16 @@
+use super::{ApiError, ApiState, TimeWindow};
+use crate::{analytics, socket::payloads::AnalyticsPayload};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+// GET /api/analytics?from=<unix>&to=<unix>
+pub async fn get_analytics(
+    State(state): State<ApiState>,
+    Query(window): Query<TimeWindow>,
+) -> Result<Json<AnalyticsPayload>, ApiError> {
+    let database = state.database.lock().unwrap();
+
+    Ok(Json(analytics::compute(&database, window.from, window.to)?))
+}
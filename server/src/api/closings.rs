@@ -0,0 +1,43 @@
+use super::{ApiError, ApiState};
+use crate::{
+    closing,
+    socket::payloads::{CashClosingPayload, CloseDayPayload},
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+// GET /api/closings, most recent first
+pub async fn list_closings(
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<CashClosingPayload>>, ApiError> {
+    let database = state.database.lock().unwrap();
+
+    Ok(Json(closing::list(&database)?))
+}
+
+// GET /api/closings/<yyyy-mm-dd>
+pub async fn get_closing(
+    State(state): State<ApiState>,
+    Path(date): Path<String>,
+) -> Result<Json<CashClosingPayload>, ApiError> {
+    let database = state.database.lock().unwrap();
+
+    Ok(Json(closing::find(&database, &date)?))
+}
+
+// POST /api/closings
+// {"date": "2024-05-31"}, or no body to close the last day that ended
+pub async fn close_day(
+    State(state): State<ApiState>,
+    request: Option<Json<CloseDayPayload>>,
+) -> Result<(StatusCode, Json<CashClosingPayload>), ApiError> {
+    let mut database = state.database.lock().unwrap();
+
+    let date = request.and_then(|Json(request)| request.date);
+    let closing = closing::close_day(&state.config.cash_closing, &mut database, date.as_deref())?;
+
+    Ok((StatusCode::CREATED, Json(closing.into())))
+}
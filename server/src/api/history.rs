@@ -0,0 +1,19 @@
+use super::{ApiError, ApiState};
+use crate::{
+    history,
+    socket::payloads::{OccupancyHistoryPayload, OccupancyHistoryWindowPayload},
+};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+// GET /api/occupancy?from=<unix>&to=<unix>&resolution=<raw|hourly>
+pub async fn get_occupancy(
+    State(state): State<ApiState>,
+    Query(window): Query<OccupancyHistoryWindowPayload>,
+) -> Result<Json<OccupancyHistoryPayload>, ApiError> {
+    let database = state.database.lock().unwrap();
+
+    Ok(Json(history::query(&database, &window)?))
+}
@@ -0,0 +1,10 @@
+use super::ApiState;
+use crate::{inputs, socket::payloads::ControllerInputsPayload};
+use axum::{extract::State, Json};
+
+// GET /api/inputs, the glitches each controller filtered out of its inputs
+pub async fn list_inputs(State(state): State<ApiState>) -> Json<Vec<ControllerInputsPayload>> {
+    let database = state.database.lock().unwrap();
+
+    Json(inputs::list(&database))
+}
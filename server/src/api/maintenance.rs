@@ -0,0 +1,44 @@
+use super::{ApiError, ApiState};
+use crate::{
+    maintenance,
+    socket::payloads::{SpotMaintenancePayload, SpotOutOfServicePayload},
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+// GET /api/maintenance, the current and scheduled maintenances
+pub async fn list_maintenances(
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<SpotMaintenancePayload>>, ApiError> {
+    let database = state.database.lock().unwrap();
+
+    Ok(Json(maintenance::list(&database)?))
+}
+
+// POST /api/maintenance
+// {"floor_number": 1, "spot_number": 3, "reason": "...", "starts_at": <unix>, "ends_at": <unix>}
+pub async fn set_out_of_service(
+    State(state): State<ApiState>,
+    Json(request): Json<SpotOutOfServicePayload>,
+) -> Result<(StatusCode, Json<SpotMaintenancePayload>), ApiError> {
+    let mut database = state.database.lock().unwrap();
+
+    let maintenance = maintenance::set_out_of_service(&state.io, &mut database, &request)?;
+
+    Ok((StatusCode::CREATED, Json(maintenance.into())))
+}
+
+// DELETE /api/maintenance/<floor_number>/<spot_number>
+pub async fn return_to_service(
+    State(state): State<ApiState>,
+    Path((floor_number, spot_number)): Path<(i32, i32)>,
+) -> Result<StatusCode, ApiError> {
+    let mut database = state.database.lock().unwrap();
+
+    maintenance::return_to_service(&state.io, &mut database, floor_number, spot_number)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,147 @@
+mod analytics;
+mod closings;
+mod history;
+mod inputs;
+mod maintenance;
+mod overstay;
+mod receipts;
+mod sensor_reports;
+mod storage;
+mod subscribers;
+
+use crate::{
+    closing::ClosingError, config::Config, database::Database, maintenance::MaintenanceError,
+    receipts::ReceiptError, subscribers::SubscriberError,
+};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
+    Router,
+};
+use serde::Deserialize;
+use socketioxide::SocketIo;
+use std::sync::{Arc, Mutex};
+use tracing::error;
+
+#[derive(Clone)]
+pub struct ApiState {
+    pub config: Arc<Config>,
+    pub database: Arc<Mutex<Database>>,
+    // The requests that change the lot also have to reach the controllers
+    pub io: SocketIo,
+}
+
+// HTTP API for the systems that don't speak socket.io, mounted under /api
+pub fn router(config: &Arc<Config>, database: &Arc<Mutex<Database>>, io: &SocketIo) -> Router {
+    Router::new()
+        .route("/analytics", get(analytics::get_analytics))
+        .route("/occupancy", get(history::get_occupancy))
+        .route("/inputs", get(inputs::list_inputs))
+        .route("/sensor-reports", get(sensor_reports::list_sensor_reports))
+        .route("/overstays", get(overstay::list_overstays))
+        .route(
+            "/maintenance",
+            get(maintenance::list_maintenances).post(maintenance::set_out_of_service),
+        )
+        .route(
+            "/maintenance/:floor_number/:spot_number",
+            delete(maintenance::return_to_service),
+        )
+        .route(
+            "/subscribers",
+            get(subscribers::list_subscribers).post(subscribers::add_subscriber),
+        )
+        .route("/subscribers/:id", delete(subscribers::remove_subscriber))
+        .route("/subscribers/identify", post(subscribers::identify_vehicle))
+        .route("/receipts/:number", get(receipts::get_receipt))
+        .route("/receipts/:number/text", get(receipts::get_receipt_text))
+        .route(
+            "/receipts/:number/payment",
+            put(receipts::set_payment_method),
+        )
+        .route(
+            "/closings",
+            get(closings::list_closings).post(closings::close_day),
+        )
+        .route("/closings/:date", get(closings::get_closing))
+        .route("/daily", get(storage::get_daily_summaries))
+        .route("/storage", get(storage::get_storage_report))
+        .route("/storage/vacuum", post(storage::vacuum))
+        .with_state(ApiState {
+            config: config.clone(),
+            database: database.clone(),
+            io: io.clone(),
+        })
+}
+
+// Optional time window, as unix timestamps, accepted by the queries
+#[derive(Deserialize)]
+pub struct TimeWindow {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+pub enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    Internal(String),
+}
+
+impl From<rusqlite::Error> for ApiError {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Internal(error.to_string())
+    }
+}
+
+impl From<MaintenanceError> for ApiError {
+    fn from(error: MaintenanceError) -> Self {
+        match error {
+            MaintenanceError::Database(error) => error.into(),
+            error => Self::BadRequest(error.to_string()),
+        }
+    }
+}
+
+impl From<SubscriberError> for ApiError {
+    fn from(error: SubscriberError) -> Self {
+        match error {
+            SubscriberError::Database(error) => error.into(),
+            error => Self::BadRequest(error.to_string()),
+        }
+    }
+}
+
+impl From<ReceiptError> for ApiError {
+    fn from(error: ReceiptError) -> Self {
+        match error {
+            ReceiptError::NotFound => Self::NotFound(error.to_string()),
+            ReceiptError::Database(error) => error.into(),
+            error => Self::BadRequest(error.to_string()),
+        }
+    }
+}
+
+impl From<ClosingError> for ApiError {
+    fn from(error: ClosingError) -> Self {
+        match error {
+            ClosingError::NotFound => Self::NotFound(error.to_string()),
+            ClosingError::Database(error) => error.into(),
+            error => Self::BadRequest(error.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::BadRequest(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+            Self::NotFound(message) => (StatusCode::NOT_FOUND, message).into_response(),
+            Self::Internal(message) => {
+                error!(error = %message, "API request failed");
+
+                (StatusCode::INTERNAL_SERVER_ERROR, message).into_response()
+            }
+        }
+    }
+}
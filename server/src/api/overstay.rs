@@ -0,0 +1,12 @@
+use super::{ApiError, ApiState};
+use crate::{overstay, socket::payloads::VehicleOverstayedPayload};
+use axum::{extract::State, Json};
+
+// GET /api/overstays, the parked vehicles that went past the limit of their spot
+pub async fn list_overstays(
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<VehicleOverstayedPayload>>, ApiError> {
+    let database = state.database.lock().unwrap();
+
+    Ok(Json(overstay::list(&database)?))
+}
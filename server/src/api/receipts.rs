@@ -0,0 +1,55 @@
+use super::{ApiError, ApiState};
+use crate::{
+    receipts,
+    socket::payloads::{PaymentMethodPayload, ReceiptPayload},
+};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+// GET /api/receipts/<number>
+pub async fn get_receipt(
+    State(state): State<ApiState>,
+    Path(number): Path<i64>,
+) -> Result<Json<ReceiptPayload>, ApiError> {
+    let database = state.database.lock().unwrap();
+
+    match receipts::document(&database, number)?.receipt {
+        Some(receipt) => Ok(Json(receipt)),
+        None => Err(ApiError::NotFound(format!(
+            "there is no receipt {}",
+            number
+        ))),
+    }
+}
+
+// GET /api/receipts/<number>/text, plain text for a thermal printer
+pub async fn get_receipt_text(
+    State(state): State<ApiState>,
+    Path(number): Path<i64>,
+) -> Result<String, ApiError> {
+    let database = state.database.lock().unwrap();
+
+    match receipts::document(&database, number)?.text {
+        Some(text) => Ok(text),
+        None => Err(ApiError::NotFound(format!(
+            "there is no receipt {}",
+            number
+        ))),
+    }
+}
+
+// PUT /api/receipts/<number>/payment
+// {"payment_method": "cash" | "card" | "pix" | "subscription"}
+pub async fn set_payment_method(
+    State(state): State<ApiState>,
+    Path(number): Path<i64>,
+    Json(request): Json<PaymentMethodPayload>,
+) -> Result<Json<ReceiptPayload>, ApiError> {
+    let mut database = state.database.lock().unwrap();
+
+    let receipt = receipts::set_payment_method(&mut database, number, request.payment_method)?;
+
+    Ok(Json(receipt.into()))
+}
@@ -0,0 +1,21 @@
+use super::{ApiError, ApiState, TimeWindow};
+use crate::{sensor_reports, socket::payloads::SensorReportPayload};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+// GET /api/sensor-reports?from=<unix>&to=<unix>, the spot mismatches and ambiguous
+// passages, newest first. Defaults to the last 24 hours
+pub async fn list_sensor_reports(
+    State(state): State<ApiState>,
+    Query(window): Query<TimeWindow>,
+) -> Result<Json<Vec<SensorReportPayload>>, ApiError> {
+    let database = state.database.lock().unwrap();
+
+    Ok(Json(sensor_reports::list(
+        &database,
+        window.from,
+        window.to,
+    )?))
+}
@@ -0,0 +1,35 @@
+use super::{ApiError, ApiState};
+use crate::{
+    retention,
+    socket::payloads::{DailySummaryPayload, DateRangePayload, StorageReportPayload},
+};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+// GET /api/storage, size of the database and the rows in it
+pub async fn get_storage_report(
+    State(state): State<ApiState>,
+) -> Result<Json<StorageReportPayload>, ApiError> {
+    let database = state.database.lock().unwrap();
+
+    Ok(Json(retention::report(&database)?))
+}
+
+// POST /api/storage/vacuum, holds every lot event while the file is rewritten
+pub async fn vacuum(State(state): State<ApiState>) -> Result<Json<StorageReportPayload>, ApiError> {
+    let mut database = state.database.lock().unwrap();
+
+    Ok(Json(retention::vacuum(&mut database)?))
+}
+
+// GET /api/daily?from=<yyyy-mm-dd>&to=<yyyy-mm-dd>
+pub async fn get_daily_summaries(
+    State(state): State<ApiState>,
+    Query(range): Query<DateRangePayload>,
+) -> Result<Json<Vec<DailySummaryPayload>>, ApiError> {
+    let database = state.database.lock().unwrap();
+
+    Ok(Json(retention::daily(&database, &range)?))
+}
@@ -0,0 +1,60 @@
+use super::{ApiError, ApiState};
+use crate::{
+    socket::payloads::{
+        IdentifyVehiclePayload, NewSubscriberPayload, SubscriberPayload, SubscriptionPayload,
+    },
+    subscribers,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+// GET /api/subscribers
+pub async fn list_subscribers(
+    State(state): State<ApiState>,
+) -> Result<Json<Vec<SubscriberPayload>>, ApiError> {
+    let database = state.database.lock().unwrap();
+
+    Ok(Json(subscribers::list(&database)?))
+}
+
+// POST /api/subscribers
+// {"credential": "ABC1D23", "name": "...", "valid_until": <unix>, "allowed_floors": [0, 1],
+//  "reserved_spot": {"floor_number": 1, "spot_number": 3}, "rate_per_minute": 0.05}
+pub async fn add_subscriber(
+    State(state): State<ApiState>,
+    Json(request): Json<NewSubscriberPayload>,
+) -> Result<(StatusCode, Json<SubscriberPayload>), ApiError> {
+    let mut database = state.database.lock().unwrap();
+
+    let subscriber = subscribers::add(&mut database, &request)?;
+
+    Ok((StatusCode::CREATED, Json(subscriber.into())))
+}
+
+// DELETE /api/subscribers/<id>
+pub async fn remove_subscriber(
+    State(state): State<ApiState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let mut database = state.database.lock().unwrap();
+
+    subscribers::remove(&mut database, id)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// POST /api/subscribers/identify
+// {"floor_number": 1, "spot_number": 3, "credential": "ABC1D23"}
+pub async fn identify_vehicle(
+    State(state): State<ApiState>,
+    Json(request): Json<IdentifyVehiclePayload>,
+) -> Result<Json<SubscriptionPayload>, ApiError> {
+    let mut database = state.database.lock().unwrap();
+
+    let subscription = subscribers::identify(&state.io, &mut database, &request)?;
+
+    Ok(Json(subscription.into()))
+}
@@ -0,0 +1,47 @@
+use crate::{
+    database::Database,
+    events::LotEvent,
+    models::barrier::BarrierFault,
+    socket::payloads::{BarrierFaultPayload, BarrierRecoveredPayload},
+};
+use tracing::{info, warn};
+
+// The ground floor stops a barrier whose engine stayed on for too long, e.g. when
+// the close sensor never fired. The fault is shown to the operator until the
+// barrier closes normally again
+pub fn record_fault(database: &mut Database, payload: &BarrierFaultPayload) {
+    warn!(
+        barrier = payload.barrier.as_str(),
+        fault = payload.fault.as_str(),
+        "barrier fault"
+    );
+
+    database.barrier_faults.insert(
+        payload.barrier.clone(),
+        BarrierFault {
+            barrier: payload.barrier.clone(),
+            fault: payload.fault.clone(),
+            since: payload.timestamp,
+        },
+    );
+
+    database
+        .events
+        .publish(LotEvent::BarrierFault(payload.clone()));
+}
+
+// Returns whether the barrier had a fault to clear
+pub fn record_recovery(database: &mut Database, payload: &BarrierRecoveredPayload) -> bool {
+    let Some(fault) = database.barrier_faults.remove(&payload.barrier) else {
+        return false;
+    };
+
+    info!(
+        barrier = payload.barrier.as_str(),
+        fault = fault.fault.as_str(),
+        down_secs = payload.timestamp - fault.since,
+        "barrier back to normal"
+    );
+
+    true
+}
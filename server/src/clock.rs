@@ -0,0 +1,162 @@
+use crate::{
+    config::{ClockConfig, ClockCorrection},
+    database::Database,
+    events::LotEvent,
+    models::{
+        client::ClientId,
+        clock::{ClockOffset, EventTime},
+    },
+    socket::{
+        constants::CLOCK_SYNC_REQUEST_EVENT,
+        payloads::{ClockSkewPayload, ClockSyncPayload, ClockSyncRequestPayload},
+    },
+};
+use chrono::Utc;
+use socketioxide::SocketIo;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time;
+use tracing::{info, warn};
+
+// The controllers answer every sync request with their own clock, the offset is
+// estimated the NTP way, assuming the request and the answer took the same time:
+//   offset = client_time - (server_sent_at + round_trip / 2)
+pub fn sync_request() -> ClockSyncRequestPayload {
+    ClockSyncRequestPayload {
+        server_time: Utc::now().timestamp_millis(),
+    }
+}
+
+// Periodically measures the controller clocks again, so drift is noticed
+pub fn spawn(config: &ClockConfig, io: &SocketIo) {
+    let resync_interval = Duration::from_secs(config.resync_interval_secs);
+    let io = io.clone();
+
+    tokio::spawn(async move {
+        let mut interval = time::interval(resync_interval);
+
+        loop {
+            interval.tick().await;
+
+            for floor in ClientId::iter_floors() {
+                if let Err(error) = io
+                    .within(floor.to_string())
+                    .emit(CLOCK_SYNC_REQUEST_EVENT, sync_request())
+                {
+                    warn!(floor = %floor, %error, "failed to request a clock sync");
+                }
+            }
+        }
+    });
+}
+
+// Stores the new offset estimate of the controller, returning the alert to
+// send when its clock just went beyond the allowed offset
+pub fn record_sync(
+    config: &ClockConfig,
+    database: &Arc<Mutex<Database>>,
+    client_id: ClientId,
+    payload: &ClockSyncPayload,
+) -> Option<ClockSkewPayload> {
+    let received_at = Utc::now().timestamp_millis();
+    let round_trip_millis = (received_at - payload.server_time).max(0);
+    let offset_millis = payload.client_time - (payload.server_time + round_trip_millis / 2);
+    let skewed = offset_millis.abs() > config.max_offset_secs * 1000;
+
+    let mut database = database.lock().unwrap();
+    let was_skewed = database
+        .clocks
+        .get(&client_id)
+        .map(|offset| offset.skewed)
+        .unwrap_or(false);
+
+    database.clocks.insert(
+        client_id,
+        ClockOffset {
+            offset_millis,
+            round_trip_millis,
+            measured_at: received_at / 1000,
+            skewed,
+        },
+    );
+
+    info!(
+        floor = %client_id,
+        offset_millis,
+        round_trip_millis,
+        "controller clock measured"
+    );
+
+    if !skewed {
+        if was_skewed {
+            info!(floor = %client_id, offset_millis, "controller clock back within the allowed offset");
+        }
+
+        return None;
+    }
+
+    if was_skewed {
+        return None;
+    }
+
+    warn!(
+        floor = %client_id,
+        offset_millis,
+        max_offset_secs = config.max_offset_secs,
+        "controller clock is skewed"
+    );
+
+    let alert = ClockSkewPayload {
+        client_id: client_id.to_string(),
+        offset_millis,
+        round_trip_millis,
+        max_offset_secs: config.max_offset_secs,
+    };
+
+    database
+        .events
+        .publish(LotEvent::ClockSkewDetected(alert.clone()));
+
+    Some(alert)
+}
+
+// Decides which time is stored for an event reported by a controller, the server
+// receive time is always kept alongside it
+pub fn event_time(
+    config: &ClockConfig,
+    database: &Database,
+    client_id: ClientId,
+    timestamp: i64,
+) -> EventTime {
+    let received_at = Utc::now().timestamp();
+
+    let Some(offset) = database.clocks.get(&client_id) else {
+        // The controller never answered a sync request, its time is taken as is
+        return EventTime {
+            timestamp,
+            received_at,
+            clock_skewed: false,
+        };
+    };
+
+    if !offset.skewed {
+        return EventTime {
+            timestamp,
+            received_at,
+            clock_skewed: false,
+        };
+    }
+
+    let timestamp = match config.correction {
+        ClockCorrection::Correct => timestamp - offset.offset_millis / 1000,
+        ClockCorrection::Flag => timestamp,
+    };
+
+    EventTime {
+        timestamp,
+        received_at,
+        clock_skewed: true,
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    config::CashClosingConfig, database::Database, models::closing::CashClosing,
+    socket::payloads::CashClosingPayload, tariff,
+};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use std::fmt::{self, Display, Formatter};
+use tracing::info;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+pub enum ClosingError {
+    InvalidDate,
+    DayNotOver,
+    AlreadyClosed,
+    NotFound,
+    Database(rusqlite::Error),
+}
+
+impl From<rusqlite::Error> for ClosingError {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Database(error)
+    }
+}
+
+impl Display for ClosingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidDate => write!(f, "the date must be in the yyyy-mm-dd format"),
+            Self::DayNotOver => write!(f, "the business day is not over yet"),
+            Self::AlreadyClosed => write!(f, "the business day was already closed"),
+            Self::NotFound => write!(f, "the business day was not closed"),
+            Self::Database(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+// Closes the business day of the date, or the last one that ended when no date
+// is given. A day can only be closed once, the record is never changed after
+pub fn close_day(
+    config: &CashClosingConfig,
+    database: &mut Database,
+    date: Option<&str>,
+) -> Result<CashClosing, ClosingError> {
+    let now = Utc::now().timestamp();
+
+    let business_date = match date {
+        Some(date) => NaiveDate::parse_from_str(date.trim(), DATE_FORMAT)
+            .map_err(|_| ClosingError::InvalidDate)?,
+        None => business_date_at(config, now).pred_opt().unwrap(),
+    };
+
+    let starts_at = cutoff(config, business_date);
+    let ends_at = cutoff(config, business_date.succ_opt().unwrap());
+
+    if ends_at > now {
+        return Err(ClosingError::DayNotOver);
+    }
+
+    let business_date = business_date.format(DATE_FORMAT).to_string();
+
+    if database.is_business_date_closed(&business_date)? {
+        return Err(ClosingError::AlreadyClosed);
+    }
+
+    let spot_types = database.get_revenue_by_spot_type(starts_at, ends_at)?;
+    let payment_methods = database.get_revenue_by_payment_method(starts_at, ends_at)?;
+
+    let mut closing = CashClosing {
+        id: 0,
+        business_date,
+        starts_at,
+        ends_at,
+        closed_at: now,
+        vehicles: payment_methods.iter().map(|total| total.vehicles).sum(),
+        total: tariff::round_cents(payment_methods.iter().map(|total| total.total).sum()),
+        spot_types,
+        payment_methods,
+        vehicles_inside: database.count_vehicles_inside(ends_at)?,
+    };
+
+    closing.id = database.insert_cash_closing(&closing)?;
+
+    info!(
+        business_date = closing.business_date,
+        vehicles = closing.vehicles,
+        total = closing.total,
+        vehicles_inside = closing.vehicles_inside,
+        "business day closed"
+    );
+
+    Ok(closing)
+}
+
+pub fn list(database: &Database) -> Result<Vec<CashClosingPayload>, ClosingError> {
+    Ok(database
+        .get_cash_closings()?
+        .into_iter()
+        .map(CashClosingPayload::from)
+        .collect())
+}
+
+pub fn find(database: &Database, business_date: &str) -> Result<CashClosingPayload, ClosingError> {
+    database
+        .get_cash_closings()?
+        .into_iter()
+        .find(|closing| closing.business_date == business_date)
+        .map(CashClosingPayload::from)
+        .ok_or(ClosingError::NotFound)
+}
+
+// The business day of an instant, the hours before the cutoff belong to the
+// previous day
+fn business_date_at(config: &CashClosingConfig, timestamp: i64) -> NaiveDate {
+    let instant = DateTime::from_timestamp(timestamp, 0).unwrap();
+
+    let local = match config.timezone {
+        Some(timezone) => instant.with_timezone(&timezone).naive_local(),
+        None => instant.with_timezone(&Local).naive_local(),
+    };
+
+    (local - TimeDelta::hours(config.cutoff_hour as i64)).date()
+}
+
+// The instant the business day of the date starts
+fn cutoff(config: &CashClosingConfig, date: NaiveDate) -> i64 {
+    let start = date.and_hms_opt(config.cutoff_hour, 0, 0).unwrap();
+
+    match config.timezone {
+        Some(timezone) => local_timestamp(&timezone, start),
+        None => local_timestamp(&Local, start),
+    }
+}
+
+fn local_timestamp<Z: TimeZone>(timezone: &Z, time: NaiveDateTime) -> i64 {
+    // A cutoff inside a DST gap doesn't exist, the day starts when the clocks jump
+    timezone
+        .from_local_datetime(&time)
+        .earliest()
+        .or_else(|| {
+            timezone
+                .from_local_datetime(&(time + TimeDelta::hours(1)))
+                .earliest()
+        })
+        .unwrap()
+        .timestamp()
+}
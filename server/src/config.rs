@@ -0,0 +1,321 @@
+use crate::models::parking_lot::SpotType;
+use chrono_tz::Tz;
+use fse_trab_1_logging::LoggingConfig;
+use serde::Deserialize;
+use std::{env, fs};
+
+pub const CONFIG_PATH_ENV: &str = "FSE_SERVER_CONFIG";
+pub const DEFAULT_CONFIG_PATH: &str = "./server.toml";
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    pub logging: LoggingConfig,
+    pub webhooks: WebhooksConfig,
+    pub overstay: OverstayConfig,
+    pub mqtt: MqttConfig,
+    pub clock: ClockConfig,
+    pub history: HistoryConfig,
+    pub forecast: ForecastConfig,
+    pub maintenance: MaintenanceConfig,
+    pub cash_closing: CashClosingConfig,
+    pub retention: RetentionConfig,
+}
+
+impl Config {
+    pub fn path() -> String {
+        env::var(CONFIG_PATH_ENV).unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string())
+    }
+
+    // Loads the configuration file, which is optional: if it doesn't exist the defaults are used.
+    // Runs before the logging is set up, so the caller is the one reporting where it came from
+    pub fn load() -> Self {
+        let path = Self::path();
+
+        let mut config: Self = match fs::read_to_string(&path) {
+            Ok(contents) => match toml::from_str(&contents) {
+                Ok(config) => config,
+                Err(error) => panic!("Invalid configuration file {}: {}", path, error),
+            },
+            Err(_) => Self::default(),
+        };
+
+        config.logging = config.logging.with_env_overrides();
+
+        if config.cash_closing.cutoff_hour > 23 {
+            panic!(
+                "Invalid configuration file {}: cutoff_hour must be between 0 and 23",
+                path
+            );
+        }
+
+        if config.retention.batch_size <= 0 {
+            panic!(
+                "Invalid configuration file {}: batch_size must be positive",
+                path
+            );
+        }
+
+        config
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct WebhooksConfig {
+    // Shared secret used to sign the body of every delivery (HMAC-SHA256)
+    pub secret: String,
+    pub targets: WebhookTargets,
+    pub max_attempts: i32,
+    pub initial_backoff_secs: i64,
+    pub max_backoff_secs: i64,
+    pub request_timeout_secs: u64,
+    pub poll_interval_millis: u64,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            secret: String::new(),
+            targets: WebhookTargets::default(),
+            max_attempts: 10,
+            initial_backoff_secs: 5,
+            max_backoff_secs: 3600,
+            request_timeout_secs: 10,
+            poll_interval_millis: 1000,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct WebhookTargets {
+    pub lot_full: Vec<String>,
+    pub floor_closed: Vec<String>,
+    pub controller_disconnected: Vec<String>,
+    pub vehicle_overstayed: Vec<String>,
+    pub clock_skew_detected: Vec<String>,
+    pub barrier_fault: Vec<String>,
+    pub sensor_report: Vec<String>,
+}
+
+impl WebhookTargets {
+    pub fn is_empty(&self) -> bool {
+        self.lot_full.is_empty()
+            && self.floor_closed.is_empty()
+            && self.controller_disconnected.is_empty()
+            && self.vehicle_overstayed.is_empty()
+            && self.clock_skew_detected.is_empty()
+            && self.barrier_fault.is_empty()
+            && self.sensor_report.is_empty()
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct OverstayConfig {
+    // Vehicles parked for longer than this are reported as overstayed, applies to
+    // the spot types without a rule of their own. None disables the check
+    pub max_minutes: Option<i64>,
+    pub spot_types: OverstaySpotTypes,
+    // Added to the fee for every minute past the limit, 0 disables the surcharge
+    pub surcharge_per_minute: f64,
+    pub check_interval_secs: u64,
+}
+
+impl Default for OverstayConfig {
+    fn default() -> Self {
+        Self {
+            max_minutes: None,
+            spot_types: OverstaySpotTypes::default(),
+            surcharge_per_minute: 0.0,
+            check_interval_secs: 60,
+        }
+    }
+}
+
+impl OverstayConfig {
+    pub fn max_minutes_for(&self, spot_type: SpotType) -> Option<i64> {
+        let max_minutes = match spot_type {
+            SpotType::Normal => self.spot_types.normal,
+            SpotType::Handicapped => self.spot_types.handicapped,
+            SpotType::Elderly => self.spot_types.elderly,
+        };
+
+        max_minutes.or(self.max_minutes)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        [SpotType::Normal, SpotType::Handicapped, SpotType::Elderly]
+            .into_iter()
+            .any(|spot_type| self.max_minutes_for(spot_type).is_some())
+    }
+}
+
+// Per spot type limits, in minutes, e.g. elderly = 240
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct OverstaySpotTypes {
+    pub normal: Option<i64>,
+    pub handicapped: Option<i64>,
+    pub elderly: Option<i64>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub keep_alive_secs: u64,
+    // Every topic is published under this prefix, e.g. parking/floor/1/spot/3
+    pub topic_prefix: String,
+    // Subscribe to <topic_prefix>/command/# and execute the open/close commands
+    pub commands: bool,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "127.0.0.1".to_string(),
+            port: 1883,
+            client_id: "fse_trab_1_server".to_string(),
+            username: None,
+            password: None,
+            keep_alive_secs: 30,
+            topic_prefix: "parking".to_string(),
+            commands: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockCorrection {
+    // Store the controller time shifted by the estimated offset
+    Correct,
+    // Store the controller time as is, only flagging the event
+    Flag,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ClockConfig {
+    // Controllers whose clock is further than this from the server clock are skewed
+    pub max_offset_secs: i64,
+    pub resync_interval_secs: u64,
+    pub correction: ClockCorrection,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            max_offset_secs: 5,
+            resync_interval_secs: 300,
+            correction: ClockCorrection::Correct,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct HistoryConfig {
+    // How often the occupancy is recorded, 0 disables the snapshots
+    pub snapshot_interval_secs: u64,
+    // Raw snapshots older than this are averaged into hourly ones
+    pub raw_retention_hours: i64,
+    // Hourly snapshots older than this are deleted by the retention, 0 keeps them
+    pub hourly_retention_days: i64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_interval_secs: 60,
+            raw_retention_hours: 48,
+            hourly_retention_days: 365,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ForecastConfig {
+    // How often the forecast sent with the parking lot state is computed again,
+    // 0 disables it
+    pub refresh_interval_secs: u64,
+}
+
+impl Default for ForecastConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval_secs: 300,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    // How often the scheduled maintenances are checked for starting or ending
+    pub check_interval_secs: u64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: 30,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CashClosingConfig {
+    // The business day starts at this hour, e.g. 4 puts the exits until 3:59
+    // in the previous day
+    pub cutoff_hour: u32,
+    // IANA name, e.g. "America/Sao_Paulo". None uses the server local time
+    pub timezone: Option<Tz>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct RetentionConfig {
+    // How often the old records are summarized and pruned, 0 disables it
+    pub interval_secs: u64,
+    // Vehicles that left longer ago than this are deleted, their daily summary
+    // is kept forever. 0 keeps every vehicle
+    pub vehicle_days: i64,
+    // Webhook deliveries that were delivered or given up on longer ago than this
+    // are deleted. 0 keeps every delivery
+    pub webhook_days: i64,
+    // Spot mismatches and ambiguous passages received longer ago than this are
+    // deleted. 0 keeps every report
+    pub sensor_report_days: i64,
+    // Input glitch reports of controllers that stopped reporting for this long
+    // are forgotten. 0 keeps the last report of every controller
+    pub input_report_hours: i64,
+    // Rows handled per transaction, the lot events wait while a batch runs
+    pub batch_size: i64,
+    // VACUUM once the free pages reach this share of the file, 0 never does
+    pub vacuum_free_percent: i64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 3600,
+            vehicle_days: 90,
+            webhook_days: 30,
+            sensor_report_days: 90,
+            input_report_hours: 24,
+            batch_size: 500,
+            vacuum_free_percent: 20,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use super::Database;
+use crate::models::analytics::VehicleStay;
+use rusqlite::{named_params, Error};
+
+impl Database {
+    // Every stay that overlaps the [from, to] window, including the vehicles still parked
+    pub fn get_vehicle_stays(&self, from: i64, to: i64) -> Result<Vec<VehicleStay>, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                v.entry_time,
+                ce.exit_time,
+                v.floor_number,
+                v.spot_number,
+                ps.spot_type
+            FROM
+                vehicle v
+            LEFT JOIN car_exit ce ON
+                v.id = ce.id
+            LEFT JOIN parking_spot ps ON
+                v.floor_number = ps.floor_number AND v.spot_number = ps.spot_number
+            WHERE
+                v.entry_time <= :to AND (ce.exit_time IS NULL OR ce.exit_time >= :from)
+            ORDER BY
+                v.entry_time ASC;",
+        )?;
+
+        let stays = stmt.query_map(
+            named_params! {
+                ":from": from,
+                ":to": to,
+            },
+            |row| {
+                Ok(VehicleStay {
+                    entry_time: row.get(0)?,
+                    exit_time: row.get(1)?,
+                    floor_number: row.get(2)?,
+                    spot_number: row.get(3)?,
+                    spot_type: row.get(4)?,
+                })
+            },
+        )?;
+
+        stays.collect()
+    }
+
+    pub fn get_first_entry_time(&self) -> Result<Option<i64>, Error> {
+        self.connection
+            .query_row("SELECT MIN(entry_time) FROM vehicle;", [], |row| row.get(0))
+    }
+}
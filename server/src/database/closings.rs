@@ -0,0 +1,324 @@
+use super::Database;
+use crate::models::{
+    closing::{CashClosing, ClosingTotal},
+    receipt::PaymentMethod,
+};
+use rusqlite::{named_params, types::FromSql, Error};
+
+// cash_closing_total.category
+const SPOT_TYPE_CATEGORY: i32 = 0;
+const PAYMENT_METHOD_CATEGORY: i32 = 1;
+
+impl Database {
+    pub(super) fn initialize_closing_tables(&self) {
+        self.connection
+            .execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS cash_closing (
+                    id INTEGER NOT NULL PRIMARY KEY,
+                    business_date TEXT NOT NULL UNIQUE,
+                    starts_at BIGINT NOT NULL,
+                    ends_at BIGINT NOT NULL,
+                    closed_at BIGINT NOT NULL,
+                    vehicles INTEGER NOT NULL,
+                    total REAL NOT NULL,
+                    vehicles_inside INTEGER NOT NULL
+                );
+
+                CREATE TABLE IF NOT EXISTS cash_closing_total (
+                    closing_id INTEGER NOT NULL,
+                    category INTEGER NOT NULL CHECK (category IN (0, 1)),
+                    key INTEGER NOT NULL,
+                    vehicles INTEGER NOT NULL,
+                    total REAL NOT NULL,
+                    PRIMARY KEY (closing_id, category, key),
+                    FOREIGN KEY (closing_id) REFERENCES cash_closing(id)
+                );
+
+                -- A closing is a fiscal record, once written it can't change
+                CREATE TRIGGER IF NOT EXISTS cash_closing_no_update
+                BEFORE UPDATE ON cash_closing
+                BEGIN
+                    SELECT RAISE(ABORT, 'cash closings are immutable');
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS cash_closing_no_delete
+                BEFORE DELETE ON cash_closing
+                BEGIN
+                    SELECT RAISE(ABORT, 'cash closings are immutable');
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS cash_closing_total_no_update
+                BEFORE UPDATE ON cash_closing_total
+                BEGIN
+                    SELECT RAISE(ABORT, 'cash closings are immutable');
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS cash_closing_total_no_delete
+                BEFORE DELETE ON cash_closing_total
+                BEGIN
+                    SELECT RAISE(ABORT, 'cash closings are immutable');
+                END;",
+            )
+            .unwrap();
+    }
+
+    // Revenue of the receipts of the exits in [starts_at, ends_at), per spot type
+    pub fn get_revenue_by_spot_type(
+        &self,
+        starts_at: i64,
+        ends_at: i64,
+    ) -> Result<Vec<ClosingTotal<i32>>, Error> {
+        self.get_revenue(
+            "
+            SELECT
+                ps.spot_type,
+                COUNT(*),
+                COALESCE(SUM(r.total), 0)
+            FROM
+                receipt r
+            INNER JOIN parking_spot ps ON
+                ps.floor_number = r.floor_number AND ps.spot_number = r.spot_number
+            WHERE
+                r.exit_time >= :starts_at AND r.exit_time < :ends_at
+            GROUP BY
+                ps.spot_type
+            ORDER BY
+                ps.spot_type ASC;",
+            starts_at,
+            ends_at,
+        )
+    }
+
+    pub fn get_revenue_by_payment_method(
+        &self,
+        starts_at: i64,
+        ends_at: i64,
+    ) -> Result<Vec<ClosingTotal<PaymentMethod>>, Error> {
+        self.get_revenue(
+            "
+            SELECT
+                payment_method,
+                COUNT(*),
+                COALESCE(SUM(total), 0)
+            FROM
+                receipt
+            WHERE
+                exit_time >= :starts_at AND exit_time < :ends_at
+            GROUP BY
+                payment_method
+            ORDER BY
+                payment_method ASC;",
+            starts_at,
+            ends_at,
+        )
+    }
+
+    fn get_revenue<T: FromSql>(
+        &self,
+        query: &str,
+        starts_at: i64,
+        ends_at: i64,
+    ) -> Result<Vec<ClosingTotal<T>>, Error> {
+        let mut stmt = self.connection.prepare(query)?;
+
+        let totals = stmt.query_map(
+            named_params! {
+                ":starts_at": starts_at,
+                ":ends_at": ends_at,
+            },
+            |row| {
+                Ok(ClosingTotal {
+                    key: row.get(0)?,
+                    vehicles: row.get(1)?,
+                    total: row.get(2)?,
+                })
+            },
+        )?;
+
+        totals.collect()
+    }
+
+    // Vehicles that entered before the instant and hadn't left yet
+    pub fn count_vehicles_inside(&self, at: i64) -> Result<i64, Error> {
+        self.connection.query_row(
+            "
+            SELECT
+                COUNT(*)
+            FROM
+                vehicle v
+            LEFT JOIN car_exit ce ON
+                ce.id = v.id
+            WHERE
+                v.entry_time < :at AND (ce.exit_time IS NULL OR ce.exit_time >= :at);",
+            named_params! {
+                ":at": at,
+            },
+            |row| row.get(0),
+        )
+    }
+
+    pub fn insert_cash_closing(&mut self, closing: &CashClosing) -> Result<i64, Error> {
+        let tx = self.connection.transaction()?;
+
+        let id = tx
+            .prepare(
+                "
+                INSERT INTO cash_closing(
+                    business_date, starts_at, ends_at, closed_at, vehicles, total, vehicles_inside
+                )
+                VALUES (
+                    :business_date, :starts_at, :ends_at, :closed_at, :vehicles, :total, :vehicles_inside
+                );",
+            )?
+            .insert(named_params! {
+                ":business_date": closing.business_date,
+                ":starts_at": closing.starts_at,
+                ":ends_at": closing.ends_at,
+                ":closed_at": closing.closed_at,
+                ":vehicles": closing.vehicles,
+                ":total": closing.total,
+                ":vehicles_inside": closing.vehicles_inside,
+            })?;
+
+        {
+            let mut stmt = tx.prepare(
+                "
+                INSERT INTO cash_closing_total(closing_id, category, key, vehicles, total)
+                VALUES (:closing_id, :category, :key, :vehicles, :total);",
+            )?;
+
+            let spot_types = closing
+                .spot_types
+                .iter()
+                .map(|total| (SPOT_TYPE_CATEGORY, total.key, total.vehicles, total.total));
+
+            let payment_methods = closing.payment_methods.iter().map(|total| {
+                (
+                    PAYMENT_METHOD_CATEGORY,
+                    total.key as i32,
+                    total.vehicles,
+                    total.total,
+                )
+            });
+
+            for (category, key, vehicles, total) in spot_types.chain(payment_methods) {
+                stmt.execute(named_params! {
+                    ":closing_id": id,
+                    ":category": category,
+                    ":key": key,
+                    ":vehicles": vehicles,
+                    ":total": total,
+                })?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(id)
+    }
+
+    pub fn is_business_date_closed(&self, business_date: &str) -> Result<bool, Error> {
+        self.connection.query_row(
+            "SELECT COUNT(*) > 0 FROM cash_closing WHERE business_date = :business_date;",
+            named_params! {
+                ":business_date": business_date,
+            },
+            |row| row.get(0),
+        )
+    }
+
+    // Whether the instant belongs to a day that was already closed
+    pub fn is_closed_at(&self, timestamp: i64) -> Result<bool, Error> {
+        self.connection.query_row(
+            "
+            SELECT COUNT(*) > 0 FROM cash_closing
+            WHERE starts_at <= :timestamp AND :timestamp < ends_at;",
+            named_params! {
+                ":timestamp": timestamp,
+            },
+            |row| row.get(0),
+        )
+    }
+
+    // Most recent first
+    pub fn get_cash_closings(&self) -> Result<Vec<CashClosing>, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                id,
+                business_date,
+                starts_at,
+                ends_at,
+                closed_at,
+                vehicles,
+                total,
+                vehicles_inside
+            FROM
+                cash_closing
+            ORDER BY
+                starts_at DESC;",
+        )?;
+
+        let closings = stmt.query_map([], |row| {
+            Ok(CashClosing {
+                id: row.get(0)?,
+                business_date: row.get(1)?,
+                starts_at: row.get(2)?,
+                ends_at: row.get(3)?,
+                closed_at: row.get(4)?,
+                vehicles: row.get(5)?,
+                total: row.get(6)?,
+                spot_types: Vec::new(),
+                payment_methods: Vec::new(),
+                vehicles_inside: row.get(7)?,
+            })
+        })?;
+
+        let mut closings = closings.collect::<Result<Vec<_>, _>>()?;
+
+        for closing in &mut closings {
+            closing.spot_types = self.get_closing_totals(closing.id, SPOT_TYPE_CATEGORY)?;
+            closing.payment_methods =
+                self.get_closing_totals(closing.id, PAYMENT_METHOD_CATEGORY)?;
+        }
+
+        Ok(closings)
+    }
+
+    fn get_closing_totals<T: FromSql>(
+        &self,
+        closing_id: i64,
+        category: i32,
+    ) -> Result<Vec<ClosingTotal<T>>, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                key,
+                vehicles,
+                total
+            FROM
+                cash_closing_total
+            WHERE
+                closing_id = :closing_id AND category = :category
+            ORDER BY
+                key ASC;",
+        )?;
+
+        let totals = stmt.query_map(
+            named_params! {
+                ":closing_id": closing_id,
+                ":category": category,
+            },
+            |row| {
+                Ok(ClosingTotal {
+                    key: row.get(0)?,
+                    vehicles: row.get(1)?,
+                    total: row.get(2)?,
+                })
+            },
+        )?;
+
+        totals.collect()
+    }
+}
@@ -0,0 +1,189 @@
+use super::Database;
+use crate::models::history::{HistoryResolution, OccupancySample, OccupancySnapshot};
+use rusqlite::{named_params, Error};
+
+impl Database {
+    pub(super) fn initialize_history_tables(&self) {
+        self.connection
+            .execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS occupancy_snapshot (
+                    taken_at BIGINT NOT NULL,
+                    resolution INTEGER NOT NULL CHECK (resolution IN (0, 1)),
+                    floor_number INTEGER NOT NULL,
+                    spot_type INTEGER NOT NULL,
+                    occupied REAL NOT NULL,
+                    capacity INTEGER NOT NULL,
+                    floor_closed BOOLEAN NOT NULL,
+                    lot_closed BOOLEAN NOT NULL,
+                    PRIMARY KEY (resolution, taken_at, floor_number, spot_type)
+                );",
+            )
+            .unwrap();
+    }
+
+    pub fn insert_occupancy_snapshot(
+        &mut self,
+        taken_at: i64,
+        samples: &[OccupancySample],
+    ) -> Result<(), Error> {
+        let tx = self.connection.transaction()?;
+
+        for sample in samples {
+            tx.execute(
+                "
+                INSERT OR REPLACE INTO occupancy_snapshot(
+                    taken_at, resolution, floor_number, spot_type, occupied, capacity, floor_closed, lot_closed
+                )
+                VALUES (:taken_at, :resolution, :floor_number, :spot_type, :occupied, :capacity, :floor_closed, :lot_closed);",
+                named_params! {
+                    ":taken_at": taken_at,
+                    ":resolution": HistoryResolution::Raw as i32,
+                    ":floor_number": sample.floor_number,
+                    ":spot_type": sample.spot_type,
+                    ":occupied": sample.occupied,
+                    ":capacity": sample.capacity,
+                    ":floor_closed": sample.floor_closed,
+                    ":lot_closed": sample.lot_closed,
+                },
+            )?;
+        }
+
+        tx.commit()
+    }
+
+    // Replaces the raw snapshots taken before `before` by their hourly averages.
+    // `before` must be the start of an hour, so no hour is ever split in two
+    pub fn downsample_occupancy(&mut self, before: i64) -> Result<usize, Error> {
+        let tx = self.connection.transaction()?;
+
+        tx.execute(
+            "
+            INSERT OR REPLACE INTO occupancy_snapshot(
+                taken_at, resolution, floor_number, spot_type, occupied, capacity, floor_closed, lot_closed
+            )
+            SELECT
+                (taken_at / 3600) * 3600,
+                :hourly,
+                floor_number,
+                spot_type,
+                AVG(occupied),
+                MAX(capacity),
+                MAX(floor_closed),
+                MAX(lot_closed)
+            FROM
+                occupancy_snapshot
+            WHERE
+                resolution = :raw AND taken_at < :before
+            GROUP BY
+                taken_at / 3600, floor_number, spot_type;",
+            named_params! {
+                ":raw": HistoryResolution::Raw as i32,
+                ":hourly": HistoryResolution::Hourly as i32,
+                ":before": before,
+            },
+        )?;
+
+        let removed = tx.execute(
+            "DELETE FROM occupancy_snapshot WHERE resolution = :raw AND taken_at < :before;",
+            named_params! {
+                ":raw": HistoryResolution::Raw as i32,
+                ":before": before,
+            },
+        )?;
+
+        tx.commit()?;
+
+        Ok(removed)
+    }
+
+    // Deletes up to `batch_size` snapshots of the resolution taken before `before`.
+    // Returns how many were deleted
+    pub fn prune_occupancy(
+        &mut self,
+        resolution: HistoryResolution,
+        before: i64,
+        batch_size: i64,
+    ) -> Result<usize, Error> {
+        self.connection.execute(
+            "
+            DELETE FROM occupancy_snapshot
+            WHERE rowid IN (
+                SELECT rowid FROM occupancy_snapshot
+                WHERE resolution = :resolution AND taken_at < :before
+                ORDER BY taken_at ASC
+                LIMIT :batch_size
+            );",
+            named_params! {
+                ":resolution": resolution as i32,
+                ":before": before,
+                ":batch_size": batch_size,
+            },
+        )
+    }
+
+    // Snapshots in the [from, to] window. Without a resolution the hourly snapshots
+    // are only used for the period that has no raw snapshots anymore
+    pub fn get_occupancy_snapshots(
+        &self,
+        from: i64,
+        to: i64,
+        resolution: Option<HistoryResolution>,
+    ) -> Result<Vec<OccupancySnapshot>, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                taken_at,
+                resolution,
+                floor_number,
+                spot_type,
+                occupied,
+                capacity,
+                floor_closed,
+                lot_closed
+            FROM
+                occupancy_snapshot
+            WHERE
+                taken_at BETWEEN :from AND :to
+                AND (
+                    resolution = :resolution
+                    OR (
+                        :resolution IS NULL
+                        AND (
+                            resolution = :raw
+                            OR taken_at < (
+                                SELECT COALESCE(MIN(taken_at), :to + 1)
+                                FROM occupancy_snapshot
+                                WHERE resolution = :raw
+                            )
+                        )
+                    )
+                )
+            ORDER BY
+                taken_at ASC, floor_number ASC, spot_type ASC;",
+        )?;
+
+        let snapshots = stmt.query_map(
+            named_params! {
+                ":from": from,
+                ":to": to,
+                ":resolution": resolution.map(|resolution| resolution as i32),
+                ":raw": HistoryResolution::Raw as i32,
+            },
+            |row| {
+                Ok(OccupancySnapshot {
+                    taken_at: row.get(0)?,
+                    resolution: row.get(1)?,
+                    floor_number: row.get(2)?,
+                    spot_type: row.get(3)?,
+                    occupied: row.get(4)?,
+                    capacity: row.get(5)?,
+                    floor_closed: row.get(6)?,
+                    lot_closed: row.get(7)?,
+                })
+            },
+        )?;
+
+        snapshots.collect()
+    }
+}
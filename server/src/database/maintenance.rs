@@ -0,0 +1,155 @@
+use super::Database;
+use crate::models::maintenance::SpotMaintenance;
+use rusqlite::{named_params, Error, Row};
+
+impl Database {
+    pub(super) fn initialize_maintenance_tables(&self) {
+        self.connection
+            .execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS spot_maintenance (
+                    id INTEGER NOT NULL PRIMARY KEY,
+                    floor_number INTEGER NOT NULL,
+                    spot_number INTEGER NOT NULL,
+                    reason TEXT NOT NULL,
+                    starts_at BIGINT NOT NULL,
+                    ends_at BIGINT,
+                    created_at BIGINT NOT NULL,
+                    FOREIGN KEY (floor_number, spot_number) REFERENCES parking_spot(floor_number, spot_number)
+                );
+
+                CREATE INDEX IF NOT EXISTS spot_maintenance_spot
+                    ON spot_maintenance(floor_number, spot_number, starts_at);",
+            )
+            .unwrap();
+    }
+
+    pub fn add_spot_maintenance(
+        &mut self,
+        floor_number: i32,
+        spot_number: i32,
+        reason: &str,
+        starts_at: i64,
+        ends_at: Option<i64>,
+        now: i64,
+    ) -> Result<SpotMaintenance, Error> {
+        self.connection.execute(
+            "
+            INSERT INTO spot_maintenance(floor_number, spot_number, reason, starts_at, ends_at, created_at)
+            VALUES (:floor_number, :spot_number, :reason, :starts_at, :ends_at, :now);",
+            named_params! {
+                ":floor_number": floor_number,
+                ":spot_number": spot_number,
+                ":reason": reason,
+                ":starts_at": starts_at,
+                ":ends_at": ends_at,
+                ":now": now,
+            },
+        )?;
+
+        Ok(SpotMaintenance {
+            id: self.connection.last_insert_rowid(),
+            floor_number,
+            spot_number,
+            reason: reason.to_string(),
+            starts_at,
+            ends_at,
+        })
+    }
+
+    // Returns the spot to service: the current maintenance ends now and the
+    // scheduled ones are dropped. Returns how many were affected
+    pub fn end_spot_maintenance(
+        &mut self,
+        floor_number: i32,
+        spot_number: i32,
+        now: i64,
+    ) -> Result<usize, Error> {
+        let tx = self.connection.transaction()?;
+
+        let ended = tx.execute(
+            "
+            UPDATE spot_maintenance
+            SET ends_at = :now
+            WHERE floor_number = :floor_number AND spot_number = :spot_number
+                AND starts_at <= :now AND (ends_at IS NULL OR ends_at > :now);",
+            named_params! {
+                ":floor_number": floor_number,
+                ":spot_number": spot_number,
+                ":now": now,
+            },
+        )?;
+
+        let dropped = tx.execute(
+            "
+            DELETE FROM spot_maintenance
+            WHERE floor_number = :floor_number AND spot_number = :spot_number AND starts_at > :now;",
+            named_params! {
+                ":floor_number": floor_number,
+                ":spot_number": spot_number,
+                ":now": now,
+            },
+        )?;
+
+        tx.commit()?;
+
+        Ok(ended + dropped)
+    }
+
+    // Current and scheduled maintenances
+    pub fn get_spot_maintenances(&self, now: i64) -> Result<Vec<SpotMaintenance>, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                id,
+                reason,
+                starts_at,
+                ends_at,
+                floor_number,
+                spot_number
+            FROM
+                spot_maintenance
+            WHERE
+                ends_at IS NULL OR ends_at > :now
+            ORDER BY
+                floor_number ASC, spot_number ASC, starts_at ASC;",
+        )?;
+
+        let maintenances = stmt.query_map(
+            named_params! {
+                ":now": now,
+            },
+            |row| {
+                let floor_number = row.get(4)?;
+                let spot_number = row.get(5)?;
+
+                Ok(maintenance_from_row(row, 0, floor_number, spot_number)?.unwrap())
+            },
+        )?;
+
+        maintenances.collect()
+    }
+}
+
+// Reads id, reason, starts_at and ends_at starting at the given column,
+// None when the spot has no maintenance (LEFT JOIN)
+pub(super) fn maintenance_from_row(
+    row: &Row,
+    first_column: usize,
+    floor_number: i32,
+    spot_number: i32,
+) -> Result<Option<SpotMaintenance>, Error> {
+    let id: Option<i64> = row.get(first_column)?;
+
+    match id {
+        Some(id) => Ok(Some(SpotMaintenance {
+            id,
+            floor_number,
+            spot_number,
+            reason: row.get(first_column + 1)?,
+            starts_at: row.get(first_column + 2)?,
+            ends_at: row.get(first_column + 3)?,
+        })),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,930 @@
+mod analytics;
+mod closings;
+mod history;
+mod maintenance;
+mod receipts;
+mod retention;
+mod sensor_reports;
+mod subscribers;
+mod webhooks;
+
+use crate::events::EventBus;
+use crate::models::{
+    barrier::BarrierFault,
+    client::ClientId,
+    clock::{ClockOffset, EventTime},
+    input::ControllerInputs,
+    parking_lot::{Floor, Overstay, Spot, SpotType, Vehicle},
+    receipt::{Charge, Receipt, VehicleExit},
+    subscriber::Subscription,
+};
+use crate::socket::payloads::{
+    BarrierFaultPayload, FloorDataPayload, ForecastPayload, LotOccupancyPayload, OverstayPayload,
+    ParkingLotDataPayload, SpotDataPayload, SpotMaintenancePayload, SubscriptionPayload,
+    VehicleDataPayload,
+};
+use chrono::Utc;
+use maintenance::maintenance_from_row;
+use rusqlite::{named_params, Connection, Error, Row};
+use std::{
+    collections::HashMap,
+    fs,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+use subscribers::subscription_from_row;
+use tracing::info;
+
+// How far back the exits sent with the parking lot state go
+const EXITED_VEHICLES_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+pub struct Database {
+    connection: Connection,
+    pub clients: HashMap<String, ClientId>,
+    pub clocks: HashMap<ClientId, ClockOffset>,
+    pub inputs: HashMap<ClientId, ControllerInputs>,
+    // By barrier name, only the ground floor has them
+    pub barrier_faults: HashMap<String, BarrierFault>,
+    // Refreshed by forecast::spawn, the broadcasts only send the last one
+    pub forecast: ForecastPayload,
+    pub events: EventBus,
+}
+
+impl Database {
+    pub fn new() -> Arc<Mutex<Self>> {
+        // create db folder if it doesn't exist
+        fs::create_dir_all("./db").unwrap();
+
+        let connection = Connection::open("./db/parking_lot.db").unwrap();
+
+        Arc::new(Mutex::new(Self::with_connection(connection)))
+    }
+
+    // For the background tasks: a handler that panicked with the lock doesn't stop
+    // them, the transactions leave the database itself consistent
+    pub fn lock(database: &Mutex<Database>) -> MutexGuard<'_, Database> {
+        database.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Nothing is kept after the test
+    #[cfg(test)]
+    pub fn in_memory() -> Arc<Mutex<Self>> {
+        let connection = Connection::open_in_memory().unwrap();
+
+        Arc::new(Mutex::new(Self::with_connection(connection)))
+    }
+
+    fn with_connection(connection: Connection) -> Self {
+        let instance = Self {
+            connection,
+            clients: HashMap::with_capacity(3),
+            clocks: HashMap::with_capacity(3),
+            inputs: HashMap::with_capacity(3),
+            barrier_faults: HashMap::with_capacity(2),
+            forecast: ForecastPayload::default(),
+            events: EventBus::new(),
+        };
+
+        instance.initialize_database_state();
+        instance.migrate_vehicle_columns();
+        instance.initialize_webhook_tables();
+        instance.initialize_history_tables();
+        instance.initialize_maintenance_tables();
+        instance.initialize_subscriber_tables();
+        instance.initialize_receipt_tables();
+        instance.initialize_closing_tables();
+        instance.initialize_retention_tables();
+        instance.initialize_sensor_report_tables();
+
+        instance
+    }
+
+    fn initialize_database_state(&self) {
+        // Check if the tables already exist and if they do, return early
+        let tables_count: i32 = self
+            .connection
+            .query_row(
+                "
+                SELECT COUNT(*) 
+                FROM sqlite_master 
+                WHERE type='table' AND name IN ('parking_lot', 'vehicle', 'parking_floor', 'parking_spot', 'car_exit');",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+
+        if tables_count == 5 {
+            return;
+        }
+
+        // Create the tables and insert the initial data
+        self.connection
+            .execute_batch(
+                "
+                CREATE TABLE parking_lot (
+                    is_closed BOOLEAN NOT NULL
+                );
+
+                CREATE TABLE vehicle (
+                    id INTEGER NOT NULL PRIMARY KEY,
+                    entry_time BIGINT NOT NULL
+                );
+
+                CREATE TABLE parking_floor (
+                    floor_number INTEGER NOT NULL PRIMARY KEY CHECK (floor_number IN (0, 1, 2)),
+                    is_closed BOOLEAN NOT NULL
+                );
+                
+                CREATE TABLE parking_spot (
+                    floor_number INTEGER NOT NULL,
+                    spot_number INTEGER NOT NULL CHECK (spot_number IN (0, 1, 2, 3, 4, 5, 6, 7)),
+                    spot_type INTEGER NOT NULL CHECK (spot_type IN (0, 1, 2)),
+                    parked_vehicle_id INTEGER UNIQUE,
+                    PRIMARY KEY (floor_number, spot_number),
+                    FOREIGN KEY (parked_vehicle_id) REFERENCES vehicle(id),
+                    FOREIGN KEY (floor_number) REFERENCES parking_floor(floor_number)
+                );
+
+                CREATE TABLE car_exit (
+                    id INTEGER NOT NULL PRIMARY KEY,
+                    exit_time BIGINT NOT NULL,
+                    FOREIGN KEY (id) REFERENCES vehicle(id)
+                );
+
+                -- Insert the initial state of the parking lot
+                INSERT INTO parking_lot(is_closed) VALUES (0);
+
+
+                -- Insert the initial state of the parking floors
+                INSERT INTO parking_floor(floor_number, is_closed) VALUES
+                    (0, 0),
+                    (1, 0),
+                    (2, 0);
+                
+                -- Insert 8 parking spots for the first floor
+                INSERT INTO parking_spot(floor_number, spot_number, spot_type) VALUES
+                    (0, 0, 1),
+                    (0, 1, 2),
+                    (0, 2, 2),
+                    (0, 3, 0),
+                    (0, 4, 0),
+                    (0, 5, 0),
+                    (0, 6, 0),
+                    (0, 7, 0);
+                                
+                -- Insert 8 parking spots for the second floor
+                INSERT INTO parking_spot(floor_number, spot_number, spot_type) VALUES
+                    (1, 0, 1),
+                    (1, 1, 2),
+                    (1, 2, 2),
+                    (1, 3, 0),
+                    (1, 4, 0),
+                    (1, 5, 0),
+                    (1, 6, 0),
+                    (1, 7, 0);
+                
+                -- Insert 8 parking spots for the third floor
+                INSERT INTO parking_spot(floor_number, spot_number, spot_type) VALUES
+                    (2, 0, 1),
+                    (2, 1, 2),
+                    (2, 2, 2),
+                    (2, 3, 0),
+                    (2, 4, 0),
+                    (2, 5, 0),
+                    (2, 6, 0),
+                    (2, 7, 0);",
+            )
+            .unwrap();
+
+        info!("database initialized");
+    }
+
+    // Columns added after the first release, existing databases are migrated in place
+    fn migrate_vehicle_columns(&self) {
+        self.add_column_if_missing("vehicle", "entry_received_at", "BIGINT");
+        self.add_column_if_missing(
+            "vehicle",
+            "entry_clock_skewed",
+            "BOOLEAN NOT NULL DEFAULT 0",
+        );
+        self.add_column_if_missing("car_exit", "exit_received_at", "BIGINT");
+        self.add_column_if_missing(
+            "car_exit",
+            "exit_clock_skewed",
+            "BOOLEAN NOT NULL DEFAULT 0",
+        );
+
+        // Where the vehicle parked, kept after it leaves for the analytics
+        self.add_column_if_missing("vehicle", "floor_number", "INTEGER");
+        self.add_column_if_missing("vehicle", "spot_number", "INTEGER");
+
+        // Overstay rule the vehicle broke, set when the overstay is detected
+        self.add_column_if_missing("vehicle", "overstay_max_minutes", "INTEGER");
+        self.add_column_if_missing("vehicle", "overstay_surcharge_per_minute", "REAL");
+
+        // Contract of the subscriber the vehicle belongs to
+        self.add_column_if_missing("vehicle", "subscriber_id", "INTEGER");
+        self.add_column_if_missing("vehicle", "subscriber_rate_per_minute", "REAL");
+    }
+
+    fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) {
+        let exists: bool = self
+            .connection
+            .query_row(
+                &format!(
+                    "SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = :column;",
+                    table
+                ),
+                named_params! {
+                    ":column": column,
+                },
+                |row| row.get(0),
+            )
+            .unwrap();
+
+        if !exists {
+            self.connection
+                .execute_batch(&format!(
+                    "ALTER TABLE {} ADD COLUMN {} {};",
+                    table, column, definition
+                ))
+                .unwrap();
+
+            info!(table, column, "database column added");
+        }
+    }
+
+    pub fn get_floor(&self, floor_number: i32) -> Result<Floor, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                ps.spot_number,
+                ps.spot_type,
+                v.id as vehicle_id,
+                v.entry_time as vehicle_entry_type,
+                v.entry_clock_skewed,
+                v.overstay_max_minutes,
+                v.overstay_surcharge_per_minute,
+                v.subscriber_id,
+                v.subscriber_rate_per_minute,
+                sm.id,
+                sm.reason,
+                sm.starts_at,
+                sm.ends_at
+            from
+                parking_spot ps
+            LEFT JOIN vehicle v ON
+                ps.parked_vehicle_id = v.id
+            LEFT JOIN spot_maintenance sm ON
+                sm.id = (
+                    SELECT id FROM spot_maintenance
+                    WHERE floor_number = ps.floor_number AND spot_number = ps.spot_number
+                        AND starts_at <= :now AND (ends_at IS NULL OR ends_at > :now)
+                    ORDER BY starts_at DESC
+                    LIMIT 1
+                )
+            WHERE
+                ps.floor_number = :floor_number
+            ORDER BY
+                ps.spot_number ASC;",
+        )?;
+
+        let spots = stmt.query_map(
+            named_params! {
+                ":floor_number": floor_number,
+                ":now": Utc::now().timestamp(),
+            },
+            |row| {
+                let spot_number: i32 = row.get(0)?;
+                let spot_type: SpotType = row.get(1)?;
+                let parked_vehicle_id: Option<i32> = row.get(2)?;
+                let parked_vehicle_entry_time: Option<i64> = row.get(3)?;
+                let parked_vehicle_clock_skewed: Option<bool> = row.get(4)?;
+                let overstay = overstay_from_row(row, 5)?;
+                let subscription = subscription_from_row(row, 7)?;
+
+                let mut spot = Spot {
+                    spot_number,
+                    spot_type,
+                    parked_vehicle: None,
+                    out_of_service: maintenance_from_row(row, 9, floor_number, spot_number)?,
+                };
+
+                if let (Some(parked_vehicle_id), Some(parked_vehicle_entry_time)) =
+                    (parked_vehicle_id, parked_vehicle_entry_time)
+                {
+                    spot.parked_vehicle = Some(Vehicle {
+                        id: parked_vehicle_id,
+                        entry_time: parked_vehicle_entry_time,
+                        clock_skewed: parked_vehicle_clock_skewed.unwrap_or(false),
+                        overstay,
+                        subscription,
+                    });
+                }
+
+                Ok(spot)
+            },
+        )?;
+
+        let mut floor = Floor {
+            floor_number,
+            spots: Vec::with_capacity(8),
+        };
+
+        for spot in spots {
+            floor.spots.push(spot?);
+        }
+
+        Ok(floor)
+    }
+
+    fn get_spot(&self, floor_number: i32, spot_number: i32) -> Result<Spot, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                ps.spot_type,
+                v.id as vehicle_id,
+                v.entry_time as vehicle_entry_time,
+                v.entry_clock_skewed,
+                v.overstay_max_minutes,
+                v.overstay_surcharge_per_minute,
+                v.subscriber_id,
+                v.subscriber_rate_per_minute,
+                sm.id,
+                sm.reason,
+                sm.starts_at,
+                sm.ends_at
+            FROM
+                parking_spot ps
+            LEFT JOIN vehicle v ON
+                ps.parked_vehicle_id = v.id
+            LEFT JOIN spot_maintenance sm ON
+                sm.id = (
+                    SELECT id FROM spot_maintenance
+                    WHERE floor_number = ps.floor_number AND spot_number = ps.spot_number
+                        AND starts_at <= :now AND (ends_at IS NULL OR ends_at > :now)
+                    ORDER BY starts_at DESC
+                    LIMIT 1
+                )
+            WHERE
+                ps.floor_number = :floor_number AND ps.spot_number = :spot_number;",
+        )?;
+
+        stmt.query_row(
+            named_params! {
+                ":floor_number": floor_number,
+                ":spot_number": spot_number,
+                ":now": Utc::now().timestamp(),
+            },
+            |row| {
+                let spot_type: SpotType = row.get(0)?;
+                let parked_vehicle_id: Option<i32> = row.get(1)?;
+                let parked_vehicle_entry_time: Option<i64> = row.get(2)?;
+                let parked_vehicle_clock_skewed: Option<bool> = row.get(3)?;
+                let overstay = overstay_from_row(row, 4)?;
+                let subscription = subscription_from_row(row, 6)?;
+
+                let mut spot = Spot {
+                    spot_number,
+                    spot_type,
+                    parked_vehicle: None,
+                    out_of_service: maintenance_from_row(row, 8, floor_number, spot_number)?,
+                };
+
+                if let (Some(parked_vehicle_id), Some(parked_vehicle_entry_time)) =
+                    (parked_vehicle_id, parked_vehicle_entry_time)
+                {
+                    spot.parked_vehicle = Some(Vehicle {
+                        id: parked_vehicle_id,
+                        entry_time: parked_vehicle_entry_time,
+                        clock_skewed: parked_vehicle_clock_skewed.unwrap_or(false),
+                        overstay,
+                        subscription,
+                    });
+                }
+
+                Ok(spot)
+            },
+        )
+    }
+
+    pub fn park_vehicle(
+        &mut self,
+        entry: EventTime,
+        floor_number: i32,
+        spot_number: i32,
+        subscription: Option<Subscription>,
+    ) -> Result<(), Error> {
+        let tx = self.connection.transaction()?;
+
+        let car_id = tx
+            .prepare(
+                "
+                INSERT INTO vehicle(
+                    id, entry_time, entry_received_at, entry_clock_skewed, floor_number, spot_number,
+                    subscriber_id, subscriber_rate_per_minute
+                )
+                VALUES (
+                    -- The id is the ticket printed on the receipts, which outlive the
+                    -- vehicles, so it is never handed out again
+                    COALESCE(
+                        (SELECT MAX(id) FROM (
+                            SELECT MAX(id) AS id FROM vehicle
+                            UNION ALL
+                            SELECT MAX(vehicle_id) FROM receipt
+                        )),
+                        0
+                    ) + 1,
+                    :entry_time, :entry_received_at, :entry_clock_skewed, :floor_number, :spot_number,
+                    :subscriber_id, :subscriber_rate_per_minute
+                );",
+            )?
+            .insert(named_params! {
+                ":entry_time": entry.timestamp,
+                ":entry_received_at": entry.received_at,
+                ":entry_clock_skewed": entry.clock_skewed,
+                ":floor_number": floor_number,
+                ":spot_number": spot_number,
+                ":subscriber_id": subscription.map(|subscription| subscription.subscriber_id),
+                ":subscriber_rate_per_minute": subscription.map(|subscription| subscription.rate_per_minute),
+            })?;
+
+        tx.prepare(
+            "
+            UPDATE 
+                parking_spot SET parked_vehicle_id = :car_id 
+            WHERE 
+                floor_number = :floor_number AND spot_number = :spot_number;",
+        )?
+        .execute(named_params! {
+            ":car_id": car_id,
+            ":floor_number": floor_number,
+            ":spot_number": spot_number,
+        })?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    // A controller sends an event again when the acknowledgement got lost, with
+    // the same timestamp. Either one of the times is taken, the stored one may
+    // have been corrected by the clock offset
+    pub fn is_parked_since(
+        &self,
+        floor_number: i32,
+        spot_number: i32,
+        entry_times: [i64; 2],
+    ) -> Result<bool, Error> {
+        let vehicle = self.get_spot(floor_number, spot_number)?.parked_vehicle;
+
+        Ok(vehicle.is_some_and(|vehicle| entry_times.contains(&vehicle.entry_time)))
+    }
+
+    pub fn has_left_at(
+        &self,
+        floor_number: i32,
+        spot_number: i32,
+        exit_times: [i64; 2],
+    ) -> Result<bool, Error> {
+        self.connection.query_row(
+            "
+            SELECT EXISTS (
+                SELECT 1
+                FROM
+                    car_exit ce
+                INNER JOIN vehicle v ON
+                    v.id = ce.id
+                WHERE
+                    v.floor_number = :floor_number AND v.spot_number = :spot_number
+                    AND ce.exit_time IN (:exit_time, :reported_time)
+            );",
+            named_params! {
+                ":floor_number": floor_number,
+                ":spot_number": spot_number,
+                ":exit_time": exit_times[0],
+                ":reported_time": exit_times[1],
+            },
+            |row| row.get(0),
+        )
+    }
+
+    // Frees the spot and issues the receipt of the stay, priced by `charge`, in
+    // the same transaction
+    pub fn unpark_vehicle(
+        &mut self,
+        floor_number: i32,
+        spot_number: i32,
+        exit: EventTime,
+        issued_at: i64,
+        charge: impl FnOnce(&VehicleExit) -> Charge,
+    ) -> Result<Receipt, Error> {
+        let vehicle = self.get_spot(floor_number, spot_number)?.parked_vehicle;
+
+        if let Some(vehicle) = vehicle {
+            let tx = self.connection.transaction()?;
+
+            tx.prepare(
+                "
+                UPDATE 
+                    parking_spot SET parked_vehicle_id = NULL 
+                WHERE 
+                    floor_number = :floor_number AND spot_number = :spot_number;",
+            )?
+            .execute(named_params! {
+                ":floor_number": floor_number,
+                ":spot_number": spot_number,
+            })?;
+
+            // A skewed clock must never produce a negative stay, in that case the
+            // exit is recorded at the entry time and flagged for review
+            let exit_clock_skewed = exit.clock_skewed || exit.timestamp < vehicle.entry_time;
+            let exit_time = exit.timestamp.max(vehicle.entry_time);
+
+            tx.prepare(
+                "
+                INSERT INTO car_exit(id, exit_time, exit_received_at, exit_clock_skewed)
+                VALUES (:vehicle_id, :exit_time, :exit_received_at, :exit_clock_skewed);",
+            )?
+            .execute(named_params! {
+                ":vehicle_id": vehicle.id,
+                ":exit_time": exit_time,
+                ":exit_received_at": exit.received_at,
+                ":exit_clock_skewed": exit_clock_skewed,
+            })?;
+
+            let vehicle_exit =
+                receipts::get_vehicle_exit(&tx, vehicle.id, floor_number, spot_number)?;
+            let charge = charge(&vehicle_exit);
+            let receipt = receipts::insert_receipt(&tx, &vehicle_exit, charge, issued_at)?;
+
+            tx.commit()?;
+
+            Ok(receipt)
+        } else {
+            Err(Error::QueryReturnedNoRows)
+        }
+    }
+
+    pub fn parking_lot_is_full(&self) -> Result<bool, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                COUNT(*)
+            FROM
+                parking_spot ps
+            WHERE
+                ps.parked_vehicle_id IS NULL
+                AND NOT EXISTS (
+                    SELECT 1 FROM spot_maintenance sm
+                    WHERE sm.floor_number = ps.floor_number AND sm.spot_number = ps.spot_number
+                        AND sm.starts_at <= :now AND (sm.ends_at IS NULL OR sm.ends_at > :now)
+                );",
+        )?;
+
+        // Full when there is no free spot left in service
+        let free_spots: i32 = stmt.query_row(
+            named_params! {
+                ":now": Utc::now().timestamp(),
+            },
+            |row| row.get(0),
+        )?;
+
+        Ok(free_spots == 0)
+    }
+
+    pub fn floor_is_full(&self, floor_number: i32) -> Result<bool, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                COUNT(*)
+            FROM
+                parking_spot ps
+            WHERE
+                ps.floor_number = :floor_number
+                AND ps.parked_vehicle_id IS NULL
+                AND NOT EXISTS (
+                    SELECT 1 FROM spot_maintenance sm
+                    WHERE sm.floor_number = ps.floor_number AND sm.spot_number = ps.spot_number
+                        AND sm.starts_at <= :now AND (sm.ends_at IS NULL OR sm.ends_at > :now)
+                );",
+        )?;
+
+        let free_spots: i32 = stmt.query_row(
+            named_params! {
+                ":floor_number": floor_number,
+                ":now": Utc::now().timestamp(),
+            },
+            |row| row.get(0),
+        )?;
+
+        Ok(free_spots == 0)
+    }
+
+    pub fn close_parking_lot(&mut self) -> Result<(), Error> {
+        self.connection.execute(
+            "
+            UPDATE 
+                parking_lot 
+            SET 
+                is_closed = 1;",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn close_floor(&mut self, floor_number: i32) -> Result<(), Error> {
+        self.connection.execute(
+            "
+            UPDATE 
+                parking_floor 
+            SET 
+                is_closed = 1 
+            WHERE 
+                floor_number = :floor_number;",
+            named_params! {
+                ":floor_number": floor_number,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn open_parking_lot(&mut self) -> Result<(), Error> {
+        self.connection.execute(
+            "
+            UPDATE 
+                parking_lot 
+            SET 
+                is_closed = 0;",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn open_floor(&mut self, floor_number: i32) -> Result<(), Error> {
+        self.connection.execute(
+            "
+            UPDATE 
+                parking_floor 
+            SET 
+                is_closed = 0 
+            WHERE 
+                floor_number = :floor_number;",
+            named_params! {
+                ":floor_number": floor_number,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn is_floor_closed(&self, floor_number: i32) -> Result<bool, Error> {
+        let is_closed: i32 = self.connection.query_row(
+            "
+            SELECT 
+                is_closed 
+            FROM 
+                parking_floor 
+            WHERE 
+                floor_number = :floor_number;",
+            named_params! {
+                ":floor_number": floor_number,
+            },
+            |row| row.get(0),
+        )?;
+
+        Ok(is_closed == 1)
+    }
+
+    pub fn is_parking_lot_closed(&self) -> Result<bool, Error> {
+        let is_closed: i32 =
+            self.connection
+                .query_row("SELECT is_closed FROM parking_lot;", [], |row| row.get(0))?;
+
+        Ok(is_closed == 1)
+    }
+
+    // Records the overstay rule the parked vehicle broke, so it is reported only once
+    pub fn mark_vehicle_overstayed(
+        &mut self,
+        vehicle_id: i32,
+        overstay: &Overstay,
+    ) -> Result<(), Error> {
+        self.connection.execute(
+            "
+            UPDATE vehicle
+            SET overstay_max_minutes = :max_minutes, overstay_surcharge_per_minute = :surcharge_per_minute
+            WHERE id = :vehicle_id;",
+            named_params! {
+                ":vehicle_id": vehicle_id,
+                ":max_minutes": overstay.max_minutes,
+                ":surcharge_per_minute": overstay.surcharge_per_minute,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    // Only the exits since `since`, the state is sent on every change and the
+    // older ones are kept in the daily summaries
+    pub fn get_exited_vehicles(&self, since: i64) -> Result<Vec<VehicleDataPayload>, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                v.id,
+                v.entry_time,
+                ce.exit_time,
+                COALESCE(v.entry_clock_skewed, 0) OR COALESCE(ce.exit_clock_skewed, 0),
+                v.overstay_max_minutes,
+                v.overstay_surcharge_per_minute,
+                v.subscriber_id,
+                v.subscriber_rate_per_minute,
+                r.number,
+                r.total
+            FROM
+                vehicle v
+            INNER JOIN car_exit ce ON
+                v.id = ce.id
+            LEFT JOIN receipt r ON
+                r.vehicle_id = v.id
+            WHERE
+                ce.exit_time >= :since
+            ORDER BY
+                ce.exit_time DESC;",
+        )?;
+
+        let vehicles = stmt.query_map(
+            named_params! {
+                ":since": since,
+            },
+            |row| {
+                let id: i32 = row.get(0)?;
+                let entry_time: i64 = row.get(1)?;
+                let exit_time: i64 = row.get(2)?;
+                let clock_skewed: bool = row.get(3)?;
+                let overstay = overstay_from_row(row, 4)?;
+                let subscription = subscription_from_row(row, 6)?;
+
+                Ok(VehicleDataPayload {
+                    id,
+                    entry_time,
+                    exit_time: Some(exit_time),
+                    clock_skewed,
+                    overstay: overstay.map(OverstayPayload::from),
+                    subscription: subscription.map(SubscriptionPayload::from),
+                    receipt_number: row.get(8)?,
+                    total: row.get(9)?,
+                })
+            },
+        )?;
+
+        let mut exited_vehicles = Vec::new();
+
+        for vehicle in vehicles {
+            exited_vehicles.push(vehicle?);
+        }
+
+        Ok(exited_vehicles)
+    }
+
+    // Entry first, then exit
+    fn get_barrier_faults(&self) -> Vec<BarrierFaultPayload> {
+        let mut faults: Vec<BarrierFaultPayload> = self
+            .barrier_faults
+            .values()
+            .map(|fault| BarrierFaultPayload {
+                barrier: fault.barrier.clone(),
+                fault: fault.fault.clone(),
+                timestamp: fault.since,
+            })
+            .collect();
+
+        faults.sort_by(|a, b| a.barrier.cmp(&b.barrier));
+        faults
+    }
+
+    pub fn get_parking_lot_state(&self) -> Result<ParkingLotDataPayload, Error> {
+        let mut data = ParkingLotDataPayload {
+            floors: Vec::with_capacity(3),
+            exited_vehicles: self
+                .get_exited_vehicles(Utc::now().timestamp() - EXITED_VEHICLES_WINDOW_SECS)?,
+            is_closed: self.is_parking_lot_closed()?,
+            forecast: self.forecast.clone(),
+            barrier_faults: self.get_barrier_faults(),
+        };
+
+        for floor_number in 0..3 {
+            let mut floor_data = FloorDataPayload {
+                is_closed: self.is_floor_closed(floor_number)?,
+                spots: Vec::with_capacity(8),
+            };
+
+            let floor = self.get_floor(floor_number)?;
+
+            for spot in floor.spots {
+                let spot_data = SpotDataPayload {
+                    spot_type: spot.spot_type as i32,
+                    out_of_service: spot.out_of_service.map(SpotMaintenancePayload::from),
+                    parked_vehicle: spot.parked_vehicle.map(|vehicle| VehicleDataPayload {
+                        id: vehicle.id,
+                        entry_time: vehicle.entry_time,
+                        exit_time: None,
+                        clock_skewed: vehicle.clock_skewed,
+                        overstay: vehicle.overstay.map(OverstayPayload::from),
+                        subscription: vehicle.subscription.map(SubscriptionPayload::from),
+                        receipt_number: None,
+                        total: None,
+                    }),
+                };
+
+                floor_data.spots.push(spot_data);
+            }
+
+            data.floors.push(floor_data);
+        }
+
+        Ok(data)
+    }
+
+    pub fn get_lot_occupancy(&self) -> Result<LotOccupancyPayload, Error> {
+        let mut occupancy = LotOccupancyPayload {
+            occupied: 0,
+            capacity: 0,
+            is_closed: self.is_parking_lot_closed()?,
+        };
+
+        for floor_number in 0..3 {
+            let floor = self.get_floor(floor_number)?;
+
+            occupancy.occupied += floor.occupied() as i32;
+            occupancy.capacity += floor.capacity() as i32;
+        }
+
+        Ok(occupancy)
+    }
+
+    // Empties the lot. The receipts are fiscal records and the daily summaries the
+    // history, so both are kept and the vehicles go into the summaries first
+    pub fn reset_parking_lot(&mut self) -> Result<(), Error> {
+        self.summarize_arrivals(i64::MAX, i64::MAX)?;
+        self.summarize_departures(i64::MAX, i64::MAX)?;
+
+        let tx = self.connection.transaction()?;
+
+        tx.execute("UPDATE parking_spot SET parked_vehicle_id = NULL;", [])?;
+        tx.execute("DELETE FROM car_exit;", [])?;
+        tx.execute("DELETE FROM vehicle;", [])?;
+        tx.execute("UPDATE parking_lot SET is_closed = 0;", [])?;
+        tx.execute("UPDATE parking_floor SET is_closed = 0;", [])?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+// Reads overstay_max_minutes and overstay_surcharge_per_minute starting at the given column
+fn overstay_from_row(row: &Row, first_column: usize) -> Result<Option<Overstay>, Error> {
+    let max_minutes: Option<i64> = row.get(first_column)?;
+    let surcharge_per_minute: Option<f64> = row.get(first_column + 1)?;
+
+    Ok(max_minutes.map(|max_minutes| Overstay {
+        max_minutes,
+        surcharge_per_minute: surcharge_per_minute.unwrap_or(0.0),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receipts;
+
+    fn at(timestamp: i64) -> EventTime {
+        EventTime {
+            timestamp,
+            received_at: timestamp,
+            clock_skewed: false,
+        }
+    }
+
+    #[test]
+    fn recognizes_the_events_sent_again() {
+        let database = Database::in_memory();
+        let mut database = database.lock().unwrap();
+        let now = Utc::now().timestamp();
+
+        database.park_vehicle(at(now - 3600), 1, 2, None).unwrap();
+
+        assert!(database.is_parked_since(1, 2, [now - 3600, now]).unwrap());
+        assert!(!database.is_parked_since(1, 2, [now - 60, now]).unwrap());
+        assert!(!database.is_parked_since(1, 3, [now - 3600, now]).unwrap());
+
+        receipts::depart(&mut database, 1, 2, at(now)).unwrap();
+
+        assert!(database.has_left_at(1, 2, [now, now]).unwrap());
+        assert!(!database.has_left_at(1, 2, [now + 60, now + 60]).unwrap());
+        assert!(!database.has_left_at(0, 2, [now, now]).unwrap());
+    }
+}
@@ -0,0 +1,329 @@
+use super::{overstay_from_row, subscribers::subscription_from_row, Database};
+use crate::models::receipt::{Charge, PaymentMethod, Receipt, ReceiptLine, VehicleExit};
+use rusqlite::{named_params, Error, OptionalExtension, Transaction};
+use tracing::info;
+
+impl Database {
+    pub(super) fn initialize_receipt_tables(&self) {
+        self.connection
+            .execute_batch(
+                "
+                -- Holds everything it was issued from, the vehicles are pruned and reset
+                -- while the receipts are kept
+                CREATE TABLE IF NOT EXISTS receipt (
+                    number INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+                    vehicle_id INTEGER NOT NULL UNIQUE,
+                    floor_number INTEGER NOT NULL,
+                    spot_number INTEGER NOT NULL,
+                    entry_time BIGINT NOT NULL,
+                    exit_time BIGINT NOT NULL,
+                    duration_minutes INTEGER NOT NULL,
+                    clock_skewed BOOLEAN NOT NULL,
+                    subscriber_id INTEGER,
+                    total REAL NOT NULL,
+                    issued_at BIGINT NOT NULL
+                );
+
+                CREATE TABLE IF NOT EXISTS receipt_line (
+                    receipt_number INTEGER NOT NULL,
+                    position INTEGER NOT NULL,
+                    kind INTEGER NOT NULL CHECK (kind IN (0, 1)),
+                    description TEXT NOT NULL,
+                    quantity INTEGER NOT NULL,
+                    unit_price REAL NOT NULL,
+                    amount REAL NOT NULL,
+                    PRIMARY KEY (receipt_number, position),
+                    FOREIGN KEY (receipt_number) REFERENCES receipt(number)
+                );",
+            )
+            .unwrap();
+
+        self.add_column_if_missing("receipt", "payment_method", "INTEGER NOT NULL DEFAULT 0");
+        self.drop_receipt_vehicle_reference();
+    }
+
+    // The first receipts referenced their vehicle, which kept it from being deleted.
+    // SQLite can't drop a foreign key, so the table is copied without it
+    fn drop_receipt_vehicle_reference(&self) {
+        let references_vehicle: bool = self
+            .connection
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_foreign_key_list('receipt') WHERE \"table\" = 'vehicle';",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+
+        if !references_vehicle {
+            return;
+        }
+
+        self.connection
+            .execute_batch(
+                "
+                PRAGMA foreign_keys = OFF;
+                BEGIN;
+
+                CREATE TABLE receipt_without_vehicle (
+                    number INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+                    vehicle_id INTEGER NOT NULL UNIQUE,
+                    floor_number INTEGER NOT NULL,
+                    spot_number INTEGER NOT NULL,
+                    entry_time BIGINT NOT NULL,
+                    exit_time BIGINT NOT NULL,
+                    duration_minutes INTEGER NOT NULL,
+                    clock_skewed BOOLEAN NOT NULL,
+                    subscriber_id INTEGER,
+                    total REAL NOT NULL,
+                    issued_at BIGINT NOT NULL,
+                    payment_method INTEGER NOT NULL DEFAULT 0
+                );
+
+                INSERT INTO receipt_without_vehicle(
+                    number, vehicle_id, floor_number, spot_number, entry_time, exit_time,
+                    duration_minutes, clock_skewed, subscriber_id, total, issued_at, payment_method
+                )
+                SELECT
+                    number, vehicle_id, floor_number, spot_number, entry_time, exit_time,
+                    duration_minutes, clock_skewed, subscriber_id, total, issued_at, payment_method
+                FROM
+                    receipt;
+
+                DROP TABLE receipt;
+                ALTER TABLE receipt_without_vehicle RENAME TO receipt;
+
+                COMMIT;
+                PRAGMA foreign_keys = ON;",
+            )
+            .unwrap();
+
+        info!("receipt no longer references the vehicle table");
+    }
+
+    pub fn get_receipt(&self, number: i64) -> Result<Option<Receipt>, Error> {
+        let receipt = self
+            .connection
+            .query_row(
+                "
+                SELECT
+                    vehicle_id,
+                    floor_number,
+                    spot_number,
+                    entry_time,
+                    exit_time,
+                    duration_minutes,
+                    clock_skewed,
+                    subscriber_id,
+                    total,
+                    payment_method,
+                    issued_at
+                FROM
+                    receipt
+                WHERE
+                    number = :number;",
+                named_params! {
+                    ":number": number,
+                },
+                |row| {
+                    Ok(Receipt {
+                        number,
+                        vehicle_id: row.get(0)?,
+                        floor_number: row.get(1)?,
+                        spot_number: row.get(2)?,
+                        entry_time: row.get(3)?,
+                        exit_time: row.get(4)?,
+                        duration_minutes: row.get(5)?,
+                        clock_skewed: row.get(6)?,
+                        subscriber_id: row.get(7)?,
+                        lines: Vec::new(),
+                        total: row.get(8)?,
+                        payment_method: row.get(9)?,
+                        issued_at: row.get(10)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        let Some(mut receipt) = receipt else {
+            return Ok(None);
+        };
+
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                kind,
+                description,
+                quantity,
+                unit_price,
+                amount
+            FROM
+                receipt_line
+            WHERE
+                receipt_number = :number
+            ORDER BY
+                position ASC;",
+        )?;
+
+        let lines = stmt.query_map(
+            named_params! {
+                ":number": number,
+            },
+            |row| {
+                Ok(ReceiptLine {
+                    kind: row.get(0)?,
+                    description: row.get(1)?,
+                    quantity: row.get(2)?,
+                    unit_price: row.get(3)?,
+                    amount: row.get(4)?,
+                })
+            },
+        )?;
+
+        for line in lines {
+            receipt.lines.push(line?);
+        }
+
+        Ok(Some(receipt))
+    }
+
+    pub fn set_receipt_payment_method(
+        &mut self,
+        number: i64,
+        payment_method: PaymentMethod,
+    ) -> Result<usize, Error> {
+        self.connection.execute(
+            "UPDATE receipt SET payment_method = :payment_method WHERE number = :number;",
+            named_params! {
+                ":number": number,
+                ":payment_method": payment_method as i32,
+            },
+        )
+    }
+}
+
+// The spot is the one the vehicle just left, vehicles from before the spot was
+// recorded have it NULL
+pub(super) fn get_vehicle_exit(
+    tx: &Transaction,
+    vehicle_id: i32,
+    floor_number: i32,
+    spot_number: i32,
+) -> Result<VehicleExit, Error> {
+    tx.query_row(
+        "
+        SELECT
+            v.entry_time,
+            ce.exit_time,
+            COALESCE(v.entry_clock_skewed, 0) OR COALESCE(ce.exit_clock_skewed, 0),
+            v.overstay_max_minutes,
+            v.overstay_surcharge_per_minute,
+            v.subscriber_id,
+            v.subscriber_rate_per_minute
+        FROM
+            vehicle v
+        INNER JOIN car_exit ce ON
+            v.id = ce.id
+        WHERE
+            v.id = :vehicle_id;",
+        named_params! {
+            ":vehicle_id": vehicle_id,
+        },
+        |row| {
+            Ok(VehicleExit {
+                vehicle_id,
+                floor_number,
+                spot_number,
+                entry_time: row.get(0)?,
+                exit_time: row.get(1)?,
+                clock_skewed: row.get(2)?,
+                overstay: overstay_from_row(row, 3)?,
+                subscription: subscription_from_row(row, 5)?,
+            })
+        },
+    )
+}
+
+// Part of the transaction that records the exit, so a departure is never left
+// without its receipt
+pub(super) fn insert_receipt(
+    tx: &Transaction,
+    exit: &VehicleExit,
+    charge: Charge,
+    issued_at: i64,
+) -> Result<Receipt, Error> {
+    let Charge {
+        lines,
+        total,
+        payment_method,
+    } = charge;
+    let duration_minutes = (exit.exit_time - exit.entry_time) / 60;
+    let subscriber_id = exit
+        .subscription
+        .map(|subscription| subscription.subscriber_id);
+
+    let number = tx
+        .prepare(
+            "
+            INSERT INTO receipt(
+                vehicle_id, floor_number, spot_number, entry_time, exit_time,
+                duration_minutes, clock_skewed, subscriber_id, total, payment_method, issued_at
+            )
+            VALUES (
+                :vehicle_id, :floor_number, :spot_number, :entry_time, :exit_time,
+                :duration_minutes, :clock_skewed, :subscriber_id, :total, :payment_method, :issued_at
+            );",
+        )?
+        .insert(named_params! {
+            ":vehicle_id": exit.vehicle_id,
+            ":floor_number": exit.floor_number,
+            ":spot_number": exit.spot_number,
+            ":entry_time": exit.entry_time,
+            ":exit_time": exit.exit_time,
+            ":duration_minutes": duration_minutes,
+            ":clock_skewed": exit.clock_skewed,
+            ":subscriber_id": subscriber_id,
+            ":total": total,
+            ":payment_method": payment_method as i32,
+            ":issued_at": issued_at,
+        })?;
+
+    {
+        let mut stmt = tx.prepare(
+            "
+            INSERT INTO receipt_line(
+                receipt_number, position, kind, description, quantity, unit_price, amount
+            )
+            VALUES (
+                :receipt_number, :position, :kind, :description, :quantity, :unit_price, :amount
+            );",
+        )?;
+
+        for (position, line) in lines.iter().enumerate() {
+            stmt.execute(named_params! {
+                ":receipt_number": number,
+                ":position": position,
+                ":kind": line.kind as i32,
+                ":description": line.description,
+                ":quantity": line.quantity,
+                ":unit_price": line.unit_price,
+                ":amount": line.amount,
+            })?;
+        }
+    }
+
+    Ok(Receipt {
+        number,
+        vehicle_id: exit.vehicle_id,
+        floor_number: exit.floor_number,
+        spot_number: exit.spot_number,
+        entry_time: exit.entry_time,
+        exit_time: exit.exit_time,
+        duration_minutes,
+        clock_skewed: exit.clock_skewed,
+        subscriber_id,
+        lines,
+        total,
+        payment_method,
+        issued_at,
+    })
+}
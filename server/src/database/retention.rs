@@ -0,0 +1,290 @@
+use super::Database;
+use crate::models::retention::{DailySummary, StorageReport};
+use rusqlite::{named_params, Error};
+
+impl Database {
+    pub(super) fn initialize_retention_tables(&self) {
+        self.connection
+            .execute_batch(
+                "
+                -- Kept forever, the raw vehicles and exits are pruned after the retention.
+                -- floor_number and spot_type are -1 for vehicles recorded before the
+                -- spot was stored with them
+                CREATE TABLE IF NOT EXISTS daily_summary (
+                    date TEXT NOT NULL,
+                    floor_number INTEGER NOT NULL,
+                    spot_type INTEGER NOT NULL,
+                    arrivals INTEGER NOT NULL DEFAULT 0,
+                    departures INTEGER NOT NULL DEFAULT 0,
+                    dwell_minutes INTEGER NOT NULL DEFAULT 0,
+                    revenue REAL NOT NULL DEFAULT 0,
+                    PRIMARY KEY (date, floor_number, spot_type)
+                );",
+            )
+            .unwrap();
+
+        // Whether the arrival and the departure are already in the daily summary
+        self.add_column_if_missing("vehicle", "summarized", "BOOLEAN NOT NULL DEFAULT 0");
+        self.add_column_if_missing("car_exit", "summarized", "BOOLEAN NOT NULL DEFAULT 0");
+
+        // The jobs only look at the rows not summarized yet and at the oldest exits
+        self.connection
+            .execute_batch(
+                "
+                CREATE INDEX IF NOT EXISTS vehicle_unsummarized ON vehicle(id) WHERE summarized = 0;
+                CREATE INDEX IF NOT EXISTS car_exit_unsummarized ON car_exit(id) WHERE summarized = 0;
+                CREATE INDEX IF NOT EXISTS car_exit_exit_time ON car_exit(exit_time);",
+            )
+            .unwrap();
+    }
+
+    // Adds up to `batch_size` arrivals before `before` to the daily summary, by the
+    // local date of the entry. Returns how many were added
+    pub fn summarize_arrivals(&mut self, before: i64, batch_size: i64) -> Result<usize, Error> {
+        let tx = self.connection.transaction()?;
+
+        tx.execute(
+            "CREATE TEMP TABLE IF NOT EXISTS summarized_batch (id INTEGER NOT NULL PRIMARY KEY);",
+            [],
+        )?;
+        tx.execute("DELETE FROM temp.summarized_batch;", [])?;
+
+        let summarized = tx.execute(
+            "
+            INSERT INTO temp.summarized_batch(id)
+            SELECT id FROM vehicle
+            WHERE summarized = 0 AND entry_time < :before
+            ORDER BY id ASC
+            LIMIT :batch_size;",
+            named_params! {
+                ":before": before,
+                ":batch_size": batch_size,
+            },
+        )?;
+
+        tx.execute(
+            "
+            INSERT INTO daily_summary(date, floor_number, spot_type, arrivals)
+            SELECT
+                date(v.entry_time, 'unixepoch', 'localtime'),
+                COALESCE(v.floor_number, -1),
+                COALESCE(ps.spot_type, -1),
+                COUNT(*)
+            FROM
+                vehicle v
+            LEFT JOIN parking_spot ps ON
+                v.floor_number = ps.floor_number AND v.spot_number = ps.spot_number
+            WHERE
+                v.id IN (SELECT id FROM temp.summarized_batch)
+            GROUP BY
+                1, 2, 3
+            ON CONFLICT (date, floor_number, spot_type) DO UPDATE SET
+                arrivals = arrivals + excluded.arrivals;",
+            [],
+        )?;
+
+        tx.execute(
+            "UPDATE vehicle SET summarized = 1 WHERE id IN (SELECT id FROM temp.summarized_batch);",
+            [],
+        )?;
+
+        tx.commit()?;
+
+        Ok(summarized)
+    }
+
+    // Same as summarize_arrivals for the departures, by the local date of the exit,
+    // with the time parked and the amount charged
+    pub fn summarize_departures(&mut self, before: i64, batch_size: i64) -> Result<usize, Error> {
+        let tx = self.connection.transaction()?;
+
+        tx.execute(
+            "CREATE TEMP TABLE IF NOT EXISTS summarized_batch (id INTEGER NOT NULL PRIMARY KEY);",
+            [],
+        )?;
+        tx.execute("DELETE FROM temp.summarized_batch;", [])?;
+
+        let summarized = tx.execute(
+            "
+            INSERT INTO temp.summarized_batch(id)
+            SELECT id FROM car_exit
+            WHERE summarized = 0 AND exit_time < :before
+            ORDER BY id ASC
+            LIMIT :batch_size;",
+            named_params! {
+                ":before": before,
+                ":batch_size": batch_size,
+            },
+        )?;
+
+        tx.execute(
+            "
+            INSERT INTO daily_summary(date, floor_number, spot_type, departures, dwell_minutes, revenue)
+            SELECT
+                date(ce.exit_time, 'unixepoch', 'localtime'),
+                COALESCE(v.floor_number, -1),
+                COALESCE(ps.spot_type, -1),
+                COUNT(*),
+                SUM((ce.exit_time - v.entry_time) / 60),
+                COALESCE(SUM(r.total), 0)
+            FROM
+                car_exit ce
+            INNER JOIN vehicle v ON
+                v.id = ce.id
+            LEFT JOIN parking_spot ps ON
+                v.floor_number = ps.floor_number AND v.spot_number = ps.spot_number
+            LEFT JOIN receipt r ON
+                r.vehicle_id = ce.id
+            WHERE
+                ce.id IN (SELECT id FROM temp.summarized_batch)
+            GROUP BY
+                1, 2, 3
+            ON CONFLICT (date, floor_number, spot_type) DO UPDATE SET
+                departures = departures + excluded.departures,
+                dwell_minutes = dwell_minutes + excluded.dwell_minutes,
+                revenue = revenue + excluded.revenue;",
+            [],
+        )?;
+
+        tx.execute(
+            "UPDATE car_exit SET summarized = 1 WHERE id IN (SELECT id FROM temp.summarized_batch);",
+            [],
+        )?;
+
+        tx.commit()?;
+
+        Ok(summarized)
+    }
+
+    // Deletes up to `batch_size` vehicles that left before `before` and are already
+    // in the daily summary. Receipts and cash closings are fiscal records and stay
+    pub fn prune_vehicles(&mut self, before: i64, batch_size: i64) -> Result<usize, Error> {
+        let tx = self.connection.transaction()?;
+
+        tx.execute(
+            "CREATE TEMP TABLE IF NOT EXISTS pruned_batch (id INTEGER NOT NULL PRIMARY KEY);",
+            [],
+        )?;
+        tx.execute("DELETE FROM temp.pruned_batch;", [])?;
+
+        // The newest vehicle is never pruned, otherwise its id (the ticket printed on
+        // the receipts) would be handed out again
+        let pruned = tx.execute(
+            "
+            INSERT INTO temp.pruned_batch(id)
+            SELECT
+                ce.id
+            FROM
+                car_exit ce
+            INNER JOIN vehicle v ON
+                v.id = ce.id
+            WHERE
+                ce.exit_time < :before
+                AND ce.summarized = 1
+                AND v.summarized = 1
+                AND ce.id < (SELECT MAX(id) FROM vehicle)
+            ORDER BY
+                ce.id ASC
+            LIMIT :batch_size;",
+            named_params! {
+                ":before": before,
+                ":batch_size": batch_size,
+            },
+        )?;
+
+        tx.execute(
+            "DELETE FROM car_exit WHERE id IN (SELECT id FROM temp.pruned_batch);",
+            [],
+        )?;
+        tx.execute(
+            "DELETE FROM vehicle WHERE id IN (SELECT id FROM temp.pruned_batch);",
+            [],
+        )?;
+
+        tx.commit()?;
+
+        Ok(pruned)
+    }
+
+    // Rewrites the file without the pages freed by the pruning
+    pub fn vacuum(&mut self) -> Result<(), Error> {
+        self.connection.execute_batch("VACUUM;")
+    }
+
+    pub fn get_storage_report(&self) -> Result<StorageReport, Error> {
+        let page_size: i64 = self
+            .connection
+            .query_row("PRAGMA page_size;", [], |row| row.get(0))?;
+        let page_count: i64 = self
+            .connection
+            .query_row("PRAGMA page_count;", [], |row| row.get(0))?;
+        let free_pages: i64 = self
+            .connection
+            .query_row("PRAGMA freelist_count;", [], |row| row.get(0))?;
+
+        self.connection.query_row(
+            "
+            SELECT
+                (SELECT COUNT(*) FROM vehicle),
+                (SELECT COUNT(*) FROM car_exit),
+                (SELECT COUNT(*) FROM receipt),
+                (SELECT COUNT(*) FROM occupancy_snapshot),
+                (SELECT COUNT(DISTINCT date) FROM daily_summary),
+                (SELECT MIN(exit_time) FROM car_exit);",
+            [],
+            |row| {
+                Ok(StorageReport {
+                    size_bytes: page_size * page_count,
+                    free_bytes: page_size * free_pages,
+                    vehicles: row.get(0)?,
+                    exits: row.get(1)?,
+                    receipts: row.get(2)?,
+                    occupancy_snapshots: row.get(3)?,
+                    summarized_days: row.get(4)?,
+                    oldest_exit_time: row.get(5)?,
+                })
+            },
+        )
+    }
+
+    // Dates as yyyy-mm-dd, both ends included
+    pub fn get_daily_summaries(&self, from: &str, to: &str) -> Result<Vec<DailySummary>, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                date,
+                floor_number,
+                spot_type,
+                arrivals,
+                departures,
+                dwell_minutes,
+                revenue
+            FROM
+                daily_summary
+            WHERE
+                date >= :from AND date <= :to
+            ORDER BY
+                date ASC, floor_number ASC, spot_type ASC;",
+        )?;
+
+        let summaries = stmt.query_map(
+            named_params! {
+                ":from": from,
+                ":to": to,
+            },
+            |row| {
+                Ok(DailySummary {
+                    date: row.get(0)?,
+                    floor_number: row.get(1)?,
+                    spot_type: row.get(2)?,
+                    arrivals: row.get(3)?,
+                    departures: row.get(4)?,
+                    dwell_minutes: row.get(5)?,
+                    revenue: row.get(6)?,
+                })
+            },
+        )?;
+
+        summaries.collect()
+    }
+}
@@ -0,0 +1,119 @@
+use super::Database;
+use crate::models::sensor_report::SensorReport;
+use rusqlite::{named_params, Error};
+
+impl Database {
+    pub(super) fn initialize_sensor_report_tables(&self) {
+        self.connection
+            .execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS sensor_report (
+                    id INTEGER NOT NULL PRIMARY KEY,
+                    floor_number INTEGER NOT NULL,
+                    kind TEXT NOT NULL,
+                    parking_space INTEGER,
+                    first_sensor TEXT,
+                    timestamp BIGINT NOT NULL,
+                    received_at BIGINT NOT NULL
+                );
+
+                CREATE INDEX IF NOT EXISTS sensor_report_received_at
+                    ON sensor_report(received_at);",
+            )
+            .unwrap();
+    }
+
+    pub fn insert_sensor_report(
+        &mut self,
+        floor_number: i32,
+        kind: &str,
+        parking_space: Option<i32>,
+        first_sensor: Option<&str>,
+        timestamp: i64,
+        received_at: i64,
+    ) -> Result<SensorReport, Error> {
+        self.connection.execute(
+            "
+            INSERT INTO sensor_report(floor_number, kind, parking_space, first_sensor, timestamp, received_at)
+            VALUES (:floor_number, :kind, :parking_space, :first_sensor, :timestamp, :received_at);",
+            named_params! {
+                ":floor_number": floor_number,
+                ":kind": kind,
+                ":parking_space": parking_space,
+                ":first_sensor": first_sensor,
+                ":timestamp": timestamp,
+                ":received_at": received_at,
+            },
+        )?;
+
+        Ok(SensorReport {
+            id: self.connection.last_insert_rowid(),
+            floor_number,
+            kind: kind.to_string(),
+            parking_space,
+            first_sensor: first_sensor.map(str::to_string),
+            timestamp,
+            received_at,
+        })
+    }
+
+    // Received in the [from, to] window, newest first
+    pub fn get_sensor_reports(&self, from: i64, to: i64) -> Result<Vec<SensorReport>, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                id,
+                floor_number,
+                kind,
+                parking_space,
+                first_sensor,
+                timestamp,
+                received_at
+            FROM
+                sensor_report
+            WHERE
+                received_at BETWEEN :from AND :to
+            ORDER BY
+                received_at DESC, id DESC;",
+        )?;
+
+        let reports = stmt.query_map(
+            named_params! {
+                ":from": from,
+                ":to": to,
+            },
+            |row| {
+                Ok(SensorReport {
+                    id: row.get(0)?,
+                    floor_number: row.get(1)?,
+                    kind: row.get(2)?,
+                    parking_space: row.get(3)?,
+                    first_sensor: row.get(4)?,
+                    timestamp: row.get(5)?,
+                    received_at: row.get(6)?,
+                })
+            },
+        )?;
+
+        reports.collect()
+    }
+
+    // Deletes up to `batch_size` reports received before `before`. Returns how
+    // many were deleted
+    pub fn prune_sensor_reports(&mut self, before: i64, batch_size: i64) -> Result<usize, Error> {
+        self.connection.execute(
+            "
+            DELETE FROM sensor_report
+            WHERE id IN (
+                SELECT id FROM sensor_report
+                WHERE received_at < :before
+                ORDER BY id ASC
+                LIMIT :batch_size
+            );",
+            named_params! {
+                ":before": before,
+                ":batch_size": batch_size,
+            },
+        )
+    }
+}
@@ -0,0 +1,198 @@
+use super::Database;
+use crate::{
+    models::subscriber::{Subscriber, Subscription},
+    socket::payloads::NewSubscriberPayload,
+};
+use rusqlite::{named_params, Error, OptionalExtension, Row};
+
+impl Database {
+    pub(super) fn initialize_subscriber_tables(&self) {
+        self.connection
+            .execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS subscriber (
+                    id INTEGER NOT NULL PRIMARY KEY,
+                    credential TEXT NOT NULL UNIQUE,
+                    name TEXT NOT NULL,
+                    valid_from BIGINT NOT NULL,
+                    valid_until BIGINT NOT NULL,
+                    allowed_floors TEXT NOT NULL,
+                    reserved_floor_number INTEGER,
+                    reserved_spot_number INTEGER,
+                    rate_per_minute REAL NOT NULL,
+                    created_at BIGINT NOT NULL
+                );",
+            )
+            .unwrap();
+    }
+
+    pub fn add_subscriber(
+        &mut self,
+        request: &NewSubscriberPayload,
+        valid_from: i64,
+        now: i64,
+    ) -> Result<Subscriber, Error> {
+        let reserved_spot = request
+            .reserved_spot
+            .as_ref()
+            .map(|spot| (spot.floor_number, spot.spot_number));
+
+        self.connection.execute(
+            "
+            INSERT INTO subscriber(
+                credential, name, valid_from, valid_until, allowed_floors,
+                reserved_floor_number, reserved_spot_number, rate_per_minute, created_at
+            )
+            VALUES (
+                :credential, :name, :valid_from, :valid_until, :allowed_floors,
+                :reserved_floor_number, :reserved_spot_number, :rate_per_minute, :now
+            );",
+            named_params! {
+                ":credential": request.credential,
+                ":name": request.name,
+                ":valid_from": valid_from,
+                ":valid_until": request.valid_until,
+                ":allowed_floors": join_floors(&request.allowed_floors),
+                ":reserved_floor_number": reserved_spot.map(|spot| spot.0),
+                ":reserved_spot_number": reserved_spot.map(|spot| spot.1),
+                ":rate_per_minute": request.rate_per_minute,
+                ":now": now,
+            },
+        )?;
+
+        Ok(Subscriber {
+            id: self.connection.last_insert_rowid(),
+            credential: request.credential.clone(),
+            name: request.name.clone(),
+            valid_from,
+            valid_until: request.valid_until,
+            allowed_floors: request.allowed_floors.clone(),
+            reserved_spot,
+            rate_per_minute: request.rate_per_minute,
+        })
+    }
+
+    // The vehicles keep their subscription, only the registry entry goes away
+    pub fn remove_subscriber(&mut self, id: i64) -> Result<usize, Error> {
+        self.connection.execute(
+            "DELETE FROM subscriber WHERE id = :id;",
+            named_params! {
+                ":id": id,
+            },
+        )
+    }
+
+    pub fn get_subscribers(&self) -> Result<Vec<Subscriber>, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                id,
+                credential,
+                name,
+                valid_from,
+                valid_until,
+                allowed_floors,
+                reserved_floor_number,
+                reserved_spot_number,
+                rate_per_minute
+            FROM
+                subscriber
+            ORDER BY
+                name ASC;",
+        )?;
+
+        let subscribers = stmt.query_map([], subscriber_from_row)?;
+
+        subscribers.collect()
+    }
+
+    pub fn find_subscriber(&self, credential: &str) -> Result<Option<Subscriber>, Error> {
+        self.connection
+            .query_row(
+                "
+                SELECT
+                    id,
+                    credential,
+                    name,
+                    valid_from,
+                    valid_until,
+                    allowed_floors,
+                    reserved_floor_number,
+                    reserved_spot_number,
+                    rate_per_minute
+                FROM
+                    subscriber
+                WHERE
+                    credential = :credential;",
+                named_params! {
+                    ":credential": credential,
+                },
+                subscriber_from_row,
+            )
+            .optional()
+    }
+
+    // For the vehicles recognized after they parked
+    pub fn set_vehicle_subscription(
+        &mut self,
+        vehicle_id: i32,
+        subscription: &Subscription,
+    ) -> Result<(), Error> {
+        self.connection.execute(
+            "
+            UPDATE vehicle
+            SET subscriber_id = :subscriber_id, subscriber_rate_per_minute = :rate_per_minute
+            WHERE id = :vehicle_id;",
+            named_params! {
+                ":vehicle_id": vehicle_id,
+                ":subscriber_id": subscription.subscriber_id,
+                ":rate_per_minute": subscription.rate_per_minute,
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+fn subscriber_from_row(row: &Row) -> Result<Subscriber, Error> {
+    let allowed_floors: String = row.get(5)?;
+    let reserved_floor_number: Option<i32> = row.get(6)?;
+    let reserved_spot_number: Option<i32> = row.get(7)?;
+
+    Ok(Subscriber {
+        id: row.get(0)?,
+        credential: row.get(1)?,
+        name: row.get(2)?,
+        valid_from: row.get(3)?,
+        valid_until: row.get(4)?,
+        allowed_floors: allowed_floors
+            .split(',')
+            .filter_map(|floor| floor.parse().ok())
+            .collect(),
+        reserved_spot: reserved_floor_number.zip(reserved_spot_number),
+        rate_per_minute: row.get(8)?,
+    })
+}
+
+// Reads subscriber_id and subscriber_rate_per_minute starting at the given column
+pub(super) fn subscription_from_row(
+    row: &Row,
+    first_column: usize,
+) -> Result<Option<Subscription>, Error> {
+    let subscriber_id: Option<i64> = row.get(first_column)?;
+    let rate_per_minute: Option<f64> = row.get(first_column + 1)?;
+
+    Ok(subscriber_id.map(|subscriber_id| Subscription {
+        subscriber_id,
+        rate_per_minute: rate_per_minute.unwrap_or(0.0),
+    }))
+}
+
+// Stored as a comma separated list, e.g. "0,1"
+fn join_floors(floors: &[i32]) -> String {
+    floors
+        .iter()
+        .map(|floor| floor.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
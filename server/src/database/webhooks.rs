@@ -0,0 +1,172 @@
+use super::Database;
+use crate::models::webhook::{DeliveryStatus, WebhookDelivery};
+use rusqlite::{named_params, Error};
+
+impl Database {
+    pub(super) fn initialize_webhook_tables(&self) {
+        self.connection
+            .execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS webhook_delivery (
+                    id INTEGER NOT NULL PRIMARY KEY,
+                    event TEXT NOT NULL,
+                    url TEXT NOT NULL,
+                    body TEXT NOT NULL,
+                    status INTEGER NOT NULL DEFAULT 0 CHECK (status IN (0, 1, 2)),
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    next_attempt_at BIGINT NOT NULL,
+                    last_error TEXT,
+                    created_at BIGINT NOT NULL
+                );
+
+                CREATE INDEX IF NOT EXISTS webhook_delivery_due
+                    ON webhook_delivery(status, next_attempt_at);",
+            )
+            .unwrap();
+    }
+
+    pub fn enqueue_webhook_delivery(
+        &mut self,
+        event: &str,
+        url: &str,
+        body: &str,
+        now: i64,
+    ) -> Result<(), Error> {
+        self.connection.execute(
+            "
+            INSERT INTO webhook_delivery(event, url, body, status, next_attempt_at, created_at)
+            VALUES (:event, :url, :body, :status, :now, :now);",
+            named_params! {
+                ":event": event,
+                ":url": url,
+                ":body": body,
+                ":status": DeliveryStatus::Pending as i32,
+                ":now": now,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_due_webhook_deliveries(
+        &self,
+        now: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, Error> {
+        let mut stmt = self.connection.prepare(
+            "
+            SELECT
+                id,
+                event,
+                url,
+                body,
+                attempts
+            FROM
+                webhook_delivery
+            WHERE
+                status = :status AND next_attempt_at <= :now
+            ORDER BY
+                id ASC
+            LIMIT :limit;",
+        )?;
+
+        let deliveries = stmt.query_map(
+            named_params! {
+                ":status": DeliveryStatus::Pending as i32,
+                ":now": now,
+                ":limit": limit,
+            },
+            |row| {
+                Ok(WebhookDelivery {
+                    id: row.get(0)?,
+                    event: row.get(1)?,
+                    url: row.get(2)?,
+                    body: row.get(3)?,
+                    attempts: row.get(4)?,
+                })
+            },
+        )?;
+
+        deliveries.collect()
+    }
+
+    pub fn mark_webhook_delivered(&mut self, id: i64) -> Result<(), Error> {
+        self.connection.execute(
+            "
+            UPDATE
+                webhook_delivery
+            SET
+                status = :status,
+                attempts = attempts + 1,
+                last_error = NULL
+            WHERE
+                id = :id;",
+            named_params! {
+                ":status": DeliveryStatus::Delivered as i32,
+                ":id": id,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    // Deletes up to `batch_size` deliveries created before `before` that were
+    // delivered or given up on. Returns how many were deleted
+    pub fn prune_webhook_deliveries(
+        &mut self,
+        before: i64,
+        batch_size: i64,
+    ) -> Result<usize, Error> {
+        self.connection.execute(
+            "
+            DELETE FROM webhook_delivery
+            WHERE id IN (
+                SELECT id FROM webhook_delivery
+                WHERE status IN (:delivered, :failed) AND created_at < :before
+                ORDER BY id ASC
+                LIMIT :batch_size
+            );",
+            named_params! {
+                ":delivered": DeliveryStatus::Delivered as i32,
+                ":failed": DeliveryStatus::Failed as i32,
+                ":before": before,
+                ":batch_size": batch_size,
+            },
+        )
+    }
+
+    // Registers a failed attempt, the delivery is retried at next_attempt_at
+    // or given up on if next_attempt_at is None
+    pub fn register_webhook_failure(
+        &mut self,
+        id: i64,
+        error: &str,
+        next_attempt_at: Option<i64>,
+    ) -> Result<(), Error> {
+        let status = match next_attempt_at {
+            Some(_) => DeliveryStatus::Pending,
+            None => DeliveryStatus::Failed,
+        };
+
+        self.connection.execute(
+            "
+            UPDATE
+                webhook_delivery
+            SET
+                status = :status,
+                attempts = attempts + 1,
+                last_error = :error,
+                next_attempt_at = COALESCE(:next_attempt_at, next_attempt_at)
+            WHERE
+                id = :id;",
+            named_params! {
+                ":status": status as i32,
+                ":error": error,
+                ":next_attempt_at": next_attempt_at,
+                ":id": id,
+            },
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::socket::payloads::{
+    BarrierFaultPayload, ClockSkewPayload, ControllerDisconnectedPayload, FloorClosedPayload,
+    ParkingLotDataPayload, SensorReportPayload, VehicleMovementPayload, VehicleOverstayedPayload,
+};
+use serde::Serialize;
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+const EVENT_BUS_CAPACITY: usize = 64;
+
+// Events that are interesting to systems outside of the socket.io clients
+#[derive(Serialize, Clone)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum LotEvent {
+    LotFull(ParkingLotDataPayload),
+    FloorClosed(FloorClosedPayload),
+    ControllerDisconnected(ControllerDisconnectedPayload),
+    VehicleOverstayed(VehicleOverstayedPayload),
+    CarArrived(VehicleMovementPayload),
+    CarDeparted(VehicleMovementPayload),
+    LotStateChanged(ParkingLotDataPayload),
+    ClockSkewDetected(ClockSkewPayload),
+    BarrierFault(BarrierFaultPayload),
+    SensorReport(SensorReportPayload),
+}
+
+impl LotEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::LotFull(_) => "lot_full",
+            Self::FloorClosed(_) => "floor_closed",
+            Self::ControllerDisconnected(_) => "controller_disconnected",
+            Self::VehicleOverstayed(_) => "vehicle_overstayed",
+            Self::CarArrived(_) => "car_arrived",
+            Self::CarDeparted(_) => "car_departed",
+            Self::LotStateChanged(_) => "lot_state_changed",
+            Self::ClockSkewDetected(_) => "clock_skew_detected",
+            Self::BarrierFault(_) => "barrier_fault",
+            Self::SensorReport(_) => "sensor_report",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    sender: Sender<LotEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: LotEvent) {
+        // Sending only fails when nobody is subscribed, which is fine
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> Receiver<LotEvent> {
+        self.sender.subscribe()
+    }
+}
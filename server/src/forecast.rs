@@ -0,0 +1,210 @@
+use crate::{
+    config::ForecastConfig,
+    database::Database,
+    models::analytics::VehicleStay,
+    socket::payloads::{FloorForecastPayload, ForecastPayload},
+};
+use chrono::{DateTime, Datelike, Local, Timelike, Utc};
+use rusqlite::Error;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time;
+use tracing::error;
+
+const SECONDS_PER_HOUR: i64 = 3600;
+const SECONDS_PER_WEEK: i64 = 7 * 24 * SECONDS_PER_HOUR;
+
+// How far back the weekday/hour profile looks
+const HISTORY_WEEKS: i64 = 4;
+const HORIZON_HOURS: i64 = 3;
+// The movements of the last hour are the current trend
+const TREND_WINDOW_SECS: i64 = SECONDS_PER_HOUR;
+const STEP_SECS: i64 = 300;
+
+// Average net flow (arrivals - departures) for each weekday and hour, in local time
+type WeeklyProfile = [[f64; 24]; 7];
+
+// Computes the forecast on a timer and caches it in the database, so the state
+// broadcasts don't query weeks of stays every time
+pub fn spawn(config: &ForecastConfig, database: &Arc<Mutex<Database>>) {
+    if config.refresh_interval_secs == 0 {
+        return;
+    }
+
+    let config = config.clone();
+    let database = database.clone();
+
+    tokio::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(config.refresh_interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            let mut database = database.lock().unwrap();
+
+            match compute(&database, Utc::now().timestamp()) {
+                Ok(forecast) => database.forecast = forecast,
+                Err(error) => error!(%error, "failed to compute the occupancy forecast"),
+            }
+        }
+    });
+}
+
+// Predicts the occupancy of each floor for the next hours, mixing the usual flow
+// of the weekday/hour with the current trend, which fades out over the first hour
+pub fn compute(database: &Database, now: i64) -> Result<ForecastPayload, Error> {
+    let from = now - HISTORY_WEEKS * SECONDS_PER_WEEK;
+    let stays = database.get_vehicle_stays(from, now)?;
+
+    // A young database has less than HISTORY_WEEKS of history to average on
+    let history_start = stays
+        .iter()
+        .map(|stay| stay.entry_time)
+        .min()
+        .unwrap_or(now)
+        .max(from);
+    let weeks = ((now - history_start) as f64 / SECONDS_PER_WEEK as f64).max(1.0);
+
+    let steps = (HORIZON_HOURS * SECONDS_PER_HOUR / STEP_SECS) as usize;
+    let mut lot_trajectory = vec![0.0; steps + 1];
+    let mut lot_capacity = 0.0;
+    let mut floors = Vec::with_capacity(3);
+
+    for floor_number in 0..3 {
+        let floor = database.get_floor(floor_number)?;
+        // Spots out of service don't count toward the capacity
+        let capacity = floor.capacity() as f64;
+        let occupied = floor.occupied() as f64;
+
+        let movements = floor_movements(&stays, floor_number, from, now);
+        let profile = weekly_profile(&movements, weeks);
+        let trend = movements
+            .iter()
+            .filter(|(timestamp, _)| *timestamp > now - TREND_WINDOW_SECS)
+            .map(|(_, delta)| delta)
+            .sum::<f64>()
+            * (SECONDS_PER_HOUR as f64 / TREND_WINDOW_SECS as f64);
+
+        let trajectory = project(occupied, capacity, &profile, trend, now, steps);
+
+        for (lot_occupancy, occupancy) in lot_trajectory.iter_mut().zip(&trajectory) {
+            *lot_occupancy += occupancy;
+        }
+        lot_capacity += capacity;
+
+        floors.push(FloorForecastPayload {
+            floor_number,
+            minutes_to_full: minutes_to_full(&trajectory, capacity),
+            expected_available: expected_available(&trajectory, capacity),
+        });
+    }
+
+    Ok(ForecastPayload {
+        generated_at: now,
+        minutes_to_full: minutes_to_full(&lot_trajectory, lot_capacity),
+        expected_available: expected_available(&lot_trajectory, lot_capacity),
+        floors,
+    })
+}
+
+// Arrivals (+1) and departures (-1) of the floor inside the window
+fn floor_movements(
+    stays: &[VehicleStay],
+    floor_number: i32,
+    from: i64,
+    to: i64,
+) -> Vec<(i64, f64)> {
+    let mut movements = Vec::new();
+
+    for stay in stays
+        .iter()
+        .filter(|stay| stay.floor_number == Some(floor_number))
+    {
+        if stay.entry_time >= from {
+            movements.push((stay.entry_time, 1.0));
+        }
+
+        if let Some(exit_time) = stay.exit_time.filter(|exit_time| *exit_time <= to) {
+            movements.push((exit_time, -1.0));
+        }
+    }
+
+    movements
+}
+
+fn weekly_profile(movements: &[(i64, f64)], weeks: f64) -> WeeklyProfile {
+    let mut profile = [[0.0; 24]; 7];
+
+    for (timestamp, delta) in movements {
+        let (weekday, hour) = weekday_hour(*timestamp);
+        profile[weekday][hour] += delta;
+    }
+
+    for hours in profile.iter_mut() {
+        for net_flow in hours.iter_mut() {
+            *net_flow /= weeks;
+        }
+    }
+
+    profile
+}
+
+// Occupancy at every step of the horizon, starting with the current one
+fn project(
+    occupied: f64,
+    capacity: f64,
+    profile: &WeeklyProfile,
+    trend: f64,
+    now: i64,
+    steps: usize,
+) -> Vec<f64> {
+    let mut trajectory = Vec::with_capacity(steps + 1);
+    let mut occupancy = occupied;
+
+    trajectory.push(occupancy);
+
+    for step in 0..steps as i64 {
+        let elapsed = step * STEP_SECS;
+        let (weekday, hour) = weekday_hour(now + elapsed);
+
+        let trend_weight = (1.0 - elapsed as f64 / SECONDS_PER_HOUR as f64).max(0.0);
+        let rate = trend_weight * trend + (1.0 - trend_weight) * profile[weekday][hour];
+
+        occupancy += rate * STEP_SECS as f64 / SECONDS_PER_HOUR as f64;
+        occupancy = occupancy.clamp(0.0, capacity);
+
+        trajectory.push(occupancy);
+    }
+
+    trajectory
+}
+
+// None when it is not expected to fill up inside the horizon
+fn minutes_to_full(trajectory: &[f64], capacity: f64) -> Option<i64> {
+    trajectory
+        .iter()
+        .position(|occupancy| occupancy.round() >= capacity)
+        .map(|step| step as i64 * STEP_SECS / 60)
+}
+
+// Free spots expected at the end of each of the next hours
+fn expected_available(trajectory: &[f64], capacity: f64) -> Vec<i32> {
+    let steps_per_hour = (SECONDS_PER_HOUR / STEP_SECS) as usize;
+
+    (1..=HORIZON_HOURS as usize)
+        .map(|hour| (capacity - trajectory[hour * steps_per_hour].round()) as i32)
+        .collect()
+}
+
+fn weekday_hour(timestamp: i64) -> (usize, usize) {
+    let time = DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .with_timezone(&Local);
+
+    (
+        time.weekday().num_days_from_monday() as usize,
+        time.hour() as usize,
+    )
+}
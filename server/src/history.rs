@@ -0,0 +1,162 @@
+use crate::{
+    config::HistoryConfig,
+    database::Database,
+    models::history::{OccupancySample, OccupancySnapshot},
+    socket::payloads::{
+        FloorOccupancyPayload, OccupancyHistoryPayload, OccupancyHistoryWindowPayload,
+        OccupancyPointPayload, SpotTypeOccupancyPayload,
+    },
+};
+use chrono::Utc;
+use rusqlite::Error;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time;
+use tracing::{debug, error};
+
+const SECONDS_PER_HOUR: i64 = 3600;
+const DEFAULT_WINDOW_SECS: i64 = 24 * SECONDS_PER_HOUR;
+
+pub fn spawn(config: &HistoryConfig, database: &Arc<Mutex<Database>>) {
+    if config.snapshot_interval_secs == 0 {
+        return;
+    }
+
+    let config = config.clone();
+    let database = database.clone();
+
+    tokio::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(config.snapshot_interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            let mut database = database.lock().unwrap();
+
+            if let Err(error) = record(&config, &mut database, Utc::now().timestamp()) {
+                error!(%error, "failed to record the occupancy snapshot");
+            }
+        }
+    });
+}
+
+// Takes a snapshot and averages the older ones, the hourly snapshots past their
+// retention are pruned with the rest of the old records
+fn record(config: &HistoryConfig, database: &mut Database, now: i64) -> Result<(), Error> {
+    let samples = sample(database)?;
+    database.insert_occupancy_snapshot(now, &samples)?;
+
+    // Only whole hours are downsampled
+    let raw_cutoff = now - config.raw_retention_hours * SECONDS_PER_HOUR;
+    let raw_cutoff = raw_cutoff - raw_cutoff.rem_euclid(SECONDS_PER_HOUR);
+    let downsampled = database.downsample_occupancy(raw_cutoff)?;
+
+    if downsampled > 0 {
+        debug!(downsampled, "occupancy history compacted");
+    }
+
+    Ok(())
+}
+
+// Current occupancy of every spot type of every floor
+fn sample(database: &Database) -> Result<Vec<OccupancySample>, Error> {
+    let lot_closed = database.is_parking_lot_closed()?;
+    let mut samples = Vec::with_capacity(9);
+
+    for floor_number in 0..3 {
+        let floor_closed = database.is_floor_closed(floor_number)?;
+        let floor = database.get_floor(floor_number)?;
+
+        for spot_type in 0..3 {
+            // Spots out of service don't count toward the capacity
+            let spots: Vec<_> = floor
+                .spots
+                .iter()
+                .filter(|spot| spot.spot_type as i32 == spot_type && spot.out_of_service.is_none())
+                .collect();
+
+            samples.push(OccupancySample {
+                floor_number,
+                spot_type,
+                occupied: spots
+                    .iter()
+                    .filter(|spot| spot.parked_vehicle.is_some())
+                    .count() as i32,
+                capacity: spots.len() as i32,
+                floor_closed,
+                lot_closed,
+            });
+        }
+    }
+
+    Ok(samples)
+}
+
+// Occupancy series of the window, by default the last 24 hours
+pub fn query(
+    database: &Database,
+    window: &OccupancyHistoryWindowPayload,
+) -> Result<OccupancyHistoryPayload, Error> {
+    let to = window.to.unwrap_or_else(|| Utc::now().timestamp());
+    let from = window.from.unwrap_or(to - DEFAULT_WINDOW_SECS);
+
+    let snapshots = database.get_occupancy_snapshots(from, to, window.resolution)?;
+
+    let mut points: Vec<OccupancyPointPayload> = Vec::new();
+
+    for snapshot in snapshots {
+        let is_new_point = match points.last() {
+            Some(point) => {
+                point.timestamp != snapshot.taken_at || point.resolution != snapshot.resolution
+            }
+            None => true,
+        };
+
+        if is_new_point {
+            points.push(OccupancyPointPayload {
+                timestamp: snapshot.taken_at,
+                resolution: snapshot.resolution,
+                is_closed: snapshot.lot_closed,
+                occupied: 0.0,
+                capacity: 0,
+                floors: Vec::with_capacity(3),
+            });
+        }
+
+        add_to_point(points.last_mut().unwrap(), &snapshot);
+    }
+
+    Ok(OccupancyHistoryPayload { from, to, points })
+}
+
+fn add_to_point(point: &mut OccupancyPointPayload, snapshot: &OccupancySnapshot) {
+    let is_new_floor = match point.floors.last() {
+        Some(floor) => floor.floor_number != snapshot.floor_number,
+        None => true,
+    };
+
+    if is_new_floor {
+        point.floors.push(FloorOccupancyPayload {
+            floor_number: snapshot.floor_number,
+            is_closed: snapshot.floor_closed,
+            occupied: 0.0,
+            capacity: 0,
+            spot_types: Vec::with_capacity(3),
+        });
+    }
+
+    let floor = point.floors.last_mut().unwrap();
+
+    floor.occupied += snapshot.occupied;
+    floor.capacity += snapshot.capacity;
+    floor.spot_types.push(SpotTypeOccupancyPayload {
+        spot_type: snapshot.spot_type,
+        occupied: snapshot.occupied,
+        capacity: snapshot.capacity,
+    });
+
+    point.occupied += snapshot.occupied;
+    point.capacity += snapshot.capacity;
+}
@@ -0,0 +1,104 @@
+use crate::{
+    database::Database,
+    models::{
+        client::ClientId,
+        input::{ControllerInputs, InputGlitches},
+    },
+    socket::payloads::{ControllerInputsPayload, InputGlitchesPayload, PinGlitchesPayload},
+};
+use chrono::Utc;
+use tracing::{info, warn};
+
+// The controllers debounce their inputs and periodically report what they filtered
+// out. A sensor that keeps glitching is usually loose or dirty, so every increase
+// is logged for the operator
+pub fn record(database: &mut Database, client_id: ClientId, payload: &InputGlitchesPayload) {
+    let previous = database.inputs.get(&client_id);
+
+    for pin in &payload.pins {
+        let (glitches_before, coalesced_before) = previous
+            .and_then(|inputs| inputs.pins.iter().find(|input| input.pin == pin.pin))
+            .map(|input| (input.glitches, input.coalesced))
+            .unwrap_or((0, 0));
+
+        // Lower counters mean the controller restarted and counts from zero again
+        let (new_glitches, new_coalesced) =
+            if pin.glitches < glitches_before || pin.coalesced < coalesced_before {
+                (pin.glitches, pin.coalesced)
+            } else {
+                (
+                    pin.glitches - glitches_before,
+                    pin.coalesced - coalesced_before,
+                )
+            };
+
+        if new_glitches > 0 {
+            warn!(
+                floor = %client_id,
+                pin = pin.pin.as_str(),
+                new_glitches,
+                glitches = pin.glitches,
+                "controller input is glitching"
+            );
+        } else if new_coalesced > 0 {
+            info!(
+                floor = %client_id,
+                pin = pin.pin.as_str(),
+                new_coalesced,
+                coalesced = pin.coalesced,
+                "controller input edges coalesced"
+            );
+        }
+    }
+
+    database.inputs.insert(
+        client_id,
+        ControllerInputs {
+            received_at: Utc::now().timestamp(),
+            pins: payload
+                .pins
+                .iter()
+                .map(|pin| InputGlitches {
+                    pin: pin.pin.clone(),
+                    glitches: pin.glitches,
+                    coalesced: pin.coalesced,
+                })
+                .collect(),
+        },
+    );
+}
+
+// Forgets the reports received before `before`, a controller that stopped
+// reporting was taken down and its old counters would only mislead
+pub fn prune(database: &mut Database, before: i64) -> usize {
+    let reports = database.inputs.len();
+    database
+        .inputs
+        .retain(|_, inputs| inputs.received_at >= before);
+
+    reports - database.inputs.len()
+}
+
+// Last report of every controller, from the ground floor up
+pub fn list(database: &Database) -> Vec<ControllerInputsPayload> {
+    ClientId::iter_floors()
+        .filter_map(|client_id| {
+            database
+                .inputs
+                .get(&client_id)
+                .map(|inputs| ControllerInputsPayload {
+                    client_id: client_id.to_string(),
+                    received_at: inputs.received_at,
+                    pins: inputs
+                        .pins
+                        .iter()
+                        .map(|pin| PinGlitchesPayload {
+                            pin: pin.pin.clone(),
+                            glitches: pin.glitches,
+                            coalesced: pin.coalesced,
+                        })
+                        .collect(),
+                })
+        })
+        .collect()
+}
@@ -1,17 +1,67 @@
+mod analytics;
+mod api;
+mod barriers;
+mod clock;
+mod closing;
+mod config;
 mod database;
+mod events;
+mod forecast;
+mod history;
+mod inputs;
+mod maintenance;
 mod models;
+mod mqtt;
+mod overstay;
+mod receipts;
+mod retention;
+mod sensor_reports;
 mod socket;
+mod subscribers;
+mod tariff;
+mod webhooks;
 
+use config::Config;
+use database::Database;
+use fse_trab_1_logging as logging;
 use socket::{namespace, server};
 use socketioxide::SocketIo;
+use std::sync::Arc;
+use tracing::info;
 
 #[tokio::main]
 async fn main() {
+    let config = Arc::new(Config::load());
+    logging::init(&config.logging);
+    info!(path = %Config::path(), "configuration ready");
+
+    let database = Database::new();
+
     let (layer, io) = SocketIo::new_layer();
 
     // Configure the one and only namespace of the socket.io server
-    namespace::configure_socket_namespace(&io);
+    namespace::configure_socket_namespace(&io, &config, &database);
+
+    // Start the background tasks that report lot events to external systems
+    webhooks::spawn(&config.webhooks, &database);
+    overstay::spawn(&config.overstay, &io, &database);
+    mqtt::spawn(&config.mqtt, &database, &io);
+
+    // Keep measuring the controller clocks to notice when they drift
+    clock::spawn(&config.clock, &io);
+
+    // Record the occupancy over time so it can be charted later
+    history::spawn(&config.history, &database);
+
+    // Keep the occupancy forecast sent with the parking lot state up to date
+    forecast::spawn(&config.forecast, &database);
+
+    // Summarize the old vehicles per day and prune them
+    retention::spawn(&config.retention, &config.history, &database);
+
+    // Start and end the scheduled spot maintenances
+    maintenance::spawn(&config.maintenance, &io, &database);
 
     // Configure the axum server and run it, this will block the main thread
-    server::configure_axum_server(layer).await;
+    server::configure_axum_server(layer, api::router(&config, &database, &io)).await;
 }
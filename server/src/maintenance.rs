@@ -0,0 +1,231 @@
+use crate::{
+    config::MaintenanceConfig,
+    database::Database,
+    models::{client::ClientId, maintenance::SpotMaintenance},
+    socket::{
+        commands,
+        constants::SPOTS_OUT_OF_SERVICE_EVENT,
+        payloads::{SpotMaintenancePayload, SpotOutOfServicePayload},
+    },
+};
+use chrono::Utc;
+use socketioxide::SocketIo;
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+use tokio::time;
+use tracing::{error, info, warn};
+
+pub enum MaintenanceError {
+    InvalidSpot,
+    InvalidWindow,
+    Database(rusqlite::Error),
+}
+
+impl From<rusqlite::Error> for MaintenanceError {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Database(error)
+    }
+}
+
+impl Display for MaintenanceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSpot => write!(f, "there is no such spot"),
+            Self::InvalidWindow => write!(f, "the maintenance must end after it starts"),
+            Self::Database(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+// What the fullness checks and the controllers see, taken before a change so
+// its effects can be applied afterwards
+pub struct ServiceState {
+    out_of_service: Vec<Vec<bool>>,
+    floors_full: Vec<bool>,
+    lot_full: bool,
+}
+
+impl ServiceState {
+    pub fn of(database: &Database) -> Result<Self, rusqlite::Error> {
+        let mut out_of_service = Vec::with_capacity(3);
+        let mut floors_full = Vec::with_capacity(3);
+
+        for floor_number in 0..3 {
+            out_of_service.push(database.get_floor(floor_number)?.out_of_service_vec());
+            floors_full.push(database.floor_is_full(floor_number)?);
+        }
+
+        Ok(Self {
+            out_of_service,
+            floors_full,
+            lot_full: database.parking_lot_is_full()?,
+        })
+    }
+}
+
+pub fn set_out_of_service(
+    io: &SocketIo,
+    database: &mut MutexGuard<Database>,
+    request: &SpotOutOfServicePayload,
+) -> Result<SpotMaintenance, MaintenanceError> {
+    validate_spot(request.floor_number, request.spot_number)?;
+
+    let now = Utc::now().timestamp();
+    let starts_at = request.starts_at.unwrap_or(now);
+
+    if request.ends_at.is_some_and(|ends_at| ends_at <= starts_at) {
+        return Err(MaintenanceError::InvalidWindow);
+    }
+
+    let before = ServiceState::of(database)?;
+
+    let maintenance = database.add_spot_maintenance(
+        request.floor_number,
+        request.spot_number,
+        &request.reason,
+        starts_at,
+        request.ends_at,
+        now,
+    )?;
+
+    info!(
+        floor = request.floor_number,
+        spot = request.spot_number,
+        reason = %request.reason,
+        starts_at,
+        ends_at = request.ends_at,
+        "spot taken out of service"
+    );
+
+    apply_committed(io, database, &before);
+
+    Ok(maintenance)
+}
+
+pub fn return_to_service(
+    io: &SocketIo,
+    database: &mut MutexGuard<Database>,
+    floor_number: i32,
+    spot_number: i32,
+) -> Result<usize, MaintenanceError> {
+    validate_spot(floor_number, spot_number)?;
+
+    let before = ServiceState::of(database)?;
+
+    let ended = database.end_spot_maintenance(floor_number, spot_number, Utc::now().timestamp())?;
+
+    info!(
+        floor = floor_number,
+        spot = spot_number,
+        "spot returned to service"
+    );
+
+    apply_committed(io, database, &before);
+
+    Ok(ended)
+}
+
+pub fn list(database: &Database) -> Result<Vec<SpotMaintenancePayload>, MaintenanceError> {
+    Ok(database
+        .get_spot_maintenances(Utc::now().timestamp())?
+        .into_iter()
+        .map(SpotMaintenancePayload::from)
+        .collect())
+}
+
+// Scheduled maintenances start and end on their own, so the spots in service
+// are checked periodically as well
+pub fn spawn(config: &MaintenanceConfig, io: &SocketIo, database: &Arc<Mutex<Database>>) {
+    let check_interval = Duration::from_secs(config.check_interval_secs);
+    let io = io.clone();
+    let database = database.clone();
+
+    tokio::spawn(async move {
+        let mut interval = time::interval(check_interval);
+        let mut last: Option<ServiceState> = None;
+
+        loop {
+            interval.tick().await;
+
+            let mut database = Database::lock(&database);
+
+            // A failed round is retried on the next tick against the same state
+            let checked = ServiceState::of(&database).and_then(|current| match &last {
+                Some(last) if current.out_of_service != last.out_of_service => {
+                    apply(&io, &mut database, last)?;
+                    ServiceState::of(&database)
+                }
+                _ => Ok(current),
+            });
+
+            match checked {
+                Ok(current) => last = Some(current),
+                Err(error) => error!(%error, "failed to check the spots in service"),
+            }
+        }
+    });
+}
+
+// The change is already saved, so a failure to apply it isn't the request's.
+// The periodic check sees the difference and applies it again
+fn apply_committed(io: &SocketIo, database: &mut MutexGuard<Database>, before: &ServiceState) {
+    if let Err(error) = apply(io, database, before) {
+        error!(%error, "failed to apply the change of the spots in service");
+    }
+}
+
+// Tells the controllers which spots to skip, closes the floors and the lot whose
+// last free spot went out of service and reopens them when a spot comes back
+fn apply(
+    io: &SocketIo,
+    database: &mut MutexGuard<Database>,
+    before: &ServiceState,
+) -> Result<(), rusqlite::Error> {
+    let after = ServiceState::of(database)?;
+
+    for floor in ClientId::iter_floors() {
+        let floor_number = floor.to_index();
+        let index = floor_number as usize;
+
+        if before.out_of_service[index] != after.out_of_service[index] {
+            if let Err(error) = io.within(floor.to_string()).emit(
+                SPOTS_OUT_OF_SERVICE_EVENT,
+                vec![after.out_of_service[index].clone()],
+            ) {
+                warn!(floor = %floor, %error, "failed to send the spots out of service");
+            }
+        }
+
+        let is_closed = database.is_floor_closed(floor_number)?;
+
+        if after.floors_full[index] && !before.floors_full[index] && !is_closed {
+            commands::close_floor(io, database, floor);
+        } else if !after.floors_full[index] && before.floors_full[index] && is_closed {
+            commands::open_floor(io, database, floor);
+        }
+    }
+
+    let is_closed = database.is_parking_lot_closed()?;
+
+    if after.lot_full && !before.lot_full && !is_closed {
+        commands::close_parking_lot(io, database);
+    } else if !after.lot_full && before.lot_full && is_closed {
+        commands::open_parking_lot(io, database);
+    }
+
+    // The capacity shown by the app changed either way
+    commands::broadcast_parking_lot_state(io, database);
+
+    Ok(())
+}
+
+fn validate_spot(floor_number: i32, spot_number: i32) -> Result<(), MaintenanceError> {
+    if (0..3).contains(&floor_number) && (0..8).contains(&spot_number) {
+        Ok(())
+    } else {
+        Err(MaintenanceError::InvalidSpot)
+    }
+}
@@ -0,0 +1,11 @@
+use super::parking_lot::SpotType;
+
+// A vehicle stay as recorded in the vehicle and car_exit tables
+pub struct VehicleStay {
+    pub entry_time: i64,
+    pub exit_time: Option<i64>,
+    // Vehicles parked before the spot was recorded have no location
+    pub floor_number: Option<i32>,
+    pub spot_number: Option<i32>,
+    pub spot_type: Option<SpotType>,
+}
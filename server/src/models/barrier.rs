@@ -0,0 +1,8 @@
+// A ground floor barrier stopped by a timeout, kept until it completes a cycle again
+#[derive(Clone)]
+pub struct BarrierFault {
+    pub barrier: String,
+    pub fault: String,
+    // Controller time of the fault
+    pub since: i64,
+}
@@ -0,0 +1,20 @@
+// Estimated difference between a controller clock and the server clock,
+// positive when the controller clock is ahead
+#[derive(Clone, Copy)]
+pub struct ClockOffset {
+    pub offset_millis: i64,
+    pub round_trip_millis: i64,
+    pub measured_at: i64,
+    pub skewed: bool,
+}
+
+// When an event happened, as recorded by the server
+#[derive(Clone, Copy)]
+pub struct EventTime {
+    // Controller timestamp, corrected by the estimated offset when configured to
+    pub timestamp: i64,
+    // Server clock when the event was received
+    pub received_at: i64,
+    // The controller clock was beyond the allowed offset when the event was recorded
+    pub clock_skewed: bool,
+}
@@ -0,0 +1,28 @@
+use super::receipt::PaymentMethod;
+
+// Revenue of the exits of one spot type or payment method
+#[derive(Clone)]
+pub struct ClosingTotal<T> {
+    pub key: T,
+    pub vehicles: i64,
+    pub total: f64,
+}
+
+// The immutable record of a closed business day
+#[derive(Clone)]
+pub struct CashClosing {
+    pub id: i64,
+    // yyyy-mm-dd in the closing timezone
+    pub business_date: String,
+    // [starts_at, ends_at) window of the exits
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub closed_at: i64,
+    pub vehicles: i64,
+    pub total: f64,
+    // Spot type as stored, 0 normal, 1 handicapped, 2 elderly
+    pub spot_types: Vec<ClosingTotal<i32>>,
+    pub payment_methods: Vec<ClosingTotal<PaymentMethod>>,
+    // Vehicles that entered before the end of the day and were still inside
+    pub vehicles_inside: i64,
+}
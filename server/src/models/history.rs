@@ -0,0 +1,47 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryResolution {
+    // One snapshot every snapshot_interval_secs
+    Raw = 0,
+    // Raw snapshots older than the raw retention, averaged per hour
+    Hourly = 1,
+}
+
+impl FromSql for HistoryResolution {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let number = value.as_i64()?;
+
+        match number {
+            0 => Ok(HistoryResolution::Raw),
+            1 => Ok(HistoryResolution::Hourly),
+            _ => Err(FromSqlError::OutOfRange(number)),
+        }
+    }
+}
+
+// Occupancy of one spot type of one floor at a point in time
+pub struct OccupancySample {
+    pub floor_number: i32,
+    pub spot_type: i32,
+    pub occupied: i32,
+    pub capacity: i32,
+    pub floor_closed: bool,
+    pub lot_closed: bool,
+}
+
+// A row of the occupancy_snapshot table
+pub struct OccupancySnapshot {
+    pub taken_at: i64,
+    pub resolution: HistoryResolution,
+    pub floor_number: i32,
+    pub spot_type: i32,
+    // Average over the hour for the hourly snapshots
+    pub occupied: f64,
+    pub capacity: i32,
+    // Closed at any moment of the hour for the hourly snapshots
+    pub floor_closed: bool,
+    pub lot_closed: bool,
+}
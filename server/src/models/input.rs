@@ -0,0 +1,16 @@
+// Edges a controller filtered out of one of its inputs, as last reported
+#[derive(Clone)]
+pub struct InputGlitches {
+    pub pin: String,
+    // Pulses shorter than the stable time
+    pub glitches: u64,
+    // Edges merged into the one that fired just before
+    pub coalesced: u64,
+}
+
+// Last report of a controller, the counters restart with it
+#[derive(Clone)]
+pub struct ControllerInputs {
+    pub received_at: i64,
+    pub pins: Vec<InputGlitches>,
+}
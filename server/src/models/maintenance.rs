@@ -0,0 +1,11 @@
+// A period in which a spot is out of service, e.g. broken sensor or construction
+#[derive(Clone)]
+pub struct SpotMaintenance {
+    pub id: i64,
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub reason: String,
+    pub starts_at: i64,
+    // None keeps the spot out of service until it is returned manually
+    pub ends_at: Option<i64>,
+}
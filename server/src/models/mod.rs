@@ -1,2 +1,14 @@
+pub mod analytics;
+pub mod barrier;
 pub mod client;
+pub mod clock;
+pub mod closing;
+pub mod history;
+pub mod input;
+pub mod maintenance;
 pub mod parking_lot;
+pub mod receipt;
+pub mod retention;
+pub mod sensor_report;
+pub mod subscriber;
+pub mod webhook;
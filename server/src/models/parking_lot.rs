@@ -1,3 +1,4 @@
+use super::{maintenance::SpotMaintenance, subscriber::Subscription};
 use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
 
 pub struct Floor {
@@ -12,12 +13,36 @@ impl Floor {
             .map(|spot| spot.parked_vehicle.is_some())
             .collect()
     }
+
+    // Flags of the spots the controller must not scan
+    pub fn out_of_service_vec(&self) -> Vec<bool> {
+        self.spots
+            .iter()
+            .map(|spot| spot.out_of_service.is_some())
+            .collect()
+    }
+
+    // Spots that can take a vehicle, the ones out of service don't count
+    pub fn capacity(&self) -> usize {
+        self.spots
+            .iter()
+            .filter(|spot| spot.out_of_service.is_none())
+            .count()
+    }
+
+    pub fn occupied(&self) -> usize {
+        self.spots
+            .iter()
+            .filter(|spot| spot.out_of_service.is_none() && spot.parked_vehicle.is_some())
+            .count()
+    }
 }
 
 pub struct Spot {
     pub spot_number: i32,
     pub spot_type: SpotType,
     pub parked_vehicle: Option<Vehicle>,
+    pub out_of_service: Option<SpotMaintenance>,
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -53,4 +78,16 @@ impl FromSql for SpotType {
 pub struct Vehicle {
     pub id: i32,
     pub entry_time: i64,
+    pub clock_skewed: bool,
+    // Set once the vehicle stays longer than its spot allows
+    pub overstay: Option<Overstay>,
+    // Set when the vehicle belongs to a subscriber
+    pub subscription: Option<Subscription>,
+}
+
+// The overstay rule a vehicle broke
+#[derive(Clone, Copy)]
+pub struct Overstay {
+    pub max_minutes: i64,
+    pub surcharge_per_minute: f64,
 }
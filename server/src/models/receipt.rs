@@ -0,0 +1,94 @@
+use super::{parking_lot::Overstay, subscriber::Subscription};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use serde::{Deserialize, Serialize};
+
+// A vehicle that left the lot, what its receipt is issued from
+pub struct VehicleExit {
+    pub vehicle_id: i32,
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub entry_time: i64,
+    pub exit_time: i64,
+    pub clock_skewed: bool,
+    pub overstay: Option<Overstay>,
+    pub subscription: Option<Subscription>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptLineKind {
+    Charge = 0,
+    // Amount is negative
+    Discount = 1,
+}
+
+impl FromSql for ReceiptLineKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let number = value.as_i64()?;
+
+        match number {
+            0 => Ok(ReceiptLineKind::Charge),
+            1 => Ok(ReceiptLineKind::Discount),
+            _ => Err(FromSqlError::OutOfRange(number)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentMethod {
+    Cash = 0,
+    Card = 1,
+    Pix = 2,
+    // Billed with the subscriber contract, not paid at the exit
+    Subscription = 3,
+}
+
+impl FromSql for PaymentMethod {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let number = value.as_i64()?;
+
+        match number {
+            0 => Ok(PaymentMethod::Cash),
+            1 => Ok(PaymentMethod::Card),
+            2 => Ok(PaymentMethod::Pix),
+            3 => Ok(PaymentMethod::Subscription),
+            _ => Err(FromSqlError::OutOfRange(number)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ReceiptLine {
+    pub kind: ReceiptLineKind,
+    pub description: String,
+    // Minutes charged
+    pub quantity: i64,
+    pub unit_price: f64,
+    pub amount: f64,
+}
+
+// What a stay costs and how it is paid, priced from its VehicleExit
+pub struct Charge {
+    pub lines: Vec<ReceiptLine>,
+    pub total: f64,
+    pub payment_method: PaymentMethod,
+}
+
+#[derive(Clone)]
+pub struct Receipt {
+    // Sequential, starting at 1
+    pub number: i64,
+    pub vehicle_id: i32,
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub entry_time: i64,
+    pub exit_time: i64,
+    pub duration_minutes: i64,
+    pub clock_skewed: bool,
+    pub subscriber_id: Option<i64>,
+    pub lines: Vec<ReceiptLine>,
+    pub total: f64,
+    pub payment_method: PaymentMethod,
+    pub issued_at: i64,
+}
@@ -0,0 +1,26 @@
+// Totals of one spot type of one floor in one local day, kept after the raw
+// vehicles are pruned
+pub struct DailySummary {
+    // yyyy-mm-dd
+    pub date: String,
+    pub floor_number: i32,
+    pub spot_type: i32,
+    // Arrivals count on the day of the entry, departures on the day of the exit
+    pub arrivals: i64,
+    pub departures: i64,
+    pub dwell_minutes: i64,
+    pub revenue: f64,
+}
+
+// How big the database is and what is taking the space
+pub struct StorageReport {
+    pub size_bytes: i64,
+    // Freed by deletions and not given back to the file system until a VACUUM
+    pub free_bytes: i64,
+    pub vehicles: i64,
+    pub exits: i64,
+    pub receipts: i64,
+    pub occupancy_snapshots: i64,
+    pub summarized_days: i64,
+    pub oldest_exit_time: Option<i64>,
+}
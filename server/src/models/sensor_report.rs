@@ -0,0 +1,16 @@
+// Something a floor saw that its spots and its gates or ramp don't agree on, kept
+// for the operator to find the failing sensor
+#[derive(Clone)]
+pub struct SensorReport {
+    pub id: i64,
+    pub floor_number: i32,
+    // The kind of spot mismatch, or "ambiguous_passage"
+    pub kind: String,
+    // Only for the mismatches of a spot
+    pub parking_space: Option<i32>,
+    // Only for the ambiguous passages
+    pub first_sensor: Option<String>,
+    // Controller clock, when the floor saw it
+    pub timestamp: i64,
+    pub received_at: i64,
+}
@@ -0,0 +1,34 @@
+// A monthly pass holder, recognized by the plate or card read at the entry
+#[derive(Clone)]
+pub struct Subscriber {
+    pub id: i64,
+    // Plate or card id
+    pub credential: String,
+    pub name: String,
+    pub valid_from: i64,
+    pub valid_until: i64,
+    // Empty allows every floor
+    pub allowed_floors: Vec<i32>,
+    // (floor_number, spot_number) kept for this subscriber
+    pub reserved_spot: Option<(i32, i32)>,
+    // Contract price per minute, 0 exempts the subscriber from paying
+    pub rate_per_minute: f64,
+}
+
+impl Subscriber {
+    pub fn is_valid_at(&self, timestamp: i64) -> bool {
+        self.valid_from <= timestamp && timestamp < self.valid_until
+    }
+
+    pub fn allows_floor(&self, floor_number: i32) -> bool {
+        self.allowed_floors.is_empty() || self.allowed_floors.contains(&floor_number)
+    }
+}
+
+// The contract a parked vehicle is billed by, copied to the vehicle when it is
+// recognized so later changes to the subscriber don't affect it
+#[derive(Clone, Copy)]
+pub struct Subscription {
+    pub subscriber_id: i64,
+    pub rate_per_minute: f64,
+}
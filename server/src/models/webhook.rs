@@ -0,0 +1,14 @@
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub event: String,
+    pub url: String,
+    pub body: String,
+    pub attempts: i32,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum DeliveryStatus {
+    Pending = 0,
+    Delivered = 1,
+    Failed = 2,
+}
@@ -0,0 +1,54 @@
+use crate::{config::MqttConfig, database::Database, models::client::ClientId, socket::commands};
+use socketioxide::SocketIo;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+// Maps the command topics onto the same operations the app can trigger:
+//   <prefix>/command/lot/open
+//   <prefix>/command/lot/close
+//   <prefix>/command/floor/<floor_number>/open
+//   <prefix>/command/floor/<floor_number>/close
+// The payload is ignored
+pub fn handle(
+    config: &MqttConfig,
+    io: &SocketIo,
+    database: &Arc<Mutex<Database>>,
+    topic: &str,
+    _payload: &[u8],
+) {
+    let command_prefix = format!("{}/command/", config.topic_prefix);
+
+    let Some(command) = topic.strip_prefix(&command_prefix) else {
+        return;
+    };
+
+    let parts: Vec<&str> = command.split('/').collect();
+    let mut database = database.lock().unwrap();
+
+    match parts.as_slice() {
+        ["lot", "open"] => commands::open_parking_lot(io, &mut database),
+        ["lot", "close"] => commands::close_parking_lot(io, &mut database),
+        ["floor", floor_number, action] => {
+            let floor = floor_number
+                .parse()
+                .ok()
+                .and_then(ClientId::from_index)
+                .filter(|floor| *floor != ClientId::App);
+
+            match (floor, *action) {
+                (Some(floor), "open") => commands::open_floor(io, &mut database, floor),
+                (Some(floor), "close") => commands::close_floor(io, &mut database, floor),
+                _ => {
+                    warn!(topic, "ignoring invalid MQTT command");
+                    return;
+                }
+            }
+        }
+        _ => {
+            warn!(topic, "ignoring unknown MQTT command");
+            return;
+        }
+    }
+
+    info!(command, "executed MQTT command");
+}
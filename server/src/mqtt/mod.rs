@@ -0,0 +1,214 @@
+mod commands;
+mod publisher;
+
+use crate::{config::MqttConfig, database::Database};
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS};
+use socketioxide::SocketIo;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{sync::Notify, time};
+use tracing::{error, info, warn};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+pub fn spawn(config: &MqttConfig, database: &Arc<Mutex<Database>>, io: &SocketIo) {
+    if !config.enabled {
+        return;
+    }
+
+    let status_topic = format!("{}/status", config.topic_prefix);
+
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(config.keep_alive_secs));
+    options.set_last_will(LastWill::new(
+        &status_topic,
+        "offline",
+        QoS::AtLeastOnce,
+        true,
+    ));
+
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        options.set_credentials(username, password);
+    }
+
+    let (client, mut eventloop) = AsyncClient::new(options, 64);
+
+    // Signals the publisher that the broker (re)accepted the connection and
+    // every retained topic has to be published again
+    let connected = Arc::new(Notify::new());
+
+    tokio::spawn(publisher::run(
+        config.clone(),
+        client.clone(),
+        database.clone(),
+        connected.clone(),
+    ));
+
+    let config = config.clone();
+    let database = database.clone();
+    let io = io.clone();
+
+    tokio::spawn(async move {
+        let command_topic = format!("{}/command/#", config.topic_prefix);
+
+        loop {
+            match eventloop.poll().await {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!(host = %config.host, port = config.port, "connected to the MQTT broker");
+
+                    // The event loop must keep being polled, so only the non blocking calls are used here
+                    if let Err(error) =
+                        client.try_publish(&status_topic, QoS::AtLeastOnce, true, "online")
+                    {
+                        error!(topic = %status_topic, %error, "failed to publish to MQTT");
+                    }
+
+                    if config.commands {
+                        if let Err(error) = client.try_subscribe(&command_topic, QoS::AtLeastOnce) {
+                            error!(topic = %command_topic, %error, "failed to subscribe to MQTT topic");
+                        }
+                    }
+
+                    connected.notify_one();
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    commands::handle(&config, &io, &database, &publish.topic, &publish.payload);
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    warn!(%error, "MQTT connection error, retrying");
+                    time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{events::LotEvent, socket::payloads::VehicleMovementPayload};
+    use bytes::BytesMut;
+    use rumqttc::mqttbytes::{
+        v4::{read, ConnAck, ConnectReturnCode, PingResp, PubAck, Publish, SubAck},
+        Error as PacketError,
+    };
+    use rumqttc::SubscribeReasonCode;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    };
+
+    // Accepts one client and answers just enough of MQTT 3.1.1 for it to publish,
+    // handing every publish to the test
+    async fn broker(listener: TcpListener, published: UnboundedSender<Publish>) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buffer = BytesMut::new();
+
+        loop {
+            let packet = match read(&mut buffer, 1024 * 1024) {
+                Ok(packet) => packet,
+                Err(PacketError::InsufficientBytes(_)) => {
+                    if stream.read_buf(&mut buffer).await.unwrap() == 0 {
+                        return;
+                    }
+
+                    continue;
+                }
+                Err(error) => panic!("invalid packet from the client: {:?}", error),
+            };
+
+            let mut reply = BytesMut::new();
+
+            match packet {
+                Packet::Connect(_) => {
+                    ConnAck::new(ConnectReturnCode::Success, false)
+                        .write(&mut reply)
+                        .unwrap();
+                }
+                Packet::Publish(publish) => {
+                    if publish.qos != QoS::AtMostOnce {
+                        PubAck::new(publish.pkid).write(&mut reply).unwrap();
+                    }
+
+                    published.send(publish).ok();
+                }
+                Packet::Subscribe(subscribe) => {
+                    let codes = vec![SubscribeReasonCode::Success(QoS::AtLeastOnce)];
+                    SubAck::new(subscribe.pkid, codes)
+                        .write(&mut reply)
+                        .unwrap();
+                }
+                Packet::PingReq => {
+                    PingResp.write(&mut reply).unwrap();
+                }
+                _ => {}
+            }
+
+            stream.write_all(&reply).await.unwrap();
+        }
+    }
+
+    async fn next_on(published: &mut UnboundedReceiver<Publish>, topic: &str) -> Publish {
+        time::timeout(Duration::from_secs(5), async {
+            loop {
+                let publish = published.recv().await.unwrap();
+
+                if publish.topic == topic {
+                    return publish;
+                }
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("nothing published on {}", topic))
+    }
+
+    #[tokio::test]
+    async fn publishes_the_state_and_the_movements() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = MqttConfig {
+            enabled: true,
+            port: listener.local_addr().unwrap().port(),
+            ..MqttConfig::default()
+        };
+        let (sender, mut published) = mpsc::unbounded_channel();
+
+        tokio::spawn(broker(listener, sender));
+
+        let database = Database::in_memory();
+        let (_, io) = SocketIo::new_layer();
+
+        spawn(&config, &database, &io);
+
+        let status = next_on(&mut published, "parking/status").await;
+        assert_eq!(&status.payload[..], b"online");
+        assert!(status.retain);
+
+        let lot = next_on(&mut published, "parking/lot").await;
+        assert_eq!(
+            &lot.payload[..],
+            br#"{"is_closed":false,"occupied":0,"capacity":24}"#
+        );
+        assert!(lot.retain);
+
+        database
+            .lock()
+            .unwrap()
+            .events
+            .publish(LotEvent::CarArrived(VehicleMovementPayload {
+                floor_number: 1,
+                spot_number: 3,
+                timestamp: 100,
+            }));
+
+        let arrival = next_on(&mut published, "parking/events/car_arrived").await;
+        assert_eq!(
+            &arrival.payload[..],
+            br#"{"floor_number":1,"spot_number":3,"timestamp":100}"#
+        );
+        assert!(!arrival.retain);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{
+    config::MqttConfig,
+    database::Database,
+    events::LotEvent,
+    socket::payloads::{ParkingLotDataPayload, SpotDataPayload, VehicleMovementPayload},
+};
+use rumqttc::{AsyncClient, QoS};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{broadcast::error::RecvError, Notify};
+use tracing::{error, warn};
+
+#[derive(Serialize)]
+struct AreaStatePayload {
+    is_closed: bool,
+    occupied: usize,
+    capacity: usize,
+}
+
+pub async fn run(
+    config: MqttConfig,
+    client: AsyncClient,
+    database: Arc<Mutex<Database>>,
+    connected: Arc<Notify>,
+) {
+    let mut receiver = Database::lock(&database).events.subscribe();
+
+    // Last payload published on each retained topic, used to only publish what changed
+    let mut retained: HashMap<String, String> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            _ = connected.notified() => {
+                // The broker may have lost the retained messages, publish everything again
+                retained.clear();
+
+                // Published again with the next change if it can't be read now
+                let parking_lot = Database::lock(&database).get_parking_lot_state();
+
+                match parking_lot {
+                    Ok(parking_lot) => {
+                        publish_state(&config, &client, &mut retained, &parking_lot).await;
+                    }
+                    Err(error) => error!(%error, "failed to read the parking lot state for MQTT"),
+                }
+            }
+            event = receiver.recv() => match event {
+                Ok(LotEvent::LotStateChanged(parking_lot)) => {
+                    publish_state(&config, &client, &mut retained, &parking_lot).await;
+                }
+                Ok(LotEvent::CarArrived(movement)) => {
+                    publish_movement(&config, &client, "car_arrived", &movement).await;
+                }
+                Ok(LotEvent::CarDeparted(movement)) => {
+                    publish_movement(&config, &client, "car_departed", &movement).await;
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "MQTT publisher fell behind, events were skipped");
+                }
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+}
+
+async fn publish_state(
+    config: &MqttConfig,
+    client: &AsyncClient,
+    retained: &mut HashMap<String, String>,
+    parking_lot: &ParkingLotDataPayload,
+) {
+    let prefix = &config.topic_prefix;
+    let mut messages = Vec::new();
+
+    push_json(
+        &mut messages,
+        format!("{}/lot", prefix),
+        &AreaStatePayload {
+            is_closed: parking_lot.is_closed,
+            occupied: parking_lot
+                .floors
+                .iter()
+                .map(|floor| count_occupied(&floor.spots))
+                .sum(),
+            capacity: parking_lot
+                .floors
+                .iter()
+                .map(|floor| count_in_service(&floor.spots))
+                .sum(),
+        },
+    );
+
+    for (floor_number, floor) in parking_lot.floors.iter().enumerate() {
+        push_json(
+            &mut messages,
+            format!("{}/floor/{}", prefix, floor_number),
+            &AreaStatePayload {
+                is_closed: floor.is_closed,
+                occupied: count_occupied(&floor.spots),
+                capacity: count_in_service(&floor.spots),
+            },
+        );
+
+        for (spot_number, spot) in floor.spots.iter().enumerate() {
+            push_json(
+                &mut messages,
+                format!("{}/floor/{}/spot/{}", prefix, floor_number, spot_number),
+                spot,
+            );
+        }
+    }
+
+    for (topic, payload) in messages {
+        if retained.get(&topic) == Some(&payload) {
+            continue;
+        }
+
+        match client
+            .publish(&topic, QoS::AtLeastOnce, true, payload.clone())
+            .await
+        {
+            Ok(()) => {
+                retained.insert(topic, payload);
+            }
+            Err(error) => error!(topic, %error, "failed to publish to MQTT"),
+        }
+    }
+}
+
+async fn publish_movement(
+    config: &MqttConfig,
+    client: &AsyncClient,
+    event: &str,
+    movement: &VehicleMovementPayload,
+) {
+    let topic = format!("{}/events/{}", config.topic_prefix, event);
+    let payload = match serde_json::to_string(movement) {
+        Ok(payload) => payload,
+        Err(error) => {
+            error!(topic, %error, "failed to serialize the MQTT payload");
+            return;
+        }
+    };
+
+    if let Err(error) = client
+        .publish(&topic, QoS::AtLeastOnce, false, payload)
+        .await
+    {
+        error!(topic, %error, "failed to publish to MQTT");
+    }
+}
+
+fn count_occupied(spots: &[SpotDataPayload]) -> usize {
+    spots
+        .iter()
+        .filter(|spot| spot.parked_vehicle.is_some())
+        .count()
+}
+
+fn count_in_service(spots: &[SpotDataPayload]) -> usize {
+    spots
+        .iter()
+        .filter(|spot| spot.out_of_service.is_none())
+        .count()
+}
+
+// A topic whose payload can't be serialized is skipped, the others still go out
+fn push_json<T: Serialize>(messages: &mut Vec<(String, String)>, topic: String, value: &T) {
+    match serde_json::to_string(value) {
+        Ok(payload) => messages.push((topic, payload)),
+        Err(error) => error!(topic, %error, "failed to serialize the MQTT payload"),
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    config::OverstayConfig,
+    database::Database,
+    events::LotEvent,
+    models::{
+        client::ClientId,
+        parking_lot::{Overstay, Spot, Vehicle},
+    },
+    socket::{
+        commands,
+        constants::VEHICLE_OVERSTAYED_EVENT,
+        payloads::{
+            OverstayPayload, SubscriptionPayload, VehicleDataPayload, VehicleOverstayedPayload,
+        },
+    },
+};
+use chrono::Utc;
+use rusqlite::Error;
+use socketioxide::SocketIo;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time;
+use tracing::{error, warn};
+
+pub fn spawn(config: &OverstayConfig, io: &SocketIo, database: &Arc<Mutex<Database>>) {
+    if !config.is_enabled() {
+        return;
+    }
+
+    let config = config.clone();
+    let io = io.clone();
+    let database = database.clone();
+
+    tokio::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(config.check_interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            let mut database = database.lock().unwrap();
+
+            let overstayed = match check(&config, &mut database, Utc::now().timestamp()) {
+                Ok(overstayed) => overstayed,
+                Err(error) => {
+                    error!(%error, "failed to check the overstayed vehicles");
+                    continue;
+                }
+            };
+
+            for payload in &overstayed {
+                if let Err(error) = io
+                    .within(ClientId::App.to_string())
+                    .emit(VEHICLE_OVERSTAYED_EVENT, payload.clone())
+                {
+                    warn!(%error, "failed to send the overstay alert");
+                }
+
+                database
+                    .events
+                    .publish(LotEvent::VehicleOverstayed(payload.clone()));
+            }
+
+            // The app computes the fees, including the surcharge, from the state
+            if !overstayed.is_empty() {
+                commands::broadcast_parking_lot_state(&io, &database);
+            }
+        }
+    });
+}
+
+// Flags the vehicles that just went past the limit of their spot type. The rule
+// is stored with the vehicle, so each one is reported only once
+fn check(
+    config: &OverstayConfig,
+    database: &mut Database,
+    now: i64,
+) -> Result<Vec<VehicleOverstayedPayload>, Error> {
+    let mut overstayed = Vec::new();
+
+    for floor_number in 0..3 {
+        for spot in database.get_floor(floor_number)?.spots {
+            let Some(vehicle) = spot.parked_vehicle.as_ref() else {
+                continue;
+            };
+
+            let Some(max_minutes) = config.max_minutes_for(spot.spot_type) else {
+                continue;
+            };
+
+            if vehicle.overstay.is_some() || (now - vehicle.entry_time) / 60 < max_minutes {
+                continue;
+            }
+
+            let overstay = Overstay {
+                max_minutes,
+                surcharge_per_minute: config.surcharge_per_minute,
+            };
+
+            database.mark_vehicle_overstayed(vehicle.id, &overstay)?;
+
+            let payload = overstayed_payload(floor_number, &spot, vehicle, overstay, now);
+
+            warn!(
+                floor = floor_number,
+                spot = spot.spot_number,
+                vehicle_id = vehicle.id,
+                parked_minutes = payload.parked_minutes,
+                max_minutes,
+                "vehicle overstayed"
+            );
+
+            overstayed.push(payload);
+        }
+    }
+
+    Ok(overstayed)
+}
+
+// Vehicles still parked that went past their limit
+pub fn list(database: &Database) -> Result<Vec<VehicleOverstayedPayload>, Error> {
+    let now = Utc::now().timestamp();
+    let mut overstayed = Vec::new();
+
+    for floor_number in 0..3 {
+        for spot in database.get_floor(floor_number)?.spots {
+            let Some(vehicle) = spot.parked_vehicle.as_ref() else {
+                continue;
+            };
+
+            if let Some(overstay) = vehicle.overstay {
+                overstayed.push(overstayed_payload(
+                    floor_number,
+                    &spot,
+                    vehicle,
+                    overstay,
+                    now,
+                ));
+            }
+        }
+    }
+
+    Ok(overstayed)
+}
+
+fn overstayed_payload(
+    floor_number: i32,
+    spot: &Spot,
+    vehicle: &Vehicle,
+    overstay: Overstay,
+    now: i64,
+) -> VehicleOverstayedPayload {
+    VehicleOverstayedPayload {
+        floor_number,
+        spot_number: spot.spot_number,
+        spot_type: spot.spot_type as i32,
+        vehicle: VehicleDataPayload {
+            id: vehicle.id,
+            entry_time: vehicle.entry_time,
+            exit_time: None,
+            clock_skewed: vehicle.clock_skewed,
+            overstay: Some(OverstayPayload::from(overstay)),
+            subscription: vehicle.subscription.map(SubscriptionPayload::from),
+            receipt_number: None,
+            total: None,
+        },
+        parked_minutes: (now - vehicle.entry_time) / 60,
+        max_minutes: overstay.max_minutes,
+    }
+}
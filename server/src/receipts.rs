@@ -0,0 +1,211 @@
+use crate::{
+    database::Database,
+    models::{
+        clock::EventTime,
+        receipt::{Charge, PaymentMethod, Receipt, ReceiptLineKind, VehicleExit},
+    },
+    socket::payloads::ReceiptDocumentPayload,
+    tariff,
+};
+use chrono::{DateTime, Local, Utc};
+use rusqlite::Error;
+use std::fmt::{self, Display, Formatter};
+use tracing::info;
+
+// Columns of a 80mm thermal printer with the default font
+const RECEIPT_WIDTH: usize = 40;
+
+pub enum ReceiptError {
+    NotFound,
+    DayClosed,
+    NotSubscriber,
+    Database(Error),
+}
+
+impl From<Error> for ReceiptError {
+    fn from(error: Error) -> Self {
+        Self::Database(error)
+    }
+}
+
+impl Display for ReceiptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "there is no such receipt"),
+            Self::DayClosed => write!(f, "the business day of the receipt was already closed"),
+            Self::NotSubscriber => write!(f, "only subscribers can be billed by their contract"),
+            Self::Database(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+// Records the exit of the vehicle parked in the spot and charges its stay
+pub fn depart(
+    database: &mut Database,
+    floor_number: i32,
+    spot_number: i32,
+    exit: EventTime,
+) -> Result<Receipt, Error> {
+    let receipt = database.unpark_vehicle(
+        floor_number,
+        spot_number,
+        exit,
+        Utc::now().timestamp(),
+        charge,
+    )?;
+
+    info!(
+        receipt_number = receipt.number,
+        vehicle_id = receipt.vehicle_id,
+        total = receipt.total,
+        "receipt issued"
+    );
+
+    Ok(receipt)
+}
+
+fn charge(exit: &VehicleExit) -> Charge {
+    let lines = tariff::charges(exit);
+    let total = tariff::total(&lines);
+
+    // Subscribers are billed by the contract, everyone else pays at the exit and
+    // the operator can change it to what was actually used
+    let payment_method = if exit.subscription.is_some() {
+        PaymentMethod::Subscription
+    } else {
+        PaymentMethod::Cash
+    };
+
+    Charge {
+        lines,
+        total,
+        payment_method,
+    }
+}
+
+pub fn set_payment_method(
+    database: &mut Database,
+    number: i64,
+    payment_method: PaymentMethod,
+) -> Result<Receipt, ReceiptError> {
+    let Some(mut receipt) = database.get_receipt(number)? else {
+        return Err(ReceiptError::NotFound);
+    };
+
+    // The closing already counted the receipt with its payment method
+    if database.is_closed_at(receipt.exit_time)? {
+        return Err(ReceiptError::DayClosed);
+    }
+
+    if payment_method == PaymentMethod::Subscription && receipt.subscriber_id.is_none() {
+        return Err(ReceiptError::NotSubscriber);
+    }
+
+    database.set_receipt_payment_method(number, payment_method)?;
+    receipt.payment_method = payment_method;
+
+    info!(receipt_number = number, "receipt payment method changed");
+
+    Ok(receipt)
+}
+
+pub fn document(database: &Database, number: i64) -> Result<ReceiptDocumentPayload, Error> {
+    let receipt = database.get_receipt(number)?;
+
+    Ok(ReceiptDocumentPayload {
+        number,
+        text: receipt.as_ref().map(text),
+        receipt: receipt.map(Into::into),
+    })
+}
+
+// Plain ASCII, most thermal printers have no UTF-8 code page
+pub fn text(receipt: &Receipt) -> String {
+    let separator = "-".repeat(RECEIPT_WIDTH);
+    let double_separator = "=".repeat(RECEIPT_WIDTH);
+
+    let mut lines = vec![
+        double_separator.clone(),
+        format!(
+            "{:^width$}",
+            "ESTACIONAMENTO - RECIBO",
+            width = RECEIPT_WIDTH
+        ),
+        double_separator.clone(),
+        two_columns("Recibo No", &format!("{:06}", receipt.number)),
+        two_columns("Ticket", &receipt.vehicle_id.to_string()),
+        two_columns(
+            "Vaga",
+            &format!("{}.{}", receipt.floor_number, receipt.spot_number),
+        ),
+        two_columns("Entrada", &format_time(receipt.entry_time)),
+        two_columns("Saida", &format_time(receipt.exit_time)),
+        two_columns(
+            "Permanencia",
+            &format!(
+                "{}h{:02}",
+                receipt.duration_minutes / 60,
+                receipt.duration_minutes % 60
+            ),
+        ),
+    ];
+
+    if let Some(subscriber_id) = receipt.subscriber_id {
+        lines.push(two_columns("Assinante", &subscriber_id.to_string()));
+    }
+
+    lines.push(separator.clone());
+
+    for line in &receipt.lines {
+        let description = match line.kind {
+            ReceiptLineKind::Charge => line.description.clone(),
+            ReceiptLineKind::Discount => format!("{} (desconto)", line.description),
+        };
+
+        lines.push(description);
+        lines.push(two_columns(
+            &format!("  {} min x R$ {:.2}", line.quantity, line.unit_price),
+            &format!("R$ {:.2}", line.amount),
+        ));
+    }
+
+    lines.push(separator);
+    lines.push(two_columns("TOTAL", &format!("R$ {:.2}", receipt.total)));
+    lines.push(two_columns(
+        "Pagamento",
+        payment_method_name(receipt.payment_method),
+    ));
+    lines.push(double_separator);
+    lines.push(format!("Emitido em {}", format_time(receipt.issued_at)));
+
+    if receipt.clock_skewed {
+        lines.push("* Horario sujeito a ajuste de relogio".to_string());
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn payment_method_name(payment_method: PaymentMethod) -> &'static str {
+    match payment_method {
+        PaymentMethod::Cash => "Dinheiro",
+        PaymentMethod::Card => "Cartao",
+        PaymentMethod::Pix => "Pix",
+        PaymentMethod::Subscription => "Assinatura",
+    }
+}
+
+fn two_columns(left: &str, right: &str) -> String {
+    let padding = RECEIPT_WIDTH
+        .saturating_sub(left.len() + right.len())
+        .max(1);
+
+    format!("{}{}{}", left, " ".repeat(padding), right)
+}
+
+fn format_time(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .unwrap()
+        .with_timezone(&Local)
+        .format("%d/%m/%Y %H:%M")
+        .to_string()
+}
@@ -0,0 +1,282 @@
+use crate::{
+    config::{HistoryConfig, RetentionConfig},
+    database::Database,
+    inputs,
+    models::history::HistoryResolution,
+    socket::payloads::{DailySummaryPayload, DateRangePayload, StorageReportPayload},
+};
+use chrono::Utc;
+use rusqlite::Error;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time;
+use tracing::{debug, error, info};
+
+const SECONDS_PER_HOUR: i64 = 3600;
+const SECONDS_PER_DAY: i64 = 24 * SECONDS_PER_HOUR;
+
+// Pause between two batches, so the lot events get the database in between
+const BATCH_PAUSE: Duration = Duration::from_millis(50);
+
+pub fn spawn(config: &RetentionConfig, history: &HistoryConfig, database: &Arc<Mutex<Database>>) {
+    if config.interval_secs == 0 {
+        return;
+    }
+
+    let config = config.clone();
+    let history = history.clone();
+    let database = database.clone();
+
+    tokio::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(config.interval_secs));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(error) = run(&config, &history, &database).await {
+                error!(%error, "failed to apply the data retention");
+            }
+        }
+    });
+}
+
+// Summarizes the new arrivals and departures per day, then prunes the vehicles,
+// webhook deliveries, sensor and input reports and hourly snapshots past their
+// retention, and reclaims the space once enough of it is free
+async fn run(
+    config: &RetentionConfig,
+    history: &HistoryConfig,
+    database: &Arc<Mutex<Database>>,
+) -> Result<(), Error> {
+    let now = Utc::now().timestamp();
+
+    let arrivals = in_batches(config, database, |database| {
+        database.summarize_arrivals(now, config.batch_size)
+    })
+    .await?;
+
+    let departures = in_batches(config, database, |database| {
+        database.summarize_departures(now, config.batch_size)
+    })
+    .await?;
+
+    let pruned = if config.vehicle_days > 0 {
+        let before = now - config.vehicle_days * SECONDS_PER_DAY;
+
+        in_batches(config, database, |database| {
+            database.prune_vehicles(before, config.batch_size)
+        })
+        .await?
+    } else {
+        0
+    };
+
+    let deliveries = if config.webhook_days > 0 {
+        let before = now - config.webhook_days * SECONDS_PER_DAY;
+
+        in_batches(config, database, |database| {
+            database.prune_webhook_deliveries(before, config.batch_size)
+        })
+        .await?
+    } else {
+        0
+    };
+
+    let sensor_reports = if config.sensor_report_days > 0 {
+        let before = now - config.sensor_report_days * SECONDS_PER_DAY;
+
+        in_batches(config, database, |database| {
+            database.prune_sensor_reports(before, config.batch_size)
+        })
+        .await?
+    } else {
+        0
+    };
+
+    let snapshots = if history.hourly_retention_days > 0 {
+        let before = now - history.hourly_retention_days * SECONDS_PER_DAY;
+
+        in_batches(config, database, |database| {
+            database.prune_occupancy(HistoryResolution::Hourly, before, config.batch_size)
+        })
+        .await?
+    } else {
+        0
+    };
+
+    // Only one report per controller is kept, in memory
+    let input_reports = if config.input_report_hours > 0 {
+        let before = now - config.input_report_hours * SECONDS_PER_HOUR;
+        inputs::prune(&mut Database::lock(database), before)
+    } else {
+        0
+    };
+
+    if arrivals > 0 || departures > 0 {
+        debug!(arrivals, departures, "daily summaries updated");
+    }
+
+    if pruned > 0 {
+        info!(pruned, "old vehicles pruned");
+    }
+
+    if deliveries > 0 || sensor_reports > 0 || snapshots > 0 || input_reports > 0 {
+        info!(
+            deliveries,
+            sensor_reports,
+            snapshots,
+            input_reports,
+            "old webhook deliveries, sensor reports, snapshots and input reports pruned"
+        );
+    }
+
+    let mut database = Database::lock(database);
+    let report = database.get_storage_report()?;
+
+    if config.vacuum_free_percent > 0
+        && report.free_bytes * 100 >= report.size_bytes * config.vacuum_free_percent
+    {
+        database.vacuum()?;
+
+        let vacuumed = database.get_storage_report()?;
+
+        info!(
+            size_bytes = vacuumed.size_bytes,
+            freed_bytes = report.size_bytes - vacuumed.size_bytes,
+            "database vacuumed"
+        );
+    }
+
+    Ok(())
+}
+
+// Repeats the batch until it handles less than a full one, letting go of the
+// database between them
+async fn in_batches(
+    config: &RetentionConfig,
+    database: &Arc<Mutex<Database>>,
+    mut batch: impl FnMut(&mut Database) -> Result<usize, Error>,
+) -> Result<usize, Error> {
+    let mut total = 0;
+
+    loop {
+        let handled = batch(&mut database.lock().unwrap())?;
+        total += handled;
+
+        if (handled as i64) < config.batch_size {
+            return Ok(total);
+        }
+
+        time::sleep(BATCH_PAUSE).await;
+    }
+}
+
+pub fn daily(
+    database: &Database,
+    range: &DateRangePayload,
+) -> Result<Vec<DailySummaryPayload>, Error> {
+    let from = range.from.as_deref().unwrap_or("0000-01-01");
+    let to = range.to.as_deref().unwrap_or("9999-12-31");
+
+    Ok(database
+        .get_daily_summaries(from, to)?
+        .into_iter()
+        .map(DailySummaryPayload::from)
+        .collect())
+}
+
+pub fn report(database: &Database) -> Result<StorageReportPayload, Error> {
+    Ok(database.get_storage_report()?.into())
+}
+
+pub fn vacuum(database: &mut Database) -> Result<StorageReportPayload, Error> {
+    database.vacuum()?;
+
+    let report = database.get_storage_report()?;
+    info!(size_bytes = report.size_bytes, "database vacuumed");
+
+    Ok(report.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::clock::EventTime, receipts};
+
+    fn at(timestamp: i64) -> EventTime {
+        EventTime {
+            timestamp,
+            received_at: timestamp,
+            clock_skewed: false,
+        }
+    }
+
+    #[test]
+    fn reset_keeps_the_receipts_and_the_daily_summaries() {
+        let database = Database::in_memory();
+        let mut database = database.lock().unwrap();
+        let now = Utc::now().timestamp();
+
+        database.park_vehicle(at(now - 3600), 0, 3, None).unwrap();
+        let first = receipts::depart(&mut database, 0, 3, at(now)).unwrap();
+
+        database.park_vehicle(at(now), 0, 4, None).unwrap();
+        database.reset_parking_lot().unwrap();
+
+        assert!(database.get_receipt(first.number).unwrap().is_some());
+
+        let summaries = database
+            .get_daily_summaries("0000-01-01", "9999-12-31")
+            .unwrap();
+        let arrivals: i64 = summaries.iter().map(|summary| summary.arrivals).sum();
+        let departures: i64 = summaries.iter().map(|summary| summary.departures).sum();
+        assert_eq!((arrivals, departures), (2, 1));
+
+        // The ticket of the receipt isn't handed out again
+        database.park_vehicle(at(now), 0, 3, None).unwrap();
+        let second = receipts::depart(&mut database, 0, 3, at(now + 60)).unwrap();
+        assert!(second.vehicle_id > first.vehicle_id);
+    }
+
+    #[tokio::test]
+    async fn prunes_the_finished_webhook_deliveries_in_batches() {
+        let database = Database::in_memory();
+        let config = RetentionConfig {
+            batch_size: 1,
+            ..Default::default()
+        };
+
+        {
+            let mut database = database.lock().unwrap();
+
+            for _ in 0..3 {
+                database
+                    .enqueue_webhook_delivery("car_arrived", "http://localhost/hook", "{}", 0)
+                    .unwrap();
+            }
+
+            database.mark_webhook_delivered(1).unwrap();
+            database.register_webhook_failure(2, "gone", None).unwrap();
+        }
+
+        let pruned = in_batches(&config, &database, |database| {
+            database.prune_webhook_deliveries(1, config.batch_size)
+        })
+        .await
+        .unwrap();
+        assert_eq!(pruned, 2);
+
+        // The pending one is still delivered
+        let due = database
+            .lock()
+            .unwrap()
+            .get_due_webhook_deliveries(i64::MAX, 10)
+            .unwrap();
+        assert_eq!(
+            due.iter().map(|delivery| delivery.id).collect::<Vec<_>>(),
+            [3]
+        );
+    }
+}
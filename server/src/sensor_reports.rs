@@ -0,0 +1,142 @@
+use crate::{
+    database::Database,
+    events::LotEvent,
+    models::client::ClientId,
+    socket::payloads::{AmbiguousPassagePayload, SensorReportPayload, SpotMismatchPayload},
+};
+use chrono::Utc;
+use rusqlite::Error;
+use tracing::warn;
+
+const AMBIGUOUS_PASSAGE: &str = "ambiguous_passage";
+
+// The controllers count the cars themselves, these only tell the operator which
+// sensor of a spot, a gate or a ramp may be failing
+pub fn record_spot_mismatch(
+    database: &mut Database,
+    client_id: ClientId,
+    payload: &SpotMismatchPayload,
+) -> Result<SensorReportPayload, Error> {
+    warn!(
+        floor = client_id.to_index(),
+        spot = payload.parking_space,
+        kind = payload.kind.as_str(),
+        timestamp = payload.timestamp,
+        "spots and cars going by don't match"
+    );
+
+    record(
+        database,
+        client_id,
+        &payload.kind,
+        payload.parking_space,
+        None,
+        payload.timestamp,
+    )
+}
+
+pub fn record_ambiguous_passage(
+    database: &mut Database,
+    client_id: ClientId,
+    payload: &AmbiguousPassagePayload,
+) -> Result<SensorReportPayload, Error> {
+    warn!(
+        floor = client_id.to_index(),
+        first_sensor = payload.first_sensor.as_str(),
+        timestamp = payload.timestamp,
+        "car seen by only one sensor of the ramp"
+    );
+
+    record(
+        database,
+        client_id,
+        AMBIGUOUS_PASSAGE,
+        None,
+        Some(&payload.first_sensor),
+        payload.timestamp,
+    )
+}
+
+fn record(
+    database: &mut Database,
+    client_id: ClientId,
+    kind: &str,
+    parking_space: Option<i32>,
+    first_sensor: Option<&str>,
+    timestamp: i64,
+) -> Result<SensorReportPayload, Error> {
+    let report: SensorReportPayload = database
+        .insert_sensor_report(
+            client_id.to_index(),
+            kind,
+            parking_space,
+            first_sensor,
+            timestamp,
+            Utc::now().timestamp(),
+        )?
+        .into();
+
+    database
+        .events
+        .publish(LotEvent::SensorReport(report.clone()));
+
+    Ok(report)
+}
+
+// Defaults to the last 24 hours
+pub fn list(
+    database: &Database,
+    from: Option<i64>,
+    to: Option<i64>,
+) -> Result<Vec<SensorReportPayload>, Error> {
+    let to = to.unwrap_or_else(|| Utc::now().timestamp());
+    let from = from.unwrap_or(to - 24 * 3600);
+
+    Ok(database
+        .get_sensor_reports(from, to)?
+        .into_iter()
+        .map(SensorReportPayload::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_and_publishes_the_reports() {
+        let database = Database::in_memory();
+        let mut database = database.lock().unwrap();
+        let mut events = database.events.subscribe();
+
+        let mismatch = SpotMismatchPayload {
+            kind: "entry_without_arrival".to_string(),
+            parking_space: None,
+            timestamp: 100,
+        };
+        let passage = AmbiguousPassagePayload {
+            first_sensor: "pass_through_sensor_1".to_string(),
+            timestamp: 200,
+        };
+
+        record_spot_mismatch(&mut database, ClientId::SecondFloor, &mismatch).unwrap();
+        record_ambiguous_passage(&mut database, ClientId::FirstFloor, &passage).unwrap();
+
+        let reports = list(&database, None, None).unwrap();
+        let kinds: Vec<_> = reports.iter().map(|report| report.kind.as_str()).collect();
+        assert_eq!(kinds, ["ambiguous_passage", "entry_without_arrival"]);
+        assert_eq!(reports[0].floor_number, 1);
+        assert_eq!(
+            reports[0].first_sensor.as_deref(),
+            Some("pass_through_sensor_1")
+        );
+        assert_eq!(reports[1].parking_space, None);
+
+        assert!(
+            matches!(events.try_recv(), Ok(LotEvent::SensorReport(report)) if report.timestamp == 100)
+        );
+        assert!(
+            matches!(events.try_recv(), Ok(LotEvent::SensorReport(report)) if report.timestamp == 200)
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use super::constants::{
+    CLOSE_FLOOR_EVENT, CLOSE_PARKING_LOT_EVENT, LOT_OCCUPANCY_EVENT, OPEN_FLOOR_EVENT,
+    OPEN_PARKING_LOT_EVENT, PARKING_LOT_STATE_EVENT,
+};
+use super::payloads::FloorClosedPayload;
+use crate::{database::Database, events::LotEvent, models::client::ClientId};
+use socketioxide::SocketIo;
+use std::sync::MutexGuard;
+use tracing::info;
+
+// Operator commands, shared by the socket.io handlers and by the integrations
+// that don't have a socket of their own (e.g. the MQTT bridge)
+
+pub fn close_parking_lot(io: &SocketIo, database: &mut MutexGuard<Database>) {
+    database.close_parking_lot().unwrap();
+    info!("parking lot closed");
+
+    io.within(ClientId::GroundFloor.to_string())
+        .emit(CLOSE_PARKING_LOT_EVENT, ())
+        .unwrap();
+
+    broadcast_parking_lot_state(io, database);
+}
+
+pub fn open_parking_lot(io: &SocketIo, database: &mut MutexGuard<Database>) {
+    database.open_parking_lot().unwrap();
+    info!("parking lot opened");
+
+    io.within(ClientId::GroundFloor.to_string())
+        .emit(OPEN_PARKING_LOT_EVENT, ())
+        .unwrap();
+
+    broadcast_parking_lot_state(io, database);
+}
+
+pub fn close_floor(io: &SocketIo, database: &mut MutexGuard<Database>, floor: ClientId) {
+    let floor_number = floor.to_index();
+
+    database.close_floor(floor_number).unwrap();
+    info!(floor = floor_number, "floor closed");
+
+    io.within(floor.to_string())
+        .emit(CLOSE_FLOOR_EVENT, ())
+        .unwrap();
+
+    publish_floor_closed(database, floor_number);
+
+    broadcast_parking_lot_state(io, database);
+}
+
+pub fn open_floor(io: &SocketIo, database: &mut MutexGuard<Database>, floor: ClientId) {
+    database.open_floor(floor.to_index()).unwrap();
+    info!(floor = floor.to_index(), "floor opened");
+
+    io.within(floor.to_string())
+        .emit(OPEN_FLOOR_EVENT, ())
+        .unwrap();
+
+    broadcast_parking_lot_state(io, database);
+}
+
+pub fn broadcast_parking_lot_state(io: &SocketIo, database: &MutexGuard<Database>) {
+    let parking_lot = database.get_parking_lot_state().unwrap();
+
+    io.within(ClientId::App.to_string())
+        .emit(PARKING_LOT_STATE_EVENT, parking_lot.clone())
+        .unwrap();
+
+    io.within(ClientId::GroundFloor.to_string())
+        .emit(LOT_OCCUPANCY_EVENT, database.get_lot_occupancy().unwrap())
+        .unwrap();
+
+    database
+        .events
+        .publish(LotEvent::LotStateChanged(parking_lot));
+}
+
+pub fn publish_floor_closed(database: &MutexGuard<Database>, floor_number: i32) {
+    let floor = database.get_floor(floor_number).unwrap();
+
+    database
+        .events
+        .publish(LotEvent::FloorClosed(FloorClosedPayload {
+            floor_number,
+            spots: floor.as_bool_vec(),
+        }));
+}
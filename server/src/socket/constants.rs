@@ -9,3 +9,33 @@ pub const OPEN_FLOOR_EVENT: &str = "open_floor";
 pub const CAR_ARRIVED_EVENT: &str = "car_arrived";
 pub const CAR_DEPARTED_EVENT: &str = "car_departed";
 pub const RESET_DATABASE_EVENT: &str = "reset_database";
+pub const CLOCK_SYNC_REQUEST_EVENT: &str = "clock_sync_request";
+pub const CLOCK_SYNC_EVENT: &str = "clock_sync";
+pub const CLOCK_SKEW_EVENT: &str = "clock_skew";
+pub const INPUT_GLITCHES_EVENT: &str = "input_glitches";
+pub const BARRIER_FAULT_EVENT: &str = "barrier_fault";
+pub const BARRIER_RECOVERED_EVENT: &str = "barrier_recovered";
+pub const SPOT_MISMATCH_EVENT: &str = "spot_mismatch";
+pub const AMBIGUOUS_PASSAGE_EVENT: &str = "ambiguous_passage";
+pub const SENSOR_REPORT_EVENT: &str = "sensor_report";
+pub const LOT_OCCUPANCY_EVENT: &str = "lot_occupancy";
+pub const REQUEST_ANALYTICS_EVENT: &str = "request_analytics";
+pub const ANALYTICS_EVENT: &str = "analytics";
+pub const REQUEST_OCCUPANCY_HISTORY_EVENT: &str = "request_occupancy_history";
+pub const OCCUPANCY_HISTORY_EVENT: &str = "occupancy_history";
+pub const SET_SPOT_OUT_OF_SERVICE_EVENT: &str = "set_spot_out_of_service";
+pub const RETURN_SPOT_TO_SERVICE_EVENT: &str = "return_spot_to_service";
+pub const SPOTS_OUT_OF_SERVICE_EVENT: &str = "spots_out_of_service";
+pub const VEHICLE_OVERSTAYED_EVENT: &str = "vehicle_overstayed";
+pub const REQUEST_SUBSCRIBERS_EVENT: &str = "request_subscribers";
+pub const SUBSCRIBERS_EVENT: &str = "subscribers";
+pub const ADD_SUBSCRIBER_EVENT: &str = "add_subscriber";
+pub const REMOVE_SUBSCRIBER_EVENT: &str = "remove_subscriber";
+pub const IDENTIFY_VEHICLE_EVENT: &str = "identify_vehicle";
+pub const REQUEST_RECEIPT_EVENT: &str = "request_receipt";
+pub const RECEIPT_EVENT: &str = "receipt";
+pub const SET_RECEIPT_PAYMENT_EVENT: &str = "set_receipt_payment";
+pub const CLOSE_DAY_EVENT: &str = "close_day";
+pub const DAY_CLOSED_EVENT: &str = "day_closed";
+pub const REQUEST_CASH_CLOSINGS_EVENT: &str = "request_cash_closings";
+pub const CASH_CLOSINGS_EVENT: &str = "cash_closings";
@@ -1,14 +1,37 @@
 use super::{
+    commands::{self, publish_floor_closed},
     constants::{
-        CAR_ARRIVED_EVENT, CAR_DEPARTED_EVENT, CLIENT_ID_HEADER, CLOSE_FLOOR_EVENT,
-        CLOSE_PARKING_LOT_EVENT, FLOOR_STATE_EVENT, OPEN_FLOOR_EVENT, OPEN_PARKING_LOT_EVENT,
-        PARKING_LOT_STATE_EVENT, RESET_DATABASE_EVENT,
+        ADD_SUBSCRIBER_EVENT, AMBIGUOUS_PASSAGE_EVENT, ANALYTICS_EVENT, BARRIER_FAULT_EVENT,
+        BARRIER_RECOVERED_EVENT, CAR_ARRIVED_EVENT, CAR_DEPARTED_EVENT, CASH_CLOSINGS_EVENT,
+        CLIENT_ID_HEADER, CLOCK_SKEW_EVENT, CLOCK_SYNC_EVENT, CLOCK_SYNC_REQUEST_EVENT,
+        CLOSE_DAY_EVENT, CLOSE_FLOOR_EVENT, CLOSE_PARKING_LOT_EVENT, DAY_CLOSED_EVENT,
+        FLOOR_STATE_EVENT, IDENTIFY_VEHICLE_EVENT, INPUT_GLITCHES_EVENT, LOT_OCCUPANCY_EVENT,
+        OCCUPANCY_HISTORY_EVENT, OPEN_FLOOR_EVENT, OPEN_PARKING_LOT_EVENT, PARKING_LOT_STATE_EVENT,
+        RECEIPT_EVENT, REMOVE_SUBSCRIBER_EVENT, REQUEST_ANALYTICS_EVENT,
+        REQUEST_CASH_CLOSINGS_EVENT, REQUEST_OCCUPANCY_HISTORY_EVENT, REQUEST_RECEIPT_EVENT,
+        REQUEST_SUBSCRIBERS_EVENT, RESET_DATABASE_EVENT, RETURN_SPOT_TO_SERVICE_EVENT,
+        SENSOR_REPORT_EVENT, SET_RECEIPT_PAYMENT_EVENT, SET_SPOT_OUT_OF_SERVICE_EVENT,
+        SPOTS_OUT_OF_SERVICE_EVENT, SPOT_MISMATCH_EVENT, SUBSCRIBERS_EVENT,
     },
-    payloads::ParkingSpaceModifiedPayload,
+    payloads::{
+        AmbiguousPassagePayload, AnalyticsWindowPayload, BarrierFaultPayload,
+        BarrierRecoveredPayload, ClockSyncPayload, CloseDayPayload, ControllerDisconnectedPayload,
+        DayClosedPayload, IdentifyVehiclePayload, InputGlitchesPayload, NewSubscriberPayload,
+        OccupancyHistoryWindowPayload, ParkingSpaceModifiedPayload, ReceiptPaymentPayload,
+        SensorReportPayload, SpotMismatchPayload, SpotOutOfServicePayload, SpotPayload,
+        VehicleMovementPayload,
+    },
+};
+use crate::{
+    analytics, barriers, clock, closing, config::Config, database::Database, events::LotEvent,
+    history, inputs, maintenance, models::client::ClientId, receipts, sensor_reports, subscribers,
+};
+use socketioxide::{
+    extract::{AckSender, Data, SocketRef},
+    SocketIo,
 };
-use crate::{database::Database, models::client::ClientId};
-use socketioxide::extract::{Data, SocketRef};
 use std::sync::{Arc, Mutex, MutexGuard};
+use tracing::{error, info, warn};
 
 pub async fn save_connection(socket: &SocketRef, database: &Arc<Mutex<Database>>) -> bool {
     // When a client connects, we need to check if it has a client_id header
@@ -20,7 +43,7 @@ pub async fn save_connection(socket: &SocketRef, database: &Arc<Mutex<Database>>
 
         // If the client_id is valid, we store it in the database and join the room
         if let Some(client_id) = ClientId::from_str(client_id_str) {
-            println!("Client {} connected as {}", socket.id, client_id);
+            info!(socket_id = %socket.id, client_id = %client_id, "client connected");
 
             database
                 .lock()
@@ -34,17 +57,14 @@ pub async fn save_connection(socket: &SocketRef, database: &Arc<Mutex<Database>>
         }
         // If the client_id is invalid, return false
         else {
-            println!(
-                "Unidentified client {} tried to connect as {}",
-                socket.id, client_id_str
-            );
+            warn!(socket_id = %socket.id, client_id = client_id_str, "unidentified client tried to connect");
 
             false
         }
     }
     // If the client_id header is missing, return false
     else {
-        println!("Unidentified client {} tried to connect", socket.id);
+        warn!(socket_id = %socket.id, "client without a client id tried to connect");
 
         false
     }
@@ -55,10 +75,28 @@ pub fn handle_disconnect(socket: &SocketRef, database: Arc<Mutex<Database>>) {
     socket.on_disconnect(move |socket: SocketRef| async move {
         let sid = socket.id.to_string();
 
-        if let Some(client_id) = database.lock().unwrap().clients.remove(&sid) {
-            println!("Client {} disconnected from {}", sid, client_id);
+        let mut database = database.lock().unwrap();
+
+        if let Some(client_id) = database.clients.remove(&sid) {
+            info!(socket_id = %sid, client_id = %client_id, "client disconnected");
+
+            // Its clock is measured again when it comes back, it may have been
+            // rebooted or corrected meanwhile. Unless it already reconnected
+            if !database.clients.values().any(|client| *client == client_id) {
+                database.clocks.remove(&client_id);
+            }
+
+            // Losing a floor controller means the floor is no longer monitored
+            if client_id != ClientId::App {
+                database.events.publish(LotEvent::ControllerDisconnected(
+                    ControllerDisconnectedPayload {
+                        client_id: client_id.to_string(),
+                        socket_id: sid,
+                    },
+                ));
+            }
         } else {
-            println!("Client {} disconnected", sid);
+            info!(socket_id = %sid, "client disconnected");
         }
     });
 }
@@ -74,6 +112,11 @@ pub async fn send_floor_state(socket: &SocketRef, database: &Arc<Mutex<Database>
         .emit(FLOOR_STATE_EVENT, vec![floor.as_bool_vec()])
         .unwrap();
 
+    socket
+        .within(client_id.to_string())
+        .emit(SPOTS_OUT_OF_SERVICE_EVENT, vec![floor.out_of_service_vec()])
+        .unwrap();
+
     if *client_id == ClientId::GroundFloor && database.parking_lot_is_full().unwrap() {
         socket
             .within(client_id.to_string())
@@ -94,23 +137,236 @@ fn send_parking_lot_state(socket: &SocketRef, database: &MutexGuard<Database>) {
 
     socket
         .within(ClientId::App.to_string())
-        .emit(PARKING_LOT_STATE_EVENT, parking_lot)
+        .emit(PARKING_LOT_STATE_EVENT, parking_lot.clone())
         .unwrap();
+
+    // Also on every connection of the ground floor, which reconciles its own count
+    socket
+        .within(ClientId::GroundFloor.to_string())
+        .emit(LOT_OCCUPANCY_EVENT, database.get_lot_occupancy().unwrap())
+        .unwrap();
+
+    database
+        .events
+        .publish(LotEvent::LotStateChanged(parking_lot));
+}
+
+// Starts the clock handshake, the controller answers with a clock_sync event
+pub fn request_clock_sync(socket: &SocketRef, database: &Arc<Mutex<Database>>) {
+    let database = database.lock().unwrap();
+    let client_id = database.clients.get(&socket.id.to_string()).unwrap();
+
+    if *client_id != ClientId::App {
+        socket
+            .emit(CLOCK_SYNC_REQUEST_EVENT, clock::sync_request())
+            .unwrap();
+    }
+}
+
+pub fn handle_clock_sync(socket: &SocketRef, config: Arc<Config>, database: Arc<Mutex<Database>>) {
+    socket.on(
+        CLOCK_SYNC_EVENT,
+        move |socket: SocketRef, Data(payload): Data<ClockSyncPayload>| async move {
+            let client_id = *database
+                .lock()
+                .unwrap()
+                .clients
+                .get(&socket.id.to_string())
+                .unwrap();
+
+            // Let the operator know as soon as a controller clock goes off
+            if let Some(alert) = clock::record_sync(&config.clock, &database, client_id, &payload) {
+                socket
+                    .within(ClientId::App.to_string())
+                    .emit(CLOCK_SKEW_EVENT, alert)
+                    .unwrap();
+            }
+        },
+    );
+}
+
+pub fn handle_barrier_fault(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        BARRIER_FAULT_EVENT,
+        move |socket: SocketRef, Data(payload): Data<BarrierFaultPayload>| async move {
+            let mut database = database.lock().unwrap();
+
+            barriers::record_fault(&mut database, &payload);
+
+            // Alert the operator right away, the dashboard keeps showing it
+            socket
+                .within(ClientId::App.to_string())
+                .emit(BARRIER_FAULT_EVENT, payload)
+                .unwrap();
+
+            send_parking_lot_state(&socket, &database);
+        },
+    );
+}
+
+pub fn handle_barrier_recovered(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        BARRIER_RECOVERED_EVENT,
+        move |socket: SocketRef, Data(payload): Data<BarrierRecoveredPayload>| async move {
+            let mut database = database.lock().unwrap();
+
+            if barriers::record_recovery(&mut database, &payload) {
+                send_parking_lot_state(&socket, &database);
+            }
+        },
+    );
+}
+
+pub fn handle_input_glitches(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        INPUT_GLITCHES_EVENT,
+        move |socket: SocketRef, Data(payload): Data<InputGlitchesPayload>| async move {
+            let mut database = database.lock().unwrap();
+            let client_id = *database.clients.get(&socket.id.to_string()).unwrap();
+
+            inputs::record(&mut database, client_id, &payload);
+        },
+    );
+}
+
+// The controller reports the spot change itself, this keeps the ones its gates or
+// ramp didn't see, and the cars that went by without a spot changing
+pub fn handle_spot_mismatch(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        SPOT_MISMATCH_EVENT,
+        move |socket: SocketRef,
+              Data(payload): Data<SpotMismatchPayload>,
+              ack: AckSender| async move {
+            let mut database = database.lock().unwrap();
+            let client_id = *database.clients.get(&socket.id.to_string()).unwrap();
+
+            let report = sensor_reports::record_spot_mismatch(&mut database, client_id, &payload);
+
+            if report.is_ok() {
+                acknowledge(ack);
+            }
+
+            send_sensor_report(&socket, report);
+        },
+    );
+}
+
+// Not counted by the controller, this only keeps a trace of it
+pub fn handle_ambiguous_passage(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        AMBIGUOUS_PASSAGE_EVENT,
+        move |socket: SocketRef, Data(payload): Data<AmbiguousPassagePayload>| async move {
+            let mut database = database.lock().unwrap();
+            let client_id = *database.clients.get(&socket.id.to_string()).unwrap();
+
+            let report =
+                sensor_reports::record_ambiguous_passage(&mut database, client_id, &payload);
+            send_sensor_report(&socket, report);
+        },
+    );
+}
+
+// Shown to the operator, who can look for the sensor that keeps failing
+fn send_sensor_report(socket: &SocketRef, report: Result<SensorReportPayload, rusqlite::Error>) {
+    let report = match report {
+        Ok(report) => report,
+        Err(error) => {
+            error!(%error, "failed to record the sensor report");
+            return;
+        }
+    };
+
+    if let Err(error) = socket
+        .within(ClientId::App.to_string())
+        .emit(SENSOR_REPORT_EVENT, report)
+    {
+        warn!(%error, "failed to send the sensor report to the app");
+    }
+}
+
+// The controller keeps the spot events in its queue until they are acknowledged,
+// and sends them again otherwise, so only the recorded ones are
+fn acknowledge(ack: AckSender) {
+    if let Err(error) = ack.send(()) {
+        warn!(%error, "failed to acknowledge the event");
+    }
 }
 
-pub fn handle_car_arrived(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+pub fn handle_car_arrived(socket: &SocketRef, config: Arc<Config>, database: Arc<Mutex<Database>>) {
     socket.on(
         CAR_ARRIVED_EVENT,
-        move |socket: SocketRef, Data(payload): Data<ParkingSpaceModifiedPayload>| async move {
+        move |socket: SocketRef,
+              Data(payload): Data<ParkingSpaceModifiedPayload>,
+              ack: AckSender| async move {
             let mut database = database.lock().unwrap();
             let client_id = *database.clients.get(&socket.id.to_string()).unwrap();
             let floor_number = client_id.to_index();
 
+            let entry = clock::event_time(&config.clock, &database, client_id, payload.timestamp);
+
+            // The controller sends it again when the acknowledgement got lost, the
+            // car is already parked with the original timestamp
+            if database
+                .is_parked_since(
+                    floor_number,
+                    payload.parking_space,
+                    [entry.timestamp, payload.timestamp],
+                )
+                .unwrap()
+            {
+                info!(
+                    floor = floor_number,
+                    spot = payload.parking_space,
+                    timestamp = payload.timestamp,
+                    "car arrival already recorded, acknowledging it again"
+                );
+                acknowledge(ack);
+                return;
+            }
+
+            // Vehicles with a valid pass are billed by their contract
+            let subscription = match payload.credential.as_deref() {
+                Some(credential) => {
+                    subscribers::recognize(&database, credential, floor_number, entry.timestamp)
+                        .unwrap()
+                }
+                None => None,
+            };
+
+            info!(
+                floor = floor_number,
+                spot = payload.parking_space,
+                timestamp = payload.timestamp,
+                received_at = entry.received_at,
+                clock_skewed = entry.clock_skewed,
+                subscriber_id = subscription.map(|subscription| subscription.subscriber_id),
+                "car arrived"
+            );
+
+            subscribers::check_reserved_spot(
+                &database,
+                floor_number,
+                payload.parking_space,
+                subscription,
+                entry.timestamp,
+            )
+            .unwrap();
+
             // park the new car in the respective floor and parking space
             database
-                .park_vehicle(payload.timestamp, floor_number, payload.parking_space)
+                .park_vehicle(entry, floor_number, payload.parking_space, subscription)
                 .unwrap();
 
+            acknowledge(ack);
+
+            database
+                .events
+                .publish(LotEvent::CarArrived(VehicleMovementPayload {
+                    floor_number,
+                    spot_number: payload.parking_space,
+                    timestamp: entry.timestamp,
+                }));
+
             // if the floor filled up, close the floor
             if database.floor_is_full(floor_number).unwrap() {
                 database.close_floor(floor_number).unwrap();
@@ -119,6 +375,8 @@ pub fn handle_car_arrived(socket: &SocketRef, database: Arc<Mutex<Database>>) {
                     .within(client_id.to_string())
                     .emit(CLOSE_FLOOR_EVENT, ())
                     .unwrap();
+
+                publish_floor_closed(&database, floor_number);
             }
 
             // if the parking lot filled up, close the parking lot
@@ -129,6 +387,10 @@ pub fn handle_car_arrived(socket: &SocketRef, database: Arc<Mutex<Database>>) {
                     .within(ClientId::GroundFloor.to_string())
                     .emit(CLOSE_PARKING_LOT_EVENT, ())
                     .unwrap();
+
+                database
+                    .events
+                    .publish(LotEvent::LotFull(database.get_parking_lot_state().unwrap()));
             }
 
             // send the new floor state to the client
@@ -137,14 +399,83 @@ pub fn handle_car_arrived(socket: &SocketRef, database: Arc<Mutex<Database>>) {
     );
 }
 
-pub fn handle_car_departed(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+pub fn handle_car_departed(
+    socket: &SocketRef,
+    config: Arc<Config>,
+    database: Arc<Mutex<Database>>,
+) {
     socket.on(
         CAR_DEPARTED_EVENT,
-        move |socket: SocketRef, Data(payload): Data<ParkingSpaceModifiedPayload>| async move {
+        move |socket: SocketRef,
+              Data(payload): Data<ParkingSpaceModifiedPayload>,
+              ack: AckSender| async move {
             let mut database = database.lock().unwrap();
+            let client_id = *database.clients.get(&socket.id.to_string()).unwrap();
+            let floor_number = client_id.to_index();
 
-            // If the parking lot is full, open it
-            if database.parking_lot_is_full().unwrap() {
+            let exit = clock::event_time(&config.clock, &database, client_id, payload.timestamp);
+
+            // Only a lot or floor closed for being full is opened by the departure
+            let lot_was_full = database.parking_lot_is_full().unwrap();
+            let floor_was_full = database.floor_is_full(floor_number).unwrap();
+
+            // Remove the vehicle from the parking space and charge the stay, the app
+            // shows the total with the new state
+            match receipts::depart(&mut database, floor_number, payload.parking_space, exit) {
+                Ok(_) => acknowledge(ack),
+                // Sent again when the acknowledgement got lost, the car already left
+                // with the original timestamp
+                Err(rusqlite::Error::QueryReturnedNoRows)
+                    if database
+                        .has_left_at(
+                            floor_number,
+                            payload.parking_space,
+                            [exit.timestamp, payload.timestamp],
+                        )
+                        .unwrap() =>
+                {
+                    info!(
+                        floor = floor_number,
+                        spot = payload.parking_space,
+                        timestamp = payload.timestamp,
+                        "car departure already recorded, acknowledging it again"
+                    );
+                    acknowledge(ack);
+                    return;
+                }
+                // Sending it again wouldn't park a car there
+                Err(rusqlite::Error::QueryReturnedNoRows) => {
+                    warn!(
+                        floor = floor_number,
+                        spot = payload.parking_space,
+                        timestamp = payload.timestamp,
+                        "departure from a spot without a car, ignored"
+                    );
+                    acknowledge(ack);
+                    return;
+                }
+                Err(error) => {
+                    error!(
+                        floor = floor_number,
+                        spot = payload.parking_space,
+                        %error,
+                        "failed to record the departure"
+                    );
+                    return;
+                }
+            }
+
+            info!(
+                floor = floor_number,
+                spot = payload.parking_space,
+                timestamp = payload.timestamp,
+                received_at = exit.received_at,
+                clock_skewed = exit.clock_skewed,
+                "car departed"
+            );
+
+            // If the parking lot was full, open it
+            if lot_was_full {
                 database.open_parking_lot().unwrap();
 
                 socket
@@ -153,11 +484,8 @@ pub fn handle_car_departed(socket: &SocketRef, database: Arc<Mutex<Database>>) {
                     .unwrap();
             }
 
-            // If the floor is full, open it
-            let client_id = *database.clients.get(&socket.id.to_string()).unwrap();
-            let floor_number = client_id.to_index();
-
-            if database.floor_is_full(floor_number).unwrap() {
+            // If the floor was full, open it
+            if floor_was_full {
                 database.open_floor(floor_number).unwrap();
 
                 socket
@@ -166,10 +494,13 @@ pub fn handle_car_departed(socket: &SocketRef, database: Arc<Mutex<Database>>) {
                     .unwrap();
             }
 
-            // Remove the vehicle from the parking space
             database
-                .unpark_vehicle(floor_number, payload.parking_space, payload.timestamp)
-                .unwrap();
+                .events
+                .publish(LotEvent::CarDeparted(VehicleMovementPayload {
+                    floor_number,
+                    spot_number: payload.parking_space,
+                    timestamp: exit.timestamp,
+                }));
 
             // Send the new floor state to the client
             send_parking_lot_state(&socket, &database);
@@ -177,76 +508,36 @@ pub fn handle_car_departed(socket: &SocketRef, database: Arc<Mutex<Database>>) {
     )
 }
 
-pub fn handle_close_parking_lot(socket: &SocketRef, database: Arc<Mutex<Database>>) {
-    socket.on(
-        CLOSE_PARKING_LOT_EVENT,
-        move |socket: SocketRef| async move {
-            let mut database = database.lock().unwrap();
-
-            database.close_parking_lot().unwrap();
-
-            socket
-                .within(ClientId::GroundFloor.to_string())
-                .emit(CLOSE_PARKING_LOT_EVENT, ())
-                .unwrap();
-
-            send_parking_lot_state(&socket, &database);
-        },
-    );
+pub fn handle_close_parking_lot(socket: &SocketRef, io: SocketIo, database: Arc<Mutex<Database>>) {
+    socket.on(CLOSE_PARKING_LOT_EVENT, move |_: SocketRef| async move {
+        commands::close_parking_lot(&io, &mut database.lock().unwrap());
+    });
 }
 
-pub fn handle_close_floor(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+pub fn handle_close_floor(socket: &SocketRef, io: SocketIo, database: Arc<Mutex<Database>>) {
     socket.on(
         CLOSE_FLOOR_EVENT,
-        move |socket: SocketRef, Data(floor_number): Data<i32>| async move {
+        move |Data(floor_number): Data<i32>| async move {
             let floor = ClientId::from_index(floor_number).unwrap();
-            let mut database = database.lock().unwrap();
 
-            database.close_floor(floor_number).unwrap();
-
-            socket
-                .within(floor.to_string())
-                .emit(CLOSE_FLOOR_EVENT, ())
-                .unwrap();
-
-            send_parking_lot_state(&socket, &database);
+            commands::close_floor(&io, &mut database.lock().unwrap(), floor);
         },
     );
 }
 
-pub fn handle_open_parking_lot(socket: &SocketRef, database: Arc<Mutex<Database>>) {
-    socket.on(
-        OPEN_PARKING_LOT_EVENT,
-        move |socket: SocketRef| async move {
-            let mut database = database.lock().unwrap();
-
-            database.open_parking_lot().unwrap();
-
-            socket
-                .within(ClientId::GroundFloor.to_string())
-                .emit(OPEN_PARKING_LOT_EVENT, ())
-                .unwrap();
-
-            send_parking_lot_state(&socket, &database);
-        },
-    );
+pub fn handle_open_parking_lot(socket: &SocketRef, io: SocketIo, database: Arc<Mutex<Database>>) {
+    socket.on(OPEN_PARKING_LOT_EVENT, move |_: SocketRef| async move {
+        commands::open_parking_lot(&io, &mut database.lock().unwrap());
+    });
 }
 
-pub fn handle_open_floor(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+pub fn handle_open_floor(socket: &SocketRef, io: SocketIo, database: Arc<Mutex<Database>>) {
     socket.on(
         OPEN_FLOOR_EVENT,
-        move |socket: SocketRef, Data(floor_number): Data<i32>| async move {
+        move |Data(floor_number): Data<i32>| async move {
             let floor = ClientId::from_index(floor_number).unwrap();
-            let mut database = database.lock().unwrap();
 
-            database.open_floor(floor_number).unwrap();
-
-            socket
-                .within(floor.to_string())
-                .emit(OPEN_FLOOR_EVENT, ())
-                .unwrap();
-
-            send_parking_lot_state(&socket, &database);
+            commands::open_floor(&io, &mut database.lock().unwrap(), floor);
         },
     );
 }
@@ -283,3 +574,223 @@ pub fn handle_reset_database(socket: &SocketRef, database: Arc<Mutex<Database>>)
         send_parking_lot_state(&socket, &database);
     });
 }
+
+pub fn handle_request_analytics(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        REQUEST_ANALYTICS_EVENT,
+        move |socket: SocketRef, Data(window): Data<AnalyticsWindowPayload>| async move {
+            let analytics = {
+                let database = database.lock().unwrap();
+                analytics::compute(&database, window.from, window.to).unwrap()
+            };
+
+            // Only the client that asked gets the statistics
+            socket.emit(ANALYTICS_EVENT, analytics).unwrap();
+        },
+    );
+}
+
+pub fn handle_request_occupancy_history(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        REQUEST_OCCUPANCY_HISTORY_EVENT,
+        move |socket: SocketRef, Data(window): Data<OccupancyHistoryWindowPayload>| async move {
+            let history = {
+                let database = database.lock().unwrap();
+                history::query(&database, &window).unwrap()
+            };
+
+            socket.emit(OCCUPANCY_HISTORY_EVENT, history).unwrap();
+        },
+    );
+}
+
+pub fn handle_set_spot_out_of_service(
+    socket: &SocketRef,
+    io: SocketIo,
+    database: Arc<Mutex<Database>>,
+) {
+    socket.on(
+        SET_SPOT_OUT_OF_SERVICE_EVENT,
+        move |Data(request): Data<SpotOutOfServicePayload>| async move {
+            let mut database = database.lock().unwrap();
+
+            if let Err(error) = maintenance::set_out_of_service(&io, &mut database, &request) {
+                warn!(
+                    floor = request.floor_number,
+                    spot = request.spot_number,
+                    %error,
+                    "could not take the spot out of service"
+                );
+            }
+        },
+    );
+}
+
+pub fn handle_return_spot_to_service(
+    socket: &SocketRef,
+    io: SocketIo,
+    database: Arc<Mutex<Database>>,
+) {
+    socket.on(
+        RETURN_SPOT_TO_SERVICE_EVENT,
+        move |Data(spot): Data<SpotPayload>| async move {
+            let mut database = database.lock().unwrap();
+
+            if let Err(error) = maintenance::return_to_service(
+                &io,
+                &mut database,
+                spot.floor_number,
+                spot.spot_number,
+            ) {
+                warn!(
+                    floor = spot.floor_number,
+                    spot = spot.spot_number,
+                    %error,
+                    "could not return the spot to service"
+                );
+            }
+        },
+    );
+}
+
+pub fn handle_request_subscribers(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        REQUEST_SUBSCRIBERS_EVENT,
+        move |socket: SocketRef| async move {
+            let subscribers = match subscribers::list(&database.lock().unwrap()) {
+                Ok(subscribers) => subscribers,
+                Err(error) => {
+                    warn!(%error, "could not list the subscribers");
+                    return;
+                }
+            };
+
+            socket.emit(SUBSCRIBERS_EVENT, vec![subscribers]).unwrap();
+        },
+    );
+}
+
+pub fn handle_add_subscriber(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        ADD_SUBSCRIBER_EVENT,
+        move |Data(request): Data<NewSubscriberPayload>| async move {
+            if let Err(error) = subscribers::add(&mut database.lock().unwrap(), &request) {
+                warn!(%error, "could not add the subscriber");
+            }
+        },
+    );
+}
+
+pub fn handle_remove_subscriber(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        REMOVE_SUBSCRIBER_EVENT,
+        move |Data(id): Data<i64>| async move {
+            if let Err(error) = subscribers::remove(&mut database.lock().unwrap(), id) {
+                warn!(subscriber_id = id, %error, "could not remove the subscriber");
+            }
+        },
+    );
+}
+
+pub fn handle_identify_vehicle(socket: &SocketRef, io: SocketIo, database: Arc<Mutex<Database>>) {
+    socket.on(
+        IDENTIFY_VEHICLE_EVENT,
+        move |Data(request): Data<IdentifyVehiclePayload>| async move {
+            let mut database = database.lock().unwrap();
+
+            if let Err(error) = subscribers::identify(&io, &mut database, &request) {
+                warn!(
+                    floor = request.floor_number,
+                    spot = request.spot_number,
+                    %error,
+                    "could not identify the vehicle"
+                );
+            }
+        },
+    );
+}
+
+pub fn handle_request_receipt(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        REQUEST_RECEIPT_EVENT,
+        move |socket: SocketRef, Data(number): Data<i64>| async move {
+            let document = {
+                let database = database.lock().unwrap();
+                receipts::document(&database, number).unwrap()
+            };
+
+            socket.emit(RECEIPT_EVENT, document).unwrap();
+        },
+    );
+}
+
+pub fn handle_set_receipt_payment(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        SET_RECEIPT_PAYMENT_EVENT,
+        move |socket: SocketRef, Data(request): Data<ReceiptPaymentPayload>| async move {
+            let document = {
+                let mut database = database.lock().unwrap();
+
+                if let Err(error) = receipts::set_payment_method(
+                    &mut database,
+                    request.number,
+                    request.payment_method,
+                ) {
+                    warn!(receipt_number = request.number, %error, "could not change the payment method");
+                }
+
+                receipts::document(&database, request.number).unwrap()
+            };
+
+            // Either way the app gets the receipt as it is now
+            socket.emit(RECEIPT_EVENT, document).unwrap();
+        },
+    );
+}
+
+pub fn handle_close_day(socket: &SocketRef, config: Arc<Config>, database: Arc<Mutex<Database>>) {
+    socket.on(
+        CLOSE_DAY_EVENT,
+        move |socket: SocketRef, Data(request): Data<CloseDayPayload>| async move {
+            let result = closing::close_day(
+                &config.cash_closing,
+                &mut database.lock().unwrap(),
+                request.date.as_deref(),
+            );
+
+            let payload = match result {
+                Ok(closing) => DayClosedPayload {
+                    closing: Some(closing.into()),
+                    error: None,
+                },
+                Err(error) => {
+                    warn!(date = request.date.as_deref(), %error, "could not close the business day");
+
+                    DayClosedPayload {
+                        closing: None,
+                        error: Some(error.to_string()),
+                    }
+                }
+            };
+
+            socket.emit(DAY_CLOSED_EVENT, payload).unwrap();
+        },
+    );
+}
+
+pub fn handle_request_cash_closings(socket: &SocketRef, database: Arc<Mutex<Database>>) {
+    socket.on(
+        REQUEST_CASH_CLOSINGS_EVENT,
+        move |socket: SocketRef| async move {
+            let closings = match closing::list(&database.lock().unwrap()) {
+                Ok(closings) => closings,
+                Err(error) => {
+                    warn!(%error, "could not list the cash closings");
+                    return;
+                }
+            };
+
+            socket.emit(CASH_CLOSINGS_EVENT, vec![closings]).unwrap();
+        },
+    );
+}
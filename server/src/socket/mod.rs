@@ -1,4 +1,5 @@
-mod constants;
+pub mod commands;
+pub mod constants;
 mod handlers;
 pub mod namespace;
 pub mod payloads;
@@ -1,13 +1,26 @@
 use super::handlers::{
-    handle_car_arrived, handle_car_departed, handle_close_floor, handle_close_parking_lot,
-    handle_disconnect, handle_open_floor, handle_open_parking_lot, handle_reset_database,
-    save_connection, send_floor_state,
+    handle_add_subscriber, handle_ambiguous_passage, handle_barrier_fault,
+    handle_barrier_recovered, handle_car_arrived, handle_car_departed, handle_clock_sync,
+    handle_close_day, handle_close_floor, handle_close_parking_lot, handle_disconnect,
+    handle_identify_vehicle, handle_input_glitches, handle_open_floor, handle_open_parking_lot,
+    handle_remove_subscriber, handle_request_analytics, handle_request_cash_closings,
+    handle_request_occupancy_history, handle_request_receipt, handle_request_subscribers,
+    handle_reset_database, handle_return_spot_to_service, handle_set_receipt_payment,
+    handle_set_spot_out_of_service, handle_spot_mismatch, request_clock_sync, save_connection,
+    send_floor_state,
 };
-use crate::database::Database;
+use crate::{config::Config, database::Database};
 use socketioxide::{extract::SocketRef, SocketIo};
+use std::sync::{Arc, Mutex};
 
-pub fn configure_socket_namespace(io: &SocketIo) {
-    let database = Database::new();
+pub fn configure_socket_namespace(
+    io: &SocketIo,
+    config: &Arc<Config>,
+    database: &Arc<Mutex<Database>>,
+) {
+    let config = config.clone();
+    let database = database.clone();
+    let io_clone = io.clone();
 
     io.ns("/", move |socket: SocketRef| async move {
         let conn_was_saved = save_connection(&socket, &database).await;
@@ -15,6 +28,9 @@ pub fn configure_socket_namespace(io: &SocketIo) {
         if conn_was_saved {
             // Send the parking lot state to the client that just connected (or reconnected)
             send_floor_state(&socket, &database).await;
+
+            // Measure the controller clock before it reports any event
+            request_clock_sync(&socket, &database);
         } else {
             socket.disconnect().unwrap();
             return;
@@ -22,15 +38,39 @@ pub fn configure_socket_namespace(io: &SocketIo) {
 
         handle_disconnect(&socket, database.clone());
 
-        handle_car_arrived(&socket, database.clone());
-        handle_car_departed(&socket, database.clone());
+        handle_clock_sync(&socket, config.clone(), database.clone());
+        handle_input_glitches(&socket, database.clone());
+        handle_barrier_fault(&socket, database.clone());
+        handle_barrier_recovered(&socket, database.clone());
+
+        handle_car_arrived(&socket, config.clone(), database.clone());
+        handle_car_departed(&socket, config.clone(), database.clone());
+        handle_spot_mismatch(&socket, database.clone());
+        handle_ambiguous_passage(&socket, database.clone());
 
-        handle_close_floor(&socket, database.clone());
-        handle_close_parking_lot(&socket, database.clone());
+        handle_close_floor(&socket, io_clone.clone(), database.clone());
+        handle_close_parking_lot(&socket, io_clone.clone(), database.clone());
 
-        handle_open_parking_lot(&socket, database.clone());
-        handle_open_floor(&socket, database.clone());
+        handle_open_parking_lot(&socket, io_clone.clone(), database.clone());
+        handle_open_floor(&socket, io_clone.clone(), database.clone());
 
         handle_reset_database(&socket, database.clone());
+
+        handle_set_spot_out_of_service(&socket, io_clone.clone(), database.clone());
+        handle_return_spot_to_service(&socket, io_clone.clone(), database.clone());
+
+        handle_request_analytics(&socket, database.clone());
+        handle_request_occupancy_history(&socket, database.clone());
+
+        handle_request_subscribers(&socket, database.clone());
+        handle_add_subscriber(&socket, database.clone());
+        handle_remove_subscriber(&socket, database.clone());
+        handle_identify_vehicle(&socket, io_clone.clone(), database.clone());
+
+        handle_request_receipt(&socket, database.clone());
+        handle_set_receipt_payment(&socket, database.clone());
+
+        handle_close_day(&socket, config.clone(), database.clone());
+        handle_request_cash_closings(&socket, database.clone());
     });
 }
@@ -1,33 +1,661 @@
+use crate::models::{
+    closing::{CashClosing, ClosingTotal},
+    history::HistoryResolution,
+    maintenance::SpotMaintenance,
+    parking_lot::Overstay,
+    receipt::{PaymentMethod, Receipt, ReceiptLine, ReceiptLineKind},
+    retention::{DailySummary, StorageReport},
+    sensor_report::SensorReport,
+    subscriber::{Subscriber, Subscription},
+};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ParkingSpaceModifiedPayload {
     pub parking_space: i32,
     pub timestamp: i64,
+    // Plate or card read at the entry, only sent by controllers with a reader
+    #[serde(default)]
+    pub credential: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ParkingLotDataPayload {
     pub floors: Vec<FloorDataPayload>,
     pub exited_vehicles: Vec<VehicleDataPayload>,
     pub is_closed: bool,
+    pub forecast: ForecastPayload,
+    // Ground floor barriers stopped by a timeout, until they work again
+    pub barrier_faults: Vec<BarrierFaultPayload>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct FloorDataPayload {
     pub spots: Vec<SpotDataPayload>,
     pub is_closed: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct SpotDataPayload {
     pub spot_type: i32,
     pub parked_vehicle: Option<VehicleDataPayload>,
+    pub out_of_service: Option<SpotMaintenancePayload>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct VehicleDataPayload {
     pub id: i32,
     pub entry_time: i64,
     pub exit_time: Option<i64>,
+    // Entry or exit time came from a controller with a skewed clock
+    pub clock_skewed: bool,
+    pub overstay: Option<OverstayPayload>,
+    pub subscription: Option<SubscriptionPayload>,
+    // Set once the vehicle left and its receipt was issued
+    pub receipt_number: Option<i64>,
+    pub total: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OverstayPayload {
+    pub max_minutes: i64,
+    // Charged for every minute past max_minutes, on top of the regular fee
+    pub surcharge_per_minute: f64,
+}
+
+impl From<Overstay> for OverstayPayload {
+    fn from(overstay: Overstay) -> Self {
+        Self {
+            max_minutes: overstay.max_minutes,
+            surcharge_per_minute: overstay.surcharge_per_minute,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SubscriptionPayload {
+    pub subscriber_id: i64,
+    // Replaces the regular fee, 0 when the subscriber is exempt
+    pub rate_per_minute: f64,
+}
+
+impl From<Subscription> for SubscriptionPayload {
+    fn from(subscription: Subscription) -> Self {
+        Self {
+            subscriber_id: subscription.subscriber_id,
+            rate_per_minute: subscription.rate_per_minute,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FloorClosedPayload {
+    pub floor_number: i32,
+    pub spots: Vec<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ControllerDisconnectedPayload {
+    pub client_id: String,
+    pub socket_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct VehicleOverstayedPayload {
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub spot_type: i32,
+    pub vehicle: VehicleDataPayload,
+    pub parked_minutes: i64,
+    pub max_minutes: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct VehicleMovementPayload {
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ClockSyncRequestPayload {
+    pub server_time: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ClockSyncPayload {
+    pub server_time: i64,
+    pub client_time: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ClockSkewPayload {
+    pub client_id: String,
+    pub offset_millis: i64,
+    pub round_trip_millis: i64,
+    pub max_offset_secs: i64,
+}
+
+// Sent by the ground floor when a barrier ("entry" or "exit") is stopped by a
+// timeout, and forwarded to the app
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BarrierFaultPayload {
+    pub barrier: String,
+    // "close_signal_timeout" or "motor_timeout"
+    pub fault: String,
+    pub timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BarrierRecoveredPayload {
+    pub barrier: String,
+    pub timestamp: i64,
+}
+
+// Sent to the ground floor, which closes the lot on its own count of the cars
+// while it can't reach the server
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LotOccupancyPayload {
+    // Spots in service with a car, out of the ones in service
+    pub occupied: i32,
+    pub capacity: i32,
+    pub is_closed: bool,
+}
+
+// A spot changed without a car going past the gate or the ramp of the floor, e.g.
+// a car that changed spots or a failed sensor, or the other way around
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpotMismatchPayload {
+    // "arrival_without_entry" or "departure_without_exit", which have the spot,
+    // "entry_without_arrival" or "exit_without_departure", which don't
+    pub kind: String,
+    pub parking_space: Option<i32>,
+    pub timestamp: i64,
+}
+
+// A car seen by only one sensor of the ramp of an upper floor, it turned back or
+// the other sensor missed it
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AmbiguousPassagePayload {
+    // "pass_through_sensor_1" or "pass_through_sensor_2"
+    pub first_sensor: String,
+    pub timestamp: i64,
+}
+
+// A spot mismatch or an ambiguous passage as stored, forwarded to the app
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SensorReportPayload {
+    pub id: i64,
+    pub floor_number: i32,
+    // The kind of the spot mismatch, or "ambiguous_passage"
+    pub kind: String,
+    pub parking_space: Option<i32>,
+    pub first_sensor: Option<String>,
+    pub timestamp: i64,
+    pub received_at: i64,
+}
+
+impl From<SensorReport> for SensorReportPayload {
+    fn from(report: SensorReport) -> Self {
+        Self {
+            id: report.id,
+            floor_number: report.floor_number,
+            kind: report.kind,
+            parking_space: report.parking_space,
+            first_sensor: report.first_sensor,
+            timestamp: report.timestamp,
+            received_at: report.received_at,
+        }
+    }
+}
+
+// Totals since the controller started, per input pin
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InputGlitchesPayload {
+    pub pins: Vec<PinGlitchesPayload>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PinGlitchesPayload {
+    pub pin: String,
+    pub glitches: u64,
+    pub coalesced: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ControllerInputsPayload {
+    pub client_id: String,
+    pub received_at: i64,
+    pub pins: Vec<PinGlitchesPayload>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AnalyticsWindowPayload {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AnalyticsPayload {
+    pub from: i64,
+    pub to: i64,
+    pub arrivals: usize,
+    pub departures: usize,
+    pub average_dwell_minutes: Option<f64>,
+    pub median_dwell_minutes: Option<f64>,
+    pub spot_turnover: Vec<SpotTurnoverPayload>,
+    pub spot_type_turnover: Vec<SpotTypeTurnoverPayload>,
+    // Index 0 is midnight to 1am, in the server local time
+    pub hourly_arrivals: Vec<usize>,
+    pub hourly_departures: Vec<usize>,
+    pub daily_peak_occupancy: Vec<DailyPeakPayload>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpotTurnoverPayload {
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub spot_type: i32,
+    pub vehicles: usize,
+    pub turnover_per_day: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpotTypeTurnoverPayload {
+    pub spot_type: i32,
+    pub spots: usize,
+    pub vehicles: usize,
+    pub turnover_per_day: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DailyPeakPayload {
+    // yyyy-mm-dd in the server local time
+    pub date: String,
+    pub peak_occupancy: i32,
+    pub peak_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OccupancyHistoryWindowPayload {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    // Mixes both resolutions when left out
+    pub resolution: Option<HistoryResolution>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OccupancyHistoryPayload {
+    pub from: i64,
+    pub to: i64,
+    pub points: Vec<OccupancyPointPayload>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OccupancyPointPayload {
+    pub timestamp: i64,
+    pub resolution: HistoryResolution,
+    pub is_closed: bool,
+    pub occupied: f64,
+    pub capacity: i32,
+    pub floors: Vec<FloorOccupancyPayload>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FloorOccupancyPayload {
+    pub floor_number: i32,
+    pub is_closed: bool,
+    pub occupied: f64,
+    pub capacity: i32,
+    pub spot_types: Vec<SpotTypeOccupancyPayload>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpotTypeOccupancyPayload {
+    pub spot_type: i32,
+    pub occupied: f64,
+    pub capacity: i32,
+}
+
+// Empty until the first forecast is computed
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ForecastPayload {
+    pub generated_at: i64,
+    // None when the lot is not expected to fill up in the next hours
+    pub minutes_to_full: Option<i64>,
+    // Free spots expected at the end of each of the next hours
+    pub expected_available: Vec<i32>,
+    pub floors: Vec<FloorForecastPayload>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FloorForecastPayload {
+    pub floor_number: i32,
+    pub minutes_to_full: Option<i64>,
+    pub expected_available: Vec<i32>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpotMaintenancePayload {
+    pub id: i64,
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub reason: String,
+    pub starts_at: i64,
+    pub ends_at: Option<i64>,
+}
+
+impl From<SpotMaintenance> for SpotMaintenancePayload {
+    fn from(maintenance: SpotMaintenance) -> Self {
+        Self {
+            id: maintenance.id,
+            floor_number: maintenance.floor_number,
+            spot_number: maintenance.spot_number,
+            reason: maintenance.reason,
+            starts_at: maintenance.starts_at,
+            ends_at: maintenance.ends_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpotOutOfServicePayload {
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub reason: String,
+    // Defaults to now
+    pub starts_at: Option<i64>,
+    // None keeps the spot out of service until it is returned
+    pub ends_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpotPayload {
+    pub floor_number: i32,
+    pub spot_number: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SubscriberPayload {
+    pub id: i64,
+    pub credential: String,
+    pub name: String,
+    pub valid_from: i64,
+    pub valid_until: i64,
+    pub allowed_floors: Vec<i32>,
+    pub reserved_spot: Option<SpotPayload>,
+    pub rate_per_minute: f64,
+}
+
+impl From<Subscriber> for SubscriberPayload {
+    fn from(subscriber: Subscriber) -> Self {
+        Self {
+            id: subscriber.id,
+            credential: subscriber.credential,
+            name: subscriber.name,
+            valid_from: subscriber.valid_from,
+            valid_until: subscriber.valid_until,
+            allowed_floors: subscriber.allowed_floors,
+            reserved_spot: subscriber.reserved_spot.map(|(floor_number, spot_number)| {
+                SpotPayload {
+                    floor_number,
+                    spot_number,
+                }
+            }),
+            rate_per_minute: subscriber.rate_per_minute,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NewSubscriberPayload {
+    pub credential: String,
+    pub name: String,
+    // Defaults to now
+    pub valid_from: Option<i64>,
+    pub valid_until: i64,
+    // Every floor when left out
+    #[serde(default)]
+    pub allowed_floors: Vec<i32>,
+    pub reserved_spot: Option<SpotPayload>,
+    // Exempt when left out
+    #[serde(default)]
+    pub rate_per_minute: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct IdentifyVehiclePayload {
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub credential: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReceiptPayload {
+    pub number: i64,
+    pub vehicle_id: i32,
+    pub floor_number: i32,
+    pub spot_number: i32,
+    pub entry_time: i64,
+    pub exit_time: i64,
+    pub duration_minutes: i64,
+    pub clock_skewed: bool,
+    pub subscriber_id: Option<i64>,
+    pub lines: Vec<ReceiptLinePayload>,
+    pub total: f64,
+    pub payment_method: PaymentMethod,
+    pub issued_at: i64,
+}
+
+impl From<Receipt> for ReceiptPayload {
+    fn from(receipt: Receipt) -> Self {
+        Self {
+            number: receipt.number,
+            vehicle_id: receipt.vehicle_id,
+            floor_number: receipt.floor_number,
+            spot_number: receipt.spot_number,
+            entry_time: receipt.entry_time,
+            exit_time: receipt.exit_time,
+            duration_minutes: receipt.duration_minutes,
+            clock_skewed: receipt.clock_skewed,
+            subscriber_id: receipt.subscriber_id,
+            lines: receipt
+                .lines
+                .into_iter()
+                .map(ReceiptLinePayload::from)
+                .collect(),
+            total: receipt.total,
+            payment_method: receipt.payment_method,
+            issued_at: receipt.issued_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReceiptLinePayload {
+    pub kind: ReceiptLineKind,
+    pub description: String,
+    pub quantity: i64,
+    pub unit_price: f64,
+    pub amount: f64,
+}
+
+impl From<ReceiptLine> for ReceiptLinePayload {
+    fn from(line: ReceiptLine) -> Self {
+        Self {
+            kind: line.kind,
+            description: line.description,
+            quantity: line.quantity,
+            unit_price: line.unit_price,
+            amount: line.amount,
+        }
+    }
+}
+
+// Answer to request_receipt, receipt and text are None when there is no such receipt
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReceiptDocumentPayload {
+    pub number: i64,
+    pub receipt: Option<ReceiptPayload>,
+    // Ready for a thermal printer
+    pub text: Option<String>,
+}
+
+// Payment the operator took at the exit, a receipt of a closed day can't change
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReceiptPaymentPayload {
+    pub number: i64,
+    pub payment_method: PaymentMethod,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PaymentMethodPayload {
+    pub payment_method: PaymentMethod,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CashClosingPayload {
+    pub id: i64,
+    // yyyy-mm-dd
+    pub business_date: String,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub closed_at: i64,
+    pub vehicles: i64,
+    pub total: f64,
+    pub spot_types: Vec<SpotTypeTotalPayload>,
+    pub payment_methods: Vec<PaymentMethodTotalPayload>,
+    pub vehicles_inside: i64,
+}
+
+impl From<CashClosing> for CashClosingPayload {
+    fn from(closing: CashClosing) -> Self {
+        Self {
+            id: closing.id,
+            business_date: closing.business_date,
+            starts_at: closing.starts_at,
+            ends_at: closing.ends_at,
+            closed_at: closing.closed_at,
+            vehicles: closing.vehicles,
+            total: closing.total,
+            spot_types: closing
+                .spot_types
+                .into_iter()
+                .map(SpotTypeTotalPayload::from)
+                .collect(),
+            payment_methods: closing
+                .payment_methods
+                .into_iter()
+                .map(PaymentMethodTotalPayload::from)
+                .collect(),
+            vehicles_inside: closing.vehicles_inside,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpotTypeTotalPayload {
+    pub spot_type: i32,
+    pub vehicles: i64,
+    pub total: f64,
+}
+
+impl From<ClosingTotal<i32>> for SpotTypeTotalPayload {
+    fn from(total: ClosingTotal<i32>) -> Self {
+        Self {
+            spot_type: total.key,
+            vehicles: total.vehicles,
+            total: total.total,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PaymentMethodTotalPayload {
+    pub payment_method: PaymentMethod,
+    pub vehicles: i64,
+    pub total: f64,
+}
+
+impl From<ClosingTotal<PaymentMethod>> for PaymentMethodTotalPayload {
+    fn from(total: ClosingTotal<PaymentMethod>) -> Self {
+        Self {
+            payment_method: total.key,
+            vehicles: total.vehicles,
+            total: total.total,
+        }
+    }
+}
+
+// Business day to close, yyyy-mm-dd. None closes the last day that ended
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CloseDayPayload {
+    #[serde(default)]
+    pub date: Option<String>,
+}
+
+// Answer to close_day, error is set when the day could not be closed
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DayClosedPayload {
+    pub closing: Option<CashClosingPayload>,
+    pub error: Option<String>,
+}
+
+// Local dates as yyyy-mm-dd, both ends included. Every day when left out
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DateRangePayload {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DailySummaryPayload {
+    pub date: String,
+    pub floor_number: i32,
+    pub spot_type: i32,
+    pub arrivals: i64,
+    pub departures: i64,
+    pub dwell_minutes: i64,
+    pub revenue: f64,
+}
+
+impl From<DailySummary> for DailySummaryPayload {
+    fn from(summary: DailySummary) -> Self {
+        Self {
+            date: summary.date,
+            floor_number: summary.floor_number,
+            spot_type: summary.spot_type,
+            arrivals: summary.arrivals,
+            departures: summary.departures,
+            dwell_minutes: summary.dwell_minutes,
+            revenue: summary.revenue,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StorageReportPayload {
+    pub size_bytes: i64,
+    pub free_bytes: i64,
+    pub vehicles: i64,
+    pub exits: i64,
+    pub receipts: i64,
+    pub occupancy_snapshots: i64,
+    pub summarized_days: i64,
+    pub oldest_exit_time: Option<i64>,
+}
+
+impl From<StorageReport> for StorageReportPayload {
+    fn from(report: StorageReport) -> Self {
+        Self {
+            size_bytes: report.size_bytes,
+            free_bytes: report.free_bytes,
+            vehicles: report.vehicles,
+            exits: report.exits,
+            receipts: report.receipts,
+            occupancy_snapshots: report.occupancy_snapshots,
+            summarized_days: report.summarized_days,
+            oldest_exit_time: report.oldest_exit_time,
+        }
+    }
 }
@@ -2,17 +2,18 @@ use crate::socket::constants::SERVER_ADDRESS;
 use axum::{self, Router};
 use socketioxide::layer::SocketIoLayer;
 use tokio::{net::TcpListener, signal};
+use tracing::info;
 
-pub async fn configure_axum_server(layer: SocketIoLayer) {
-    let app = Router::new().layer(layer);
+pub async fn configure_axum_server(layer: SocketIoLayer, api: Router) {
+    let app = Router::new().nest("/api", api).layer(layer);
     let listener = TcpListener::bind(SERVER_ADDRESS).await.unwrap();
 
     let shutdown = async {
         signal::ctrl_c().await.unwrap();
-        println!("Shutting down...");
+        info!("shutting down");
     };
 
-    println!("Server running on: http://{}", SERVER_ADDRESS);
+    info!(address = SERVER_ADDRESS, "server running");
     axum::serve(listener, app)
         .with_graceful_shutdown(shutdown)
         .await
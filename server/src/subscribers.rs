@@ -0,0 +1,232 @@
+use crate::{
+    database::Database,
+    models::subscriber::{Subscriber, Subscription},
+    socket::{
+        commands,
+        payloads::{IdentifyVehiclePayload, NewSubscriberPayload, SubscriberPayload},
+    },
+};
+use chrono::Utc;
+use rusqlite::Error;
+use socketioxide::SocketIo;
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::MutexGuard,
+};
+use tracing::{info, warn};
+
+pub enum SubscriberError {
+    InvalidCredential,
+    InvalidValidity,
+    InvalidFloor,
+    InvalidSpot,
+    InvalidRate,
+    DuplicateCredential,
+    NotFound,
+    NoVehicle,
+    NotRecognized,
+    Database(Error),
+}
+
+impl From<Error> for SubscriberError {
+    fn from(error: Error) -> Self {
+        Self::Database(error)
+    }
+}
+
+impl Display for SubscriberError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCredential => write!(f, "the plate or card id can't be empty"),
+            Self::InvalidValidity => write!(f, "the pass must end after it starts"),
+            Self::InvalidFloor => write!(f, "there is no such floor"),
+            Self::InvalidSpot => write!(f, "there is no such spot"),
+            Self::InvalidRate => write!(f, "the rate can't be negative"),
+            Self::DuplicateCredential => write!(f, "the plate or card id is already registered"),
+            Self::NotFound => write!(f, "there is no such subscriber"),
+            Self::NoVehicle => write!(f, "there is no vehicle parked in the spot"),
+            Self::NotRecognized => {
+                write!(
+                    f,
+                    "no valid pass for this floor matches the plate or card id"
+                )
+            }
+            Self::Database(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+pub fn add(
+    database: &mut Database,
+    request: &NewSubscriberPayload,
+) -> Result<Subscriber, SubscriberError> {
+    let now = Utc::now().timestamp();
+    let valid_from = request.valid_from.unwrap_or(now);
+
+    if request.credential.trim().is_empty() {
+        return Err(SubscriberError::InvalidCredential);
+    }
+
+    if request.valid_until <= valid_from {
+        return Err(SubscriberError::InvalidValidity);
+    }
+
+    if request
+        .allowed_floors
+        .iter()
+        .any(|floor_number| !(0..3).contains(floor_number))
+    {
+        return Err(SubscriberError::InvalidFloor);
+    }
+
+    if let Some(spot) = &request.reserved_spot {
+        if !(0..3).contains(&spot.floor_number) || !(0..8).contains(&spot.spot_number) {
+            return Err(SubscriberError::InvalidSpot);
+        }
+    }
+
+    if request.rate_per_minute < 0.0 {
+        return Err(SubscriberError::InvalidRate);
+    }
+
+    if database.find_subscriber(&request.credential)?.is_some() {
+        return Err(SubscriberError::DuplicateCredential);
+    }
+
+    let subscriber = database.add_subscriber(request, valid_from, now)?;
+
+    info!(
+        subscriber_id = subscriber.id,
+        valid_from,
+        valid_until = subscriber.valid_until,
+        rate_per_minute = subscriber.rate_per_minute,
+        "subscriber added"
+    );
+
+    Ok(subscriber)
+}
+
+pub fn remove(database: &mut Database, id: i64) -> Result<(), SubscriberError> {
+    if database.remove_subscriber(id)? == 0 {
+        return Err(SubscriberError::NotFound);
+    }
+
+    info!(subscriber_id = id, "subscriber removed");
+
+    Ok(())
+}
+
+pub fn list(database: &Database) -> Result<Vec<SubscriberPayload>, SubscriberError> {
+    Ok(database
+        .get_subscribers()?
+        .into_iter()
+        .map(SubscriberPayload::from)
+        .collect())
+}
+
+// The contract a vehicle entering with the credential is billed by. Passes that
+// expired or don't cover the floor are billed as a regular vehicle
+pub fn recognize(
+    database: &Database,
+    credential: &str,
+    floor_number: i32,
+    timestamp: i64,
+) -> Result<Option<Subscription>, Error> {
+    let Some(subscriber) = database.find_subscriber(credential)? else {
+        return Ok(None);
+    };
+
+    if !subscriber.is_valid_at(timestamp) {
+        warn!(
+            subscriber_id = subscriber.id,
+            valid_until = subscriber.valid_until,
+            "subscriber pass is not valid"
+        );
+
+        return Ok(None);
+    }
+
+    if !subscriber.allows_floor(floor_number) {
+        warn!(
+            subscriber_id = subscriber.id,
+            floor = floor_number,
+            "subscriber pass doesn't cover the floor"
+        );
+
+        return Ok(None);
+    }
+
+    Ok(Some(Subscription {
+        subscriber_id: subscriber.id,
+        rate_per_minute: subscriber.rate_per_minute,
+    }))
+}
+
+// Only reported, the spot can't be kept free without a barrier of its own
+pub fn check_reserved_spot(
+    database: &Database,
+    floor_number: i32,
+    spot_number: i32,
+    subscription: Option<Subscription>,
+    timestamp: i64,
+) -> Result<(), Error> {
+    let holder = database.get_subscribers()?.into_iter().find(|subscriber| {
+        subscriber.reserved_spot == Some((floor_number, spot_number))
+            && subscriber.is_valid_at(timestamp)
+    });
+
+    if let Some(holder) = holder {
+        if subscription.map(|subscription| subscription.subscriber_id) != Some(holder.id) {
+            warn!(
+                floor = floor_number,
+                spot = spot_number,
+                subscriber_id = holder.id,
+                "reserved spot taken by another vehicle"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Attaches a pass to a vehicle that is already parked, for the entries without
+// a reader or when the operator checks the pass by hand
+pub fn identify(
+    io: &SocketIo,
+    database: &mut MutexGuard<Database>,
+    request: &IdentifyVehiclePayload,
+) -> Result<Subscription, SubscriberError> {
+    if !(0..3).contains(&request.floor_number) || !(0..8).contains(&request.spot_number) {
+        return Err(SubscriberError::InvalidSpot);
+    }
+
+    let vehicle = database
+        .get_floor(request.floor_number)?
+        .spots
+        .swap_remove(request.spot_number as usize)
+        .parked_vehicle
+        .ok_or(SubscriberError::NoVehicle)?;
+
+    let subscription = recognize(
+        database,
+        &request.credential,
+        request.floor_number,
+        vehicle.entry_time,
+    )?
+    .ok_or(SubscriberError::NotRecognized)?;
+
+    database.set_vehicle_subscription(vehicle.id, &subscription)?;
+
+    info!(
+        floor = request.floor_number,
+        spot = request.spot_number,
+        vehicle_id = vehicle.id,
+        subscriber_id = subscription.subscriber_id,
+        "vehicle identified as a subscriber"
+    );
+
+    // The app bills the vehicle by the contract from now on
+    commands::broadcast_parking_lot_state(io, database);
+
+    Ok(subscription)
+}
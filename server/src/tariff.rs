@@ -0,0 +1,68 @@
+use crate::models::receipt::{ReceiptLine, ReceiptLineKind, VehicleExit};
+
+// Same price the app shows for the vehicles still parked
+pub const RATE_PER_MINUTE: f64 = 0.1;
+
+// Itemized charges of a stay: the regular fee, the subscriber contract as a
+// discount (or an extra when the contract costs more) and the overstay surcharge
+pub fn charges(exit: &VehicleExit) -> Vec<ReceiptLine> {
+    let minutes = (exit.exit_time - exit.entry_time) / 60;
+
+    let mut lines = vec![ReceiptLine {
+        kind: ReceiptLineKind::Charge,
+        description: "Estacionamento".to_string(),
+        quantity: minutes,
+        unit_price: RATE_PER_MINUTE,
+        amount: round_cents(minutes as f64 * RATE_PER_MINUTE),
+    }];
+
+    if let Some(subscription) = exit.subscription {
+        let difference = subscription.rate_per_minute - RATE_PER_MINUTE;
+
+        if subscription.rate_per_minute == 0.0 {
+            lines.push(ReceiptLine {
+                kind: ReceiptLineKind::Discount,
+                description: "Assinante isento".to_string(),
+                quantity: minutes,
+                unit_price: -RATE_PER_MINUTE,
+                amount: -lines[0].amount,
+            });
+        } else if difference != 0.0 {
+            lines.push(ReceiptLine {
+                kind: if difference < 0.0 {
+                    ReceiptLineKind::Discount
+                } else {
+                    ReceiptLineKind::Charge
+                },
+                description: "Tarifa de assinante".to_string(),
+                quantity: minutes,
+                unit_price: difference,
+                amount: round_cents(minutes as f64 * difference),
+            });
+        }
+    }
+
+    if let Some(overstay) = exit.overstay {
+        let extra_minutes = minutes - overstay.max_minutes;
+
+        if extra_minutes > 0 && overstay.surcharge_per_minute > 0.0 {
+            lines.push(ReceiptLine {
+                kind: ReceiptLineKind::Charge,
+                description: "Permanencia excessiva".to_string(),
+                quantity: extra_minutes,
+                unit_price: overstay.surcharge_per_minute,
+                amount: round_cents(extra_minutes as f64 * overstay.surcharge_per_minute),
+            });
+        }
+    }
+
+    lines
+}
+
+pub fn total(lines: &[ReceiptLine]) -> f64 {
+    round_cents(lines.iter().map(|line| line.amount).sum())
+}
+
+pub fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
@@ -0,0 +1,218 @@
+use super::signature::{self, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER};
+use crate::{config::WebhooksConfig, database::Database, models::webhook::WebhookDelivery};
+use chrono::Utc;
+use reqwest::{header::CONTENT_TYPE, Client};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time;
+use tracing::{debug, error, warn};
+
+const DELIVERY_BATCH_SIZE: i64 = 16;
+
+pub async fn run(config: WebhooksConfig, database: Arc<Mutex<Database>>) {
+    let client = match Client::builder()
+        .timeout(Duration::from_secs(config.request_timeout_secs))
+        .build()
+    {
+        Ok(client) => client,
+        Err(error) => {
+            error!(%error, "failed to create the webhook client, webhooks disabled");
+            return;
+        }
+    };
+
+    let mut interval = time::interval(Duration::from_millis(config.poll_interval_millis));
+
+    // A database error only skips this round, the deliveries stay queued and are
+    // tried again on the next one
+    loop {
+        interval.tick().await;
+
+        // Never hold the database lock while waiting on the network
+        let deliveries = Database::lock(&database)
+            .get_due_webhook_deliveries(Utc::now().timestamp(), DELIVERY_BATCH_SIZE);
+
+        let deliveries = match deliveries {
+            Ok(deliveries) => deliveries,
+            Err(error) => {
+                error!(%error, "failed to read the due webhook deliveries");
+                continue;
+            }
+        };
+
+        for delivery in deliveries {
+            let result = send(&client, &config, &delivery).await;
+            let mut database = Database::lock(&database);
+
+            let recorded = match result {
+                Ok(()) => {
+                    debug!(event = %delivery.event, url = %delivery.url, "webhook delivered");
+                    database.mark_webhook_delivered(delivery.id)
+                }
+                Err(error) => {
+                    let next_attempt_at = next_attempt_at(&config, delivery.attempts + 1);
+
+                    match next_attempt_at {
+                        Some(_) => warn!(
+                            event = %delivery.event,
+                            url = %delivery.url,
+                            attempt = delivery.attempts + 1,
+                            %error,
+                            "webhook delivery failed, will retry"
+                        ),
+                        None => error!(
+                            event = %delivery.event,
+                            url = %delivery.url,
+                            attempt = delivery.attempts + 1,
+                            %error,
+                            "webhook delivery failed permanently"
+                        ),
+                    }
+
+                    database.register_webhook_failure(delivery.id, &error, next_attempt_at)
+                }
+            };
+
+            if let Err(error) = recorded {
+                error!(
+                    delivery = delivery.id,
+                    %error,
+                    "failed to record the webhook delivery attempt"
+                );
+            }
+        }
+    }
+}
+
+async fn send(
+    client: &Client,
+    config: &WebhooksConfig,
+    delivery: &WebhookDelivery,
+) -> Result<(), String> {
+    let response = client
+        .post(&delivery.url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event)
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(
+            SIGNATURE_HEADER,
+            signature::sign(&config.secret, &delivery.body),
+        )
+        .body(delivery.body.clone())
+        .send()
+        .await
+        .map_err(|error| error.to_string())?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("receiver answered with {}", response.status()))
+    }
+}
+
+// Exponential backoff, returns None when the delivery ran out of attempts
+fn next_attempt_at(config: &WebhooksConfig, attempts: i32) -> Option<i64> {
+    if attempts >= config.max_attempts {
+        return None;
+    }
+
+    let exponent = (attempts - 1).clamp(0, 30) as u32;
+    let backoff = config
+        .initial_backoff_secs
+        .saturating_mul(2_i64.saturating_pow(exponent))
+        .min(config.max_backoff_secs);
+
+    Some(Utc::now().timestamp() + backoff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::State,
+        http::{HeaderMap, StatusCode},
+        routing::post,
+        Router,
+    };
+    use tokio::net::TcpListener;
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, String)>>>;
+
+    // Fails the first request, accepts the next ones
+    async fn receive(
+        State(received): State<Received>,
+        headers: HeaderMap,
+        body: String,
+    ) -> StatusCode {
+        let mut received = received.lock().unwrap();
+        received.push((headers, body));
+
+        if received.len() == 1 {
+            StatusCode::INTERNAL_SERVER_ERROR
+        } else {
+            StatusCode::OK
+        }
+    }
+
+    #[tokio::test]
+    async fn signs_the_body_and_retries_after_a_server_error() {
+        let received = Received::default();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let receiver = Router::new()
+            .route("/hook", post(receive))
+            .with_state(received.clone());
+
+        tokio::spawn(async move { axum::serve(listener, receiver).await.unwrap() });
+
+        let config = WebhooksConfig {
+            secret: "secret".to_string(),
+            initial_backoff_secs: 0,
+            poll_interval_millis: 10,
+            ..WebhooksConfig::default()
+        };
+        let database = Database::in_memory();
+        let body = r#"{"timestamp":1,"event":"lot_full"}"#;
+
+        database
+            .lock()
+            .unwrap()
+            .enqueue_webhook_delivery("lot_full", &url, body, Utc::now().timestamp())
+            .unwrap();
+
+        let delivery = tokio::spawn(run(config, database.clone()));
+
+        for _ in 0..200 {
+            if received.lock().unwrap().len() >= 2 {
+                break;
+            }
+
+            time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // Long enough for a third attempt, if the second wasn't recorded
+        time::sleep(Duration::from_millis(100)).await;
+        delivery.abort();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+
+        for (headers, received_body) in received.iter() {
+            assert_eq!(received_body, body);
+            assert_eq!(headers[EVENT_HEADER].to_str().unwrap(), "lot_full");
+            assert_eq!(
+                headers[SIGNATURE_HEADER].to_str().unwrap(),
+                signature::sign("secret", body)
+            );
+        }
+
+        let due = database
+            .lock()
+            .unwrap()
+            .get_due_webhook_deliveries(i64::MAX, DELIVERY_BATCH_SIZE)
+            .unwrap();
+        assert!(due.is_empty());
+    }
+}
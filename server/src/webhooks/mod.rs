@@ -0,0 +1,76 @@
+mod delivery;
+mod signature;
+
+use crate::{config::WebhooksConfig, database::Database, events::LotEvent};
+use chrono::Utc;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+#[derive(Serialize)]
+struct WebhookBody<'a> {
+    timestamp: i64,
+    #[serde(flatten)]
+    event: &'a LotEvent,
+}
+
+pub fn spawn(config: &WebhooksConfig, database: &Arc<Mutex<Database>>) {
+    if config.targets.is_empty() {
+        info!("no webhook targets configured, webhooks disabled");
+        return;
+    }
+
+    // Listen to the lot events and store a delivery for each configured target,
+    // the queue lives in the database so pending deliveries survive restarts
+    let mut receiver = database.lock().unwrap().events.subscribe();
+    let enqueue_config = config.clone();
+    let enqueue_database = database.clone();
+
+    tokio::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => enqueue(&enqueue_config, &enqueue_database, &event),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "webhooks fell behind, events were not queued");
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    // Deliver the queued webhooks, retrying with backoff
+    tokio::spawn(delivery::run(config.clone(), database.clone()));
+}
+
+fn enqueue(config: &WebhooksConfig, database: &Arc<Mutex<Database>>, event: &LotEvent) {
+    let targets = match event {
+        LotEvent::LotFull(_) => &config.targets.lot_full,
+        LotEvent::FloorClosed(_) => &config.targets.floor_closed,
+        LotEvent::ControllerDisconnected(_) => &config.targets.controller_disconnected,
+        LotEvent::VehicleOverstayed(_) => &config.targets.vehicle_overstayed,
+        LotEvent::ClockSkewDetected(_) => &config.targets.clock_skew_detected,
+        LotEvent::BarrierFault(_) => &config.targets.barrier_fault,
+        LotEvent::SensorReport(_) => &config.targets.sensor_report,
+        _ => return,
+    };
+
+    if targets.is_empty() {
+        return;
+    }
+
+    let now = Utc::now().timestamp();
+    let body = serde_json::to_string(&WebhookBody {
+        timestamp: now,
+        event,
+    })
+    .unwrap();
+
+    let mut database = database.lock().unwrap();
+
+    for url in targets {
+        database
+            .enqueue_webhook_delivery(event.name(), url, &body, now)
+            .unwrap();
+    }
+}